@@ -0,0 +1,247 @@
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use console::style;
+use serde::Serialize;
+
+use crate::cache::load_cache;
+use crate::common::*;
+use crate::magic_hat::*;
+use crate::mint::{mint_parallel, MintOptions, MintRecord};
+use crate::pdas::get_collection_pda;
+use crate::utils::*;
+
+pub struct AirdropArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub magic_hat: Option<String>,
+    pub list: String,
+    pub results: String,
+    pub parallel: usize,
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// Progress of the airdrop to a single address.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AirdropResult {
+    pub requested: u64,
+    pub minted: Vec<MintRecord>,
+}
+
+pub type AirdropResults = IndexMap<String, AirdropResult>;
+
+pub async fn process_airdrop(args: AirdropArgs) -> Result<()> {
+    let laddu_config = Arc::new(laddu_setup(args.keypair, args.rpc_url)?);
+    let client = setup_client(&laddu_config)?;
+    let program = client.program(MAGIC_HAT_ID);
+
+    // the magic hat id specified takes precedence over the one from the cache
+
+    let magic_hat_id = match args.magic_hat {
+        Some(magic_hat_id) => magic_hat_id,
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.magic_hat
+        }
+    };
+
+    let magichat_pubkey = match Pubkey::from_str(&magic_hat_id) {
+        Ok(magichat_pubkey) => magichat_pubkey,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat_id);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    println!(
+        "{} {}Loading airdrop list",
+        style("[1/3]").bold().dim(),
+        PAPER_EMOJI
+    );
+
+    let list: IndexMap<String, u64> = match File::open(&args.list) {
+        Ok(file) => serde_json::from_reader(file)
+            .map_err(|err| anyhow!("Failed to parse airdrop list '{}': {}", args.list, err))?,
+        Err(err) => {
+            let error = anyhow!("Failed to open airdrop list '{}': {}", args.list, err);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    let mut results = load_airdrop_results(&args.results)?;
+    // (address, options) of each pending mint
+    let mut jobs: Vec<(String, MintOptions)> = Vec::new();
+
+    for (address, quantity) in &list {
+        let receiver = match Pubkey::from_str(address) {
+            Ok(receiver) => receiver,
+            Err(_) => {
+                let error = anyhow!("Invalid airdrop address: {}", address);
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+
+        let result = results.entry(address.to_string()).or_default();
+        result.requested = *quantity;
+
+        let remaining = quantity.saturating_sub(result.minted.len() as u64);
+
+        for _ in 0..remaining {
+            jobs.push((
+                address.to_string(),
+                MintOptions {
+                    receiver: Some(receiver),
+                    ..MintOptions::default()
+                },
+            ));
+        }
+    }
+
+    println!(
+        "{} address(es), {} NFT(s) remaining to airdrop",
+        list.len(),
+        jobs.len()
+    );
+
+    if jobs.is_empty() {
+        println!("\nAll airdrops completed.");
+        return Ok(());
+    }
+
+    println!(
+        "\n{} {}Loading Magic Hat",
+        style("[2/3]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let magic_hat_state = Arc::new(get_magic_hat_state(&laddu_config, &magichat_pubkey)?);
+    let collection_pda_info = Arc::new(get_collection_pda(&magichat_pubkey, &program).ok());
+
+    pb.finish_with_message("Done");
+
+    let available = magic_hat_state.data.items_available - magic_hat_state.items_redeemed;
+
+    if jobs.len() as u64 > available {
+        let error = anyhow!(
+            "{} item(s) available, airdrop requires {}",
+            available,
+            jobs.len()
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    println!(
+        "\n{} {}Airdropping NFTs (Ctrl+C to abort)",
+        style("[3/3]").bold().dim(),
+        MAGICHAT_EMOJI
+    );
+
+    let pb = progress_bar_with_style(jobs.len() as u64);
+    let mut errors = Vec::new();
+
+    // clear the interruption handler value ahead of the airdrop
+    args.interrupted.store(false, Ordering::SeqCst);
+
+    let (addresses, options): (Vec<String>, Vec<MintOptions>) = jobs.into_iter().unzip();
+
+    let skipped = mint_parallel(
+        Arc::clone(&laddu_config),
+        magichat_pubkey,
+        magic_hat_state,
+        collection_pda_info,
+        options,
+        args.parallel,
+        |index, result| {
+            let address = &addresses[index];
+
+            match result {
+                Ok(record) => {
+                    if let Some(result) = results.get_mut(address) {
+                        result.minted.push(record);
+                    }
+                    // saves the progress after each mint
+                    save_airdrop_results(&args.results, &results)?;
+                    pb.inc(1);
+                }
+                Err(err) => {
+                    error!("Airdrop to {} failed: {:?}", address, err);
+                    errors.push(format!("{}: {}", address, err));
+                }
+            }
+
+            Ok(!args.interrupted.load(Ordering::SeqCst))
+        },
+    )
+    .await?;
+
+    if args.interrupted.load(Ordering::SeqCst) {
+        pb.abandon_with_message(format!("{}", style("Airdrop aborted ").red().bold()));
+        return Err(anyhow!(
+            "Airdrop interrupted, re-run the command to airdrop the remaining NFTs"
+        ));
+    }
+
+    if skipped > 0 {
+        errors.push(format!("Magic Hat is empty: {} mint(s) not sent", skipped));
+    }
+
+    if !errors.is_empty() {
+        pb.abandon_with_message(format!("{}", style("Airdrop failed ").red().bold()));
+
+        let mut message = format!(
+            "Failed to airdrop all NFTs, {} error(s) occurred:",
+            errors.len()
+        );
+        for err in errors {
+            message.push_str(&style("\n=> ").dim().to_string());
+            message.push_str(&err);
+        }
+        message.push_str("\n\nRe-run the command to airdrop the remaining NFTs.");
+
+        return Err(anyhow!(message));
+    }
+
+    pb.finish_with_message(format!("{}", style("Airdrop successful ").green().bold()));
+    println!(
+        "\nAirdrop results recorded to {}",
+        style(&args.results).bold()
+    );
+
+    Ok(())
+}
+
+/// Load the airdrop results file (if any).
+pub fn load_airdrop_results(path: &str) -> Result<AirdropResults> {
+    let path = Path::new(path);
+
+    if !path.exists() {
+        return Ok(AirdropResults::new());
+    }
+
+    let file = File::open(path)?;
+    serde_json::from_reader(file)
+        .map_err(|err| anyhow!("Failed to parse airdrop results file: {}", err))
+}
+
+/// Save the airdrop results file atomically, so an interruption never leaves
+/// a partially written file.
+pub fn save_airdrop_results(path: &str, results: &AirdropResults) -> Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(results)?.as_bytes())
+}
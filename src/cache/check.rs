@@ -0,0 +1,313 @@
+use console::style;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use std::{
+    collections::HashSet,
+    fmt,
+    io::{BufRead, BufReader},
+};
+use url::Url;
+
+use crate::cache::*;
+use crate::common::*;
+use crate::config::parser::get_config_data;
+use crate::validate::format_ranges;
+
+pub struct CacheCheckArgs {
+    pub cache: String,
+    pub config: Option<String>,
+    pub fix: bool,
+}
+
+/// A problem found in the cache file, for an item (`index`) or for the whole file.
+struct Finding {
+    index: Option<String>,
+    problem: String,
+    fixable: bool,
+}
+
+/// Cache file as written on disk. Items are kept as a list, so that duplicated
+/// indices are not collapsed when parsing.
+#[derive(Deserialize)]
+struct RawCache {
+    #[serde(default)]
+    version: Value,
+    program: Value,
+    items: RawItems,
+}
+
+struct RawItems(Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for RawItems {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ItemsVisitor;
+
+        impl<'de> Visitor<'de> for ItemsVisitor {
+            type Value = RawItems;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of cache items")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawItems, A::Error> {
+                let mut items = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    items.push(entry);
+                }
+                Ok(RawItems(items))
+            }
+        }
+
+        deserializer.deserialize_map(ItemsVisitor)
+    }
+}
+
+/// Checks the cache file without any network access: indices, names, metadata
+/// links, the magic hat address and the on-chain flags. With `--fix`, whitespace
+/// around names and links is trimmed and on-chain flags written as strings are
+/// converted to booleans.
+pub fn process_cache_check(args: CacheCheckArgs) -> Result<()> {
+    let mut pretty = false;
+    let mut raw: RawCache = if is_sqlite_cache(&args.cache) {
+        // indices are unique in a SQLite cache, the items are checked as JSON
+        let cache = load_cache(&args.cache, false)?;
+        serde_json::from_value(serde_json::to_value(&cache)?)?
+    } else {
+        let file = match File::open(&args.cache) {
+            Ok(file) => file,
+            Err(err) => {
+                let error = CacheError::FailedToOpenCacheFile(args.cache.clone(), err.to_string());
+                error!("{:?}", error);
+                return Err(error.into());
+            }
+        };
+
+        let mut reader = BufReader::new(file);
+        pretty = is_pretty(reader.fill_buf()?);
+
+        match serde_json::from_reader(reader) {
+            Ok(raw) => raw,
+            Err(err) => {
+                let error = CacheError::CacheFileWrongFormat(err.to_string());
+                error!("{:?}", error);
+                return Err(error.into());
+            }
+        }
+    };
+
+    // the expected number of items comes from the config, when there is one, or
+    // from the cache when the config number is auto
+    let number = match &args.config {
+        Some(config) => get_config_data(config)?.number,
+        None if Path::new(DEFAULT_CONFIG).exists() => get_config_data(DEFAULT_CONFIG)?.number,
+        None => None,
+    };
+    let number = number
+        .or_else(|| raw.program.get("number").and_then(Value::as_u64))
+        .unwrap_or(raw.items.0.len() as u64) as usize;
+
+    println!(
+        "{} {}Checking cache file '{}' ({} item(s), {} expected)",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        args.cache,
+        raw.items.0.len(),
+        number
+    );
+
+    // writing the items back would silently drop duplicated indices, so nothing is
+    // fixed until they are removed by hand
+    let mut seen = HashSet::new();
+    let duplicated = raw
+        .items
+        .0
+        .iter()
+        .any(|(index, _)| !seen.insert(index.clone()));
+    let fix = args.fix && !duplicated;
+
+    let mut findings = Vec::new();
+    let mut add = |index: Option<&str>, problem: String, fixable: bool| {
+        findings.push(Finding {
+            index: index.map(String::from),
+            problem,
+            fixable,
+        })
+    };
+
+    let magic_hat = raw.program["magicHat"].as_str().unwrap_or_default();
+    if !magic_hat.is_empty() && Pubkey::from_str(magic_hat).is_err() {
+        add(
+            None,
+            format!("program.magicHat '{}' is not a valid pubkey", magic_hat),
+            false,
+        );
+    }
+
+    seen.clear();
+    let mut links: HashMap<String, String> = HashMap::new();
+
+    for (index, item) in raw.items.0.iter_mut() {
+        let index = index.as_str();
+
+        match index.parse::<usize>() {
+            Ok(i) if i >= number => add(
+                Some(index),
+                format!("index is out of range (0-{})", number.saturating_sub(1)),
+                false,
+            ),
+            Ok(_) => {}
+            Err(_) => add(Some(index), "index is not a number".to_string(), false),
+        }
+
+        if !seen.insert(index.to_string()) {
+            add(
+                Some(index),
+                "index is present more than once".to_string(),
+                false,
+            );
+        }
+
+        let item = match item.as_object_mut() {
+            Some(item) => item,
+            None => {
+                add(Some(index), "item is not an object".to_string(), false);
+                continue;
+            }
+        };
+
+        // whitespace around the name and link is trimmed by --fix
+        for field in ["name", "metadata_link"] {
+            if let Some(Value::String(value)) = item.get_mut(field) {
+                let trimmed = value.trim().to_string();
+                if trimmed != *value {
+                    add(
+                        Some(index),
+                        format!(
+                            "{} '{}' has leading/trailing whitespace",
+                            field,
+                            value.escape_debug()
+                        ),
+                        true,
+                    );
+                    if fix {
+                        *value = trimmed;
+                    }
+                }
+            }
+        }
+
+        match item.get("name").and_then(Value::as_str) {
+            Some(name) if name.len() > MAX_NAME_LENGTH => add(
+                Some(index),
+                format!("name is longer than {} bytes", MAX_NAME_LENGTH),
+                false,
+            ),
+            Some(_) => {}
+            None => add(Some(index), "missing name".to_string(), false),
+        }
+
+        match item.get("metadata_link").and_then(Value::as_str) {
+            // items not uploaded yet have no link
+            Some("") => {}
+            Some(link) => {
+                if Url::parse(link).is_err() {
+                    add(
+                        Some(index),
+                        format!("metadata link '{}' is not a URL", link),
+                        false,
+                    );
+                }
+                if link.len() > MAX_URI_LENGTH {
+                    add(
+                        Some(index),
+                        format!("metadata link is longer than {} bytes", MAX_URI_LENGTH),
+                        false,
+                    );
+                }
+                if let Some(other) = links.insert(link.to_string(), index.to_string()) {
+                    add(
+                        Some(index),
+                        format!("metadata link is also used by item {}", other),
+                        false,
+                    );
+                }
+            }
+            None => add(Some(index), "missing metadata link".to_string(), false),
+        }
+
+        match item.get("onChain") {
+            Some(Value::Bool(_)) => {}
+            Some(Value::String(flag)) if matches!(flag.as_str(), "true" | "false") => {
+                let flag = flag == "true";
+                add(
+                    Some(index),
+                    format!("onChain is the string \"{}\", not a boolean", flag),
+                    true,
+                );
+                if fix {
+                    item.insert("onChain".to_string(), Value::Bool(flag));
+                }
+            }
+            _ => add(Some(index), "onChain is not a boolean".to_string(), false),
+        }
+    }
+
+    let missing: Vec<usize> = (0..number)
+        .filter(|i| !seen.contains(&i.to_string()))
+        .collect();
+    if !missing.is_empty() {
+        add(
+            None,
+            format!("missing item(s) {}", format_ranges(&missing)),
+            false,
+        );
+    }
+
+    let fixable = findings.iter().filter(|finding| finding.fixable).count();
+    let fixed = if fix { fixable } else { 0 };
+
+    if !findings.is_empty() {
+        println!();
+        for finding in &findings {
+            let status = if fix && finding.fixable {
+                style("FIXED").green().bold()
+            } else {
+                style("FAIL").red().bold()
+            };
+            match &finding.index {
+                Some(index) => println!("{} item {}: {}", status, index, finding.problem),
+                None => println!("{} {}", status, finding.problem),
+            }
+        }
+    }
+
+    if fixed > 0 {
+        let value = json!({
+            "version": raw.version,
+            "program": raw.program,
+            "items": raw.items.0.into_iter().collect::<serde_json::Map<String, Value>>(),
+        });
+        let mut cache: Cache = serde_json::from_value(migrate_cache(value)?)?;
+        cache.file_path = args.cache.clone();
+        cache.pretty = pretty;
+        cache.sync_file()?;
+
+        println!("\n{} fix(es) saved to '{}'.", fixed, args.cache);
+    } else if args.fix && duplicated {
+        println!(
+            "\n{} remove the duplicated indices by hand before using --fix",
+            style("Warning:").yellow().bold()
+        );
+    }
+
+    println!("\n{} problem(s) found, {} fixed.", findings.len(), fixed);
+
+    let remaining = findings.len() - fixed;
+    if remaining > 0 {
+        if !args.fix && fixable > 0 {
+            println!("Re-run with --fix to fix {} of them.", fixable);
+        }
+        return Err(anyhow!("{} problem(s) found in the cache file.", remaining));
+    }
+
+    Ok(())
+}
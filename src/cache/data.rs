@@ -0,0 +1,516 @@
+use crate::cache::{is_sqlite_cache, load_sqlite_cache, write_sqlite_cache};
+use crate::common::*;
+use crate::pdas::find_magic_hat_creator_pda;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use console::style;
+use magic_hat::ConfigLine;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// Version of the cache file format written by this binary.
+pub const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Cache {
+    /// Format version, missing in the caches written before versioning (version 0).
+    #[serde(default)]
+    pub version: u32,
+    pub program: CacheProgram,
+    pub items: CacheItems,
+    #[serde(skip_deserializing, skip_serializing)]
+    pub file_path: String,
+    /// Items (in JSON) as last written to a SQLite cache, used to only write the
+    /// changed rows.
+    #[serde(skip_deserializing, skip_serializing)]
+    pub synced: HashMap<String, String>,
+    /// Whether the JSON is written indented; the formatting of the file is kept
+    /// when the cache is loaded.
+    #[serde(skip_deserializing, skip_serializing)]
+    pub pretty: bool,
+    /// Whether the `.bak` copy of the file was made; it is only made on the first
+    /// write, so it holds the cache as it was before the command.
+    #[serde(skip_deserializing, skip_serializing)]
+    pub backed_up: bool,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Cache {
+            version: CACHE_VERSION,
+            program: CacheProgram::new(),
+            items: CacheItems::new(),
+            file_path: String::new(),
+            synced: HashMap::new(),
+            pretty: false,
+            backed_up: false,
+        }
+    }
+
+    /// Writes the cache atomically: the content is written to a temporary file in
+    /// the same directory and renamed over the target, so the file on disk is always
+    /// complete. The version before the first write is kept as a `.bak` file. The
+    /// JSON is streamed to the file, compact unless `pretty` is set.
+    pub fn write_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        if is_sqlite_cache(path) {
+            return write_sqlite_cache(self, path);
+        }
+
+        let tmp_path = with_suffix(path, ".tmp");
+        let mut writer = BufWriter::new(fs::File::create(&tmp_path)?);
+        if self.pretty {
+            serde_json::to_writer_pretty(&mut writer, &self)?;
+        } else {
+            serde_json::to_writer(&mut writer, &self)?;
+        }
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        if !self.backed_up && path.exists() {
+            fs::copy(path, with_suffix(path, ".bak"))?;
+        }
+        self.backed_up = true;
+        fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+
+    pub fn sync_file(&mut self) -> Result<()> {
+        let file_path = self.file_path.clone();
+        self.write_to_file(Path::new(&file_path))
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheProgram {
+    #[serde(rename = "magicHat")]
+    pub magic_hat: String,
+    #[serde(rename = "magicHatCreator")]
+    pub magic_hat_creator: String,
+    #[serde(
+        rename = "collectionMint",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub collection_mint: Option<String>,
+    /// Number of items detected by upload when the config number is `auto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<u64>,
+}
+
+impl CacheProgram {
+    pub fn new() -> Self {
+        CacheProgram {
+            magic_hat: String::new(),
+            magic_hat_creator: String::new(),
+            collection_mint: None,
+            number: None,
+        }
+    }
+
+    pub fn new_from_cm(magic_hat: &Pubkey) -> Self {
+        let (magic_hat_creator_pda, _creator_bump) = find_magic_hat_creator_pda(magic_hat);
+        CacheProgram {
+            magic_hat: magic_hat.to_string(),
+            magic_hat_creator: magic_hat_creator_pda.to_string(),
+            collection_mint: None,
+            number: None,
+        }
+    }
+}
+
+impl Default for CacheProgram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheItems(pub IndexMap<String, CacheItem>);
+
+impl CacheItems {
+    pub fn new() -> Self {
+        CacheItems(IndexMap::new())
+    }
+}
+impl Default for CacheItems {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CacheItem {
+    pub name: String,
+    #[serde(default = "String::default")]
+    pub image_hash: String,
+    pub image_link: String,
+    #[serde(default = "String::default")]
+    pub metadata_hash: String,
+    pub metadata_link: String,
+    #[serde(rename = "onChain")]
+    pub on_chain: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation_link: Option<String>,
+    /// URIs of the additional uploaded files (from `properties.files`), by file name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_files: Option<IndexMap<String, String>>,
+    /// Storage receipt of the image upload (Bundlr transaction id, IPFS CID or S3 ETag)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_receipt: Option<String>,
+    /// Storage receipt of the metadata upload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_receipt: Option<String>,
+}
+
+/// Storage receipts of the uploads of an item, as reported by show and verify.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemReceipts {
+    pub index: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<String>,
+}
+
+impl CacheItem {
+    /// Receipts of the item, if any of its uploads recorded one.
+    pub fn receipts(&self, index: &str) -> Option<ItemReceipts> {
+        if self.image_receipt.is_none() && self.metadata_receipt.is_none() {
+            return None;
+        }
+
+        Some(ItemReceipts {
+            index: index.to_string(),
+            image: self.image_receipt.clone(),
+            metadata: self.metadata_receipt.clone(),
+        })
+    }
+
+    pub fn into_config_line(&self) -> Option<ConfigLine> {
+        if !self.on_chain {
+            Some(ConfigLine {
+                name: self.name.clone(),
+                uri: self.metadata_link.clone(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+pub fn load_cache(cache_file_path: &str, create: bool) -> Result<Cache> {
+    let cache_file_path = Path::new(cache_file_path);
+    if !cache_file_path.exists() {
+        if create {
+            // if the cache file does not exist, creates a new Cache object
+            let mut cache = Cache::new();
+            cache.file_path = path_to_string(cache_file_path)?;
+            Ok(cache)
+        } else {
+            let cache_file_string = path_to_string(cache_file_path)?;
+            let error = CacheError::CacheFileNotFound(cache_file_string).into();
+            error!("{:?}", error);
+            Err(error)
+        }
+    } else {
+        info!("Cache exists, loading...");
+
+        if is_sqlite_cache(cache_file_path) {
+            let mut cache = match load_sqlite_cache(cache_file_path) {
+                Ok(cache) => cache,
+                Err(error) => {
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+            };
+            cache.file_path = path_to_string(cache_file_path)?;
+            return Ok(cache);
+        }
+
+        let file = match File::open(cache_file_path) {
+            Ok(file) => file,
+            Err(err) => {
+                let cache_file_string = path_to_string(cache_file_path)?;
+                let error =
+                    CacheError::FailedToOpenCacheFile(cache_file_string, err.to_string()).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+
+        let mut reader = BufReader::new(file);
+        let pretty = is_pretty(reader.fill_buf()?);
+
+        let value: Value = match serde_json::from_reader(reader) {
+            Ok(value) => value,
+            Err(err) if err.is_eof() => {
+                let cache_file_string = path_to_string(cache_file_path)?;
+
+                match latest_backup(cache_file_path) {
+                    Some(backup) => println!(
+                        "{} the most recent backup is '{}', review it and copy it over '{}'",
+                        style("Recovery:").yellow().bold(),
+                        backup.display(),
+                        cache_file_string
+                    ),
+                    None => println!(
+                        "{} no backup found, run `laddu cache import` to rebuild the cache",
+                        style("Recovery:").yellow().bold()
+                    ),
+                }
+
+                let error = CacheError::CacheFileTruncated(cache_file_string).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+            Err(err) => {
+                let error = CacheError::CacheFileWrongFormat(err.to_string()).into();
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+
+        // older caches are migrated in memory, the file is only updated on the next sync
+        let mut cache: Cache = match migrate_cache(value).and_then(|value| {
+            serde_json::from_value(value)
+                .map_err(|err| CacheError::CacheFileWrongFormat(err.to_string()).into())
+        }) {
+            Ok(cache) => cache,
+            Err(error) => {
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+        cache.file_path = path_to_string(cache_file_path)?;
+        cache.pretty = pretty;
+
+        Ok(cache)
+    }
+}
+
+/// Whether the JSON was written indented, based on the start of the file.
+pub fn is_pretty(start: &[u8]) -> bool {
+    start
+        .iter()
+        .skip_while(|byte| byte.is_ascii_whitespace())
+        .skip(1)
+        .take_while(|byte| byte.is_ascii_whitespace())
+        .any(|byte| *byte == b'\n')
+}
+
+/// Migrates the JSON of a cache file to the current version, one version at a time.
+/// Caches written by a newer version of laddu are rejected.
+pub fn migrate_cache(mut value: Value) -> Result<Value> {
+    if !value.is_object() || !value["program"].is_object() {
+        return Err(CacheError::CacheFileWrongFormat("missing program".to_string()).into());
+    }
+
+    let mut version = value["version"].as_u64().unwrap_or_default() as u32;
+
+    if version > CACHE_VERSION {
+        return Err(CacheError::CacheFileTooNew(version, CACHE_VERSION).into());
+    }
+
+    while version < CACHE_VERSION {
+        match version {
+            // version 0: items might miss the hashes and the program the creator PDA
+            0 => {
+                if let Some(items) = value["items"].as_object_mut() {
+                    for item in items.values_mut().filter_map(Value::as_object_mut) {
+                        for field in ["image_hash", "metadata_hash"] {
+                            item.entry(field).or_insert_with(|| json!(""));
+                        }
+                    }
+                }
+
+                let creator = value["program"]["magicHatCreator"]
+                    .as_str()
+                    .unwrap_or_default();
+                if creator.is_empty() {
+                    let magic_hat = value["program"]["magicHat"].as_str().unwrap_or_default();
+                    // an empty magic hat means the cache was not deployed yet
+                    value["program"]["magicHatCreator"] = match Pubkey::from_str(magic_hat) {
+                        Ok(magic_hat) => {
+                            json!(find_magic_hat_creator_pda(&magic_hat).0.to_string())
+                        }
+                        Err(_) => json!(""),
+                    };
+                }
+            }
+            _ => unreachable!("no migration for cache version {}", version),
+        }
+
+        version += 1;
+        info!("Migrated cache file to version {}", version);
+    }
+
+    value["version"] = json!(CACHE_VERSION);
+
+    Ok(value)
+}
+
+/// Appends a suffix to the file name of a path (e.g. `cache.json.bak`).
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Finds the most recently modified `.bak` file of the cache file.
+fn latest_backup(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(file_name) && name.ends_with(".bak")
+        })
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V0_LEGACY: &str = include_str!("../../tests/fixtures/cache/v0_legacy.json");
+    const V0_HASHES: &str = include_str!("../../tests/fixtures/cache/v0_hashes.json");
+    const V0_NOT_DEPLOYED: &str = include_str!("../../tests/fixtures/cache/v0_not_deployed.json");
+    const V1: &str = include_str!("../../tests/fixtures/cache/v1.json");
+    const V2_NEWER: &str = include_str!("../../tests/fixtures/cache/v2_newer.json");
+
+    fn migrate(fixture: &str) -> Result<Cache> {
+        let value = migrate_cache(serde_json::from_str(fixture)?)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    #[test]
+    fn migrate_legacy_cache() {
+        let cache = migrate(V0_LEGACY).unwrap();
+
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert_eq!(cache.items.0.len(), 2);
+
+        let item = &cache.items.0["0"];
+        assert_eq!(item.image_hash, "");
+        assert_eq!(item.metadata_hash, "");
+        assert!(item.on_chain);
+
+        // the creator PDA is derived from the magic hat address
+        let magic_hat = Pubkey::from_str(&cache.program.magic_hat).unwrap();
+        assert_eq!(
+            cache.program.magic_hat_creator,
+            find_magic_hat_creator_pda(&magic_hat).0.to_string()
+        );
+    }
+
+    #[test]
+    fn migrate_cache_with_hashes() {
+        let cache = migrate(V0_HASHES).unwrap();
+
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert_eq!(cache.items.0["0"].image_hash, "image-hash-0");
+        assert_eq!(cache.items.0["0"].metadata_hash, "metadata-hash-0");
+        // an existing creator is kept
+        assert_eq!(
+            cache.program.magic_hat_creator,
+            "11111111111111111111111111111111"
+        );
+    }
+
+    #[test]
+    fn migrate_cache_not_deployed() {
+        let cache = migrate(V0_NOT_DEPLOYED).unwrap();
+
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert_eq!(cache.program.magic_hat, "");
+        assert_eq!(cache.program.magic_hat_creator, "");
+    }
+
+    #[test]
+    fn current_cache_is_unchanged() {
+        let value: Value = serde_json::from_str(V1).unwrap();
+        assert_eq!(migrate_cache(value.clone()).unwrap(), value);
+
+        let cache = migrate(V1).unwrap();
+        assert_eq!(
+            cache.items.0["0"].image_receipt.as_deref(),
+            Some("receipt-0")
+        );
+    }
+
+    #[test]
+    fn newer_cache_is_rejected() {
+        let error = migrate(V2_NEWER).unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<CacheError>(),
+            Some(CacheError::CacheFileTooNew(2, CACHE_VERSION))
+        ));
+    }
+
+    #[test]
+    fn invalid_cache_is_rejected() {
+        assert!(migrate_cache(json!({ "items": {} })).is_err());
+        assert!(migrate_cache(json!([])).is_err());
+    }
+
+    #[test]
+    fn load_migrates_in_memory_only() {
+        let dir = std::env::temp_dir().join(format!("laddu-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.json");
+        fs::write(&path, V0_LEGACY).unwrap();
+
+        let cache = load_cache(path.to_str().unwrap(), false).unwrap();
+        assert_eq!(cache.version, CACHE_VERSION);
+        assert!(cache.pretty);
+        // the file is only written back on the next sync
+        assert_eq!(fs::read_to_string(&path).unwrap(), V0_LEGACY);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backup_is_made_once() {
+        let dir = std::env::temp_dir().join(format!("laddu-backup-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.json");
+        fs::write(&path, V1).unwrap();
+
+        let mut cache = load_cache(path.to_str().unwrap(), false).unwrap();
+        cache.items.0.clear();
+        cache.sync_file().unwrap();
+        cache.sync_file().unwrap();
+
+        // the backup still holds the cache from before the first write
+        let backup = with_suffix(&path, ".bak");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), V1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+use console::style;
+
+use crate::cache::*;
+use crate::common::*;
+
+pub struct CacheExportArgs {
+    pub cache: String,
+    pub out: String,
+    pub force: bool,
+}
+
+pub fn process_cache_export(args: CacheExportArgs) -> Result<()> {
+    convert_cache(&args.cache, &args.out, args.force)
+}
+
+/// Copies a cache file to another file, converting between JSON and SQLite based on
+/// the extension of each file.
+pub fn convert_cache(from: &str, out: &str, force: bool) -> Result<()> {
+    if Path::new(out).exists() && !force {
+        let error = anyhow!("File {} already exists, use --force to overwrite it", out);
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let mut cache = load_cache(from, false)?;
+    cache.write_to_file(Path::new(out))?;
+
+    let format = |path: &str| {
+        if is_sqlite_cache(path) {
+            "SQLite"
+        } else {
+            "JSON"
+        }
+    };
+
+    println!(
+        "{} {} ({}) to {} ({}), {} item(s)",
+        style("Cache converted from").bold(),
+        from,
+        format(from),
+        out,
+        format(out),
+        cache.items.0.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use console::style;
+
+use crate::cache::*;
+use crate::common::*;
+
+pub struct CacheFmtArgs {
+    pub cache: String,
+    pub pretty: bool,
+}
+
+/// Rewrites the cache file in place, indented (`--pretty`) or compact. Later syncs
+/// keep the chosen formatting.
+pub fn process_cache_fmt(args: CacheFmtArgs) -> Result<()> {
+    if is_sqlite_cache(&args.cache) {
+        let error = anyhow!(
+            "Cache file {} is stored in SQLite, only JSON caches can be formatted",
+            args.cache
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let mut cache = load_cache(&args.cache, false)?;
+    cache.pretty = args.pretty;
+    cache.sync_file()?;
+
+    println!(
+        "{} {} ({}, {} item(s))",
+        style("Cache formatted:").bold(),
+        args.cache,
+        if args.pretty { "pretty" } else { "compact" },
+        cache.items.0.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use console::style;
+use futures::stream::{self, StreamExt};
+
+use crate::cache::*;
+use crate::common::*;
+use crate::magic_hat::MAGIC_HAT_ID;
+use crate::pdas::get_collection_pda;
+use crate::show::{get_bitmask, is_bit_set, LOADED_BITMASK};
+use crate::utils::{progress_bar_with_style, spinner_with_style};
+use crate::verify::{decode_config_lines, fetch_magic_hat};
+
+pub struct CacheImportArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub magic_hat: Option<String>,
+    pub from: Option<String>,
+    pub out: String,
+    pub force: bool,
+    pub fetch_images: bool,
+}
+
+pub async fn process_cache_import(args: CacheImportArgs) -> Result<()> {
+    // a cache file is converted without any network access
+    let magic_hat = match (args.from, args.magic_hat) {
+        (Some(from), _) => return convert_cache(&from, &args.out, args.force),
+        (None, Some(magic_hat)) => magic_hat,
+        (None, None) => {
+            let error = anyhow!("Either --magic-hat or --from must be specified");
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    if Path::new(&args.out).exists() && !args.force {
+        let error = anyhow!(
+            "File {} already exists, use --force to overwrite it",
+            args.out
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let magichat_pubkey = match Pubkey::from_str(&magic_hat) {
+        Ok(magichat_pubkey) => magichat_pubkey,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    println!(
+        "{} {}Loading Magic Hat",
+        style(if args.fetch_images { "[1/2]" } else { "[1/1]" })
+            .bold()
+            .dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&laddu_config)?;
+    let program = client.program(MAGIC_HAT_ID);
+    let (data, magic_hat) = fetch_magic_hat(&program, &magichat_pubkey)?;
+
+    let mut cache = Cache::new();
+    cache.program = CacheProgram::new_from_cm(&magichat_pubkey);
+    cache.program.collection_mint = get_collection_pda(&magichat_pubkey, &program)
+        .ok()
+        .map(|(_, collection_pda)| collection_pda.mint.to_string());
+
+    pb.finish_with_message("Done");
+
+    let mut warnings = Vec::new();
+
+    if magic_hat.data.hidden_settings.is_some() {
+        warnings.push(
+            "Magic Hat uses hidden settings, there are no config lines to import".to_string(),
+        );
+    } else {
+        let items_available = magic_hat.data.items_available;
+        let loaded = get_bitmask(&data, items_available, LOADED_BITMASK)?;
+        let indices: Vec<usize> = (0..items_available as usize).collect();
+        let lines = decode_config_lines(&data, &indices)?;
+
+        for (index, line) in lines.into_iter().enumerate() {
+            // unwritten slots are imported as empty items, to be filled in by upload
+            let on_chain = is_bit_set(loaded, index as u64);
+
+            cache.items.0.insert(
+                index.to_string(),
+                CacheItem {
+                    name: if on_chain { line.name } else { String::new() },
+                    image_hash: String::new(),
+                    image_link: String::new(),
+                    metadata_hash: String::new(),
+                    metadata_link: if on_chain { line.uri } else { String::new() },
+                    on_chain,
+                    animation_hash: None,
+                    animation_link: None,
+                    extra_files: None,
+                    image_receipt: None,
+                    metadata_receipt: None,
+                },
+            );
+        }
+
+        let unwritten = cache.items.0.values().filter(|item| !item.on_chain).count();
+        if unwritten > 0 {
+            warnings.push(format!(
+                "{} config line(s) not written on chain, imported as empty items",
+                unwritten
+            ));
+        }
+    }
+
+    if args.fetch_images {
+        println!(
+            "\n{} {}Fetching image links",
+            style("[2/2]").bold().dim(),
+            UPLOAD_EMOJI
+        );
+
+        let failed = fetch_image_links(&mut cache).await;
+        if failed > 0 {
+            warnings.push(format!(
+                "Failed to fetch the metadata of {} item(s), their image link is empty",
+                failed
+            ));
+        }
+    } else if !cache.items.0.is_empty() {
+        warnings.push("image links are empty, use --fetch-images to fill them in".to_string());
+    }
+
+    cache.write_to_file(Path::new(&args.out))?;
+
+    println!();
+    for warning in warnings {
+        warn!("{}", warning);
+        println!("{} {}", style("Warning:").yellow().bold(), warning);
+    }
+
+    println!(
+        "\n{} {} ({} item(s))",
+        style("Cache written to").bold(),
+        style(&args.out).bold(),
+        cache.items.0.len()
+    );
+
+    Ok(())
+}
+
+/// Fills in the image links of the written items from their metadata JSON. Returns
+/// the number of items which metadata could not be fetched.
+async fn fetch_image_links(cache: &mut Cache) -> usize {
+    let http_client = HttpClient::new();
+
+    let links: Vec<(String, String)> = cache
+        .items
+        .0
+        .iter()
+        .filter(|(_, item)| item.on_chain)
+        .map(|(index, item)| (index.clone(), item.metadata_link.clone()))
+        .collect();
+
+    let pb = progress_bar_with_style(links.len() as u64);
+
+    let images: Vec<(String, Option<String>)> = stream::iter(links)
+        .map(|(index, uri)| {
+            let http_client = &http_client;
+            let pb = &pb;
+            async move {
+                let image = match http_client.get(&uri).send().await {
+                    Ok(response) => response
+                        .json::<Value>()
+                        .await
+                        .ok()
+                        .and_then(|json| json["image"].as_str().map(String::from)),
+                    Err(_) => None,
+                };
+                pb.inc(1);
+                (index, image)
+            }
+        })
+        .buffer_unordered(PARALLEL_LIMIT)
+        .collect()
+        .await;
+
+    pb.finish();
+
+    let mut failed = 0;
+    for (index, image) in images {
+        match (image, cache.items.0.get_mut(&index)) {
+            (Some(image), Some(item)) => item.image_link = image,
+            _ => failed += 1,
+        }
+    }
+
+    failed
+}
@@ -0,0 +1,150 @@
+use chrono::Utc;
+use serde::Serialize;
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    process,
+    sync::Mutex,
+};
+
+use crate::cache::with_suffix;
+use crate::common::*;
+
+/// Lock files held by this process, removed by the Ctrl-C handler before exiting.
+static HELD_LOCKS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Content of a lock file: the process holding the cache.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// When the lock was taken (RFC 3339).
+    pub timestamp: String,
+    pub command: String,
+}
+
+impl std::fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' (pid {}, since {})",
+            self.command, self.pid, self.timestamp
+        )
+    }
+}
+
+/// Advisory lock of a cache file (`<cache>.lock`), taken by the commands which
+/// write the cache so that two runs do not interleave their writes. The lock is
+/// released when dropped.
+pub struct CacheLock {
+    path: PathBuf,
+}
+
+impl CacheLock {
+    /// Takes the lock of the cache file, failing when another process holds it.
+    /// With `force_unlock`, the lock of a process which is no longer running is
+    /// cleared first.
+    pub fn acquire(cache_path: &str, command: &str, force_unlock: bool) -> Result<CacheLock> {
+        let path = with_suffix(Path::new(cache_path), ".lock");
+
+        if force_unlock {
+            force_unlock_cache(cache_path, &path)?;
+        }
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let holder = match read_holder(&path) {
+                    Some(holder) => holder.to_string(),
+                    None => "an unknown process".to_string(),
+                };
+                let error = CacheError::CacheFileLocked(cache_path.to_string(), holder);
+                error!("{:?}", error);
+                return Err(error.into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let holder = LockHolder {
+            pid: process::id(),
+            timestamp: Utc::now().to_rfc3339(),
+            command: command.to_string(),
+        };
+        file.write_all(serde_json::to_string(&holder)?.as_bytes())?;
+
+        HELD_LOCKS.lock().unwrap().push(path.clone());
+        info!("Cache lock taken: {}", path.display());
+
+        Ok(CacheLock { path })
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        HELD_LOCKS.lock().unwrap().retain(|path| *path != self.path);
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!(
+                "Failed to remove cache lock {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Removes the lock files held by this process, for when it exits without
+/// unwinding (e.g. on Ctrl-C).
+pub fn release_cache_locks() {
+    if let Ok(mut locks) = HELD_LOCKS.lock() {
+        for path in locks.drain(..) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn force_unlock_cache(cache_path: &str, path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if let Some(holder) = read_holder(path) {
+        if is_running(holder.pid) {
+            let error = anyhow!(
+                "Cache file '{}' is locked by {}, which is still running",
+                cache_path,
+                holder
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+    }
+
+    fs::remove_file(path)?;
+    warn!("Removed stale cache lock {}", path.display());
+
+    Ok(())
+}
+
+fn read_holder(path: &Path) -> Option<LockHolder> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// Whether a process with the pid is running.
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+/// Whether a process with the pid is running.
+#[cfg(windows)]
+fn is_running(pid: u32) -> bool {
+    process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+        .unwrap_or(true)
+}
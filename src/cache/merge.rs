@@ -0,0 +1,129 @@
+use console::style;
+
+use crate::cache::*;
+use crate::common::*;
+use crate::validate::format_ranges;
+
+pub struct CacheMergeArgs {
+    pub caches: Vec<String>,
+    pub out: String,
+    pub force: bool,
+}
+
+/// Merges cache shards (e.g. uploads split across machines) into a single cache:
+/// items are unioned, overlapping indices must have the same content and the
+/// merged indices must be contiguous from 0. The program section comes from the
+/// shard with a magic hat, if any.
+pub fn process_cache_merge(args: CacheMergeArgs) -> Result<()> {
+    if Path::new(&args.out).exists() && !args.force {
+        let error = anyhow!(
+            "File {} already exists, use --force to overwrite it",
+            args.out
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    println!(
+        "{} {}Merging {} cache file(s)",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI,
+        args.caches.len()
+    );
+
+    let mut merged = Cache::new();
+    let mut program_source: Option<&String> = None;
+    let mut items: Vec<(usize, String, CacheItem)> = Vec::new();
+
+    for path in &args.caches {
+        let shard = load_cache(path, false)?;
+
+        if !shard.program.magic_hat.is_empty() {
+            match program_source {
+                Some(source) if merged.program.magic_hat != shard.program.magic_hat => {
+                    let error = anyhow!(
+                        "Cache files {} and {} are for different Magic Hats ({} and {})",
+                        source,
+                        path,
+                        merged.program.magic_hat,
+                        shard.program.magic_hat
+                    );
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+                Some(_) => {}
+                None => {
+                    merged.program = shard.program;
+                    program_source = Some(path);
+                }
+            }
+        }
+
+        println!("{} {} item(s)", style(path).bold(), shard.items.0.len());
+
+        for (index, item) in shard.items.0 {
+            let position = match index.parse::<usize>() {
+                Ok(position) => position,
+                Err(_) => {
+                    let error = anyhow!("Invalid index '{}' in cache file {}", index, path);
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+            };
+            items.push((position, path.clone(), item));
+        }
+    }
+
+    items.sort_by_key(|(position, _, _)| *position);
+
+    let mut sources: HashMap<usize, String> = HashMap::new();
+
+    for (position, path, item) in items {
+        let index = position.to_string();
+
+        if let Some(existing) = merged.items.0.get(&index) {
+            // the same item uploaded by two shards is fine, as long as it matches
+            if serde_json::to_value(existing)? != serde_json::to_value(&item)? {
+                let error = anyhow!(
+                    "Item {} differs between cache files {} and {}",
+                    index,
+                    sources[&position],
+                    path
+                );
+                error!("{:?}", error);
+                return Err(error);
+            }
+            continue;
+        }
+
+        merged.items.0.insert(index, item);
+        sources.insert(position, path);
+    }
+
+    let missing: Vec<usize> = (0..sources.keys().max().map(|max| max + 1).unwrap_or_default())
+        .filter(|position| !sources.contains_key(position))
+        .collect();
+
+    if !missing.is_empty() {
+        let error = anyhow!(
+            "Merged cache is not contiguous, missing item(s) {}",
+            format_ranges(&missing)
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    merged.write_to_file(Path::new(&args.out))?;
+
+    // the merged file must load like any other cache
+    load_cache(&args.out, false)?;
+
+    println!(
+        "\n{} {} ({} item(s))",
+        style("Merged cache written to").bold(),
+        style(&args.out).bold(),
+        merged.items.0.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+pub mod check;
+pub mod data;
+pub mod export;
+pub mod fmt;
+pub mod import;
+pub mod lock;
+pub mod merge;
+pub mod sqlite;
+
+pub use check::*;
+pub use data::*;
+pub use export::*;
+pub use fmt::*;
+pub use import::*;
+pub use lock::*;
+pub use merge::*;
+pub use sqlite::*;
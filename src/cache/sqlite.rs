@@ -0,0 +1,145 @@
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+use crate::cache::*;
+use crate::common::*;
+
+/// Extensions of the cache files stored in SQLite.
+pub const SQLITE_EXTENSIONS: &[&str] = &["db", "sqlite", "sqlite3"];
+
+const SCHEMA: &str =
+    "CREATE TABLE IF NOT EXISTS program (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+     CREATE TABLE IF NOT EXISTS items (
+         idx TEXT PRIMARY KEY,
+         position INTEGER NOT NULL,
+         item TEXT NOT NULL
+     );";
+
+/// Whether the cache file is stored in SQLite, based on its extension.
+pub fn is_sqlite_cache<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| SQLITE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Loads a cache stored in SQLite: the program values are stored as key/value rows
+/// and each item as a row (in JSON), ordered by position.
+pub fn load_sqlite_cache(path: &Path) -> Result<Cache> {
+    let connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+
+    let value = |key: &str| -> Result<Option<String>> {
+        Ok(connection
+            .query_row(
+                "SELECT value FROM program WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    };
+
+    let version = value("version")?
+        .and_then(|version| version.parse::<u32>().ok())
+        .unwrap_or(CACHE_VERSION);
+
+    if version > CACHE_VERSION {
+        return Err(CacheError::CacheFileTooNew(version, CACHE_VERSION).into());
+    }
+
+    let mut cache = Cache::new();
+    cache.program.magic_hat = value("magicHat")?.unwrap_or_default();
+    cache.program.magic_hat_creator = value("magicHatCreator")?.unwrap_or_default();
+    cache.program.collection_mint = value("collectionMint")?;
+    cache.program.number = value("number")?.and_then(|number| number.parse::<u64>().ok());
+
+    let mut statement = connection.prepare("SELECT idx, item FROM items ORDER BY position")?;
+    let rows = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    for row in rows {
+        let (index, item) = row?;
+        let cache_item: CacheItem = serde_json::from_str(&item)
+            .map_err(|err| CacheError::CacheFileWrongFormat(err.to_string()))?;
+        cache.items.0.insert(index.clone(), cache_item);
+        cache.synced.insert(index, item);
+    }
+
+    Ok(cache)
+}
+
+/// Writes a cache to SQLite in a single transaction. When writing back to the file
+/// the cache was loaded from, only the items changed since the last write are
+/// updated; otherwise all the rows are replaced.
+pub fn write_sqlite_cache(cache: &mut Cache, path: &Path) -> Result<()> {
+    let incremental = path.exists() && Path::new(&cache.file_path) == path;
+
+    let mut connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+    let transaction = connection.transaction()?;
+
+    if !incremental {
+        transaction.execute("DELETE FROM items", [])?;
+        cache.synced.clear();
+    }
+
+    {
+        let mut program = transaction
+            .prepare_cached("INSERT OR REPLACE INTO program (key, value) VALUES (?1, ?2)")?;
+        program.execute(params!["version", cache.version.to_string()])?;
+        program.execute(params!["magicHat", cache.program.magic_hat])?;
+        program.execute(params!["magicHatCreator", cache.program.magic_hat_creator])?;
+
+        match &cache.program.collection_mint {
+            Some(collection_mint) => {
+                program.execute(params!["collectionMint", collection_mint])?;
+            }
+            None => {
+                transaction.execute("DELETE FROM program WHERE key = 'collectionMint'", [])?;
+            }
+        }
+
+        match cache.program.number {
+            Some(number) => {
+                program.execute(params!["number", number.to_string()])?;
+            }
+            None => {
+                transaction.execute("DELETE FROM program WHERE key = 'number'", [])?;
+            }
+        }
+
+        let mut upsert = transaction.prepare_cached(
+            "INSERT OR REPLACE INTO items (idx, position, item) VALUES (?1, ?2, ?3)",
+        )?;
+
+        for (position, (index, item)) in cache.items.0.iter().enumerate() {
+            let item = serde_json::to_string(item)?;
+
+            if cache.synced.get(index) != Some(&item) {
+                upsert.execute(params![index, position as i64, item])?;
+                cache.synced.insert(index.clone(), item);
+            }
+        }
+
+        // items removed from the cache since the last write
+        let removed: Vec<String> = cache
+            .synced
+            .keys()
+            .filter(|index| !cache.items.0.contains_key(*index))
+            .cloned()
+            .collect();
+
+        let mut delete = transaction.prepare_cached("DELETE FROM items WHERE idx = ?1")?;
+        for index in removed {
+            delete.execute(params![index])?;
+            cache.synced.remove(&index);
+        }
+    }
+
+    transaction.commit()?;
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgEnum, Parser, Subcommand};
 
-use crate::constants::{DEFAULT_ASSETS, DEFAULT_CACHE, DEFAULT_CONFIG};
+use crate::config::{Price, UploadMethod};
+use crate::constants::{
+    DEFAULT_AIRDROP_RESULTS, DEFAULT_ASSETS, DEFAULT_CACHE, DEFAULT_CONFIG, DEFAULT_MINT_OUT,
+    DEFAULT_SELLER_FEE_THRESHOLD, DEFAULT_VALIDATION_REPORT, DEFAULT_VERIFY_REPORT,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about)]
@@ -9,6 +13,20 @@ pub struct Cli {
     #[clap(short, long, global = true)]
     pub log_level: Option<String>,
 
+    /// Resolve the default paths (cache, config and assets) relative to the current
+    /// directory instead of the project root (the first parent with a config file)
+    #[clap(long, global = true)]
+    pub no_discovery: bool,
+
+    /// Clear the lock of the cache file left by a laddu run which is no longer running
+    #[clap(long, global = true)]
+    pub force_unlock: bool,
+
+    /// Do not resolve .sol domains given as addresses (creators, treasury and authority),
+    /// for a fully offline validation of the config file
+    #[clap(long, global = true)]
+    pub no_sns: bool,
+
     #[clap(subcommand)]
     pub command: Commands,
 }
@@ -21,17 +39,136 @@ pub enum Commands {
         #[clap(short, long)]
         config: Option<String>,
 
+        /// Format of the config file [default: from the extension of the config file, or json]
+        #[clap(long, arg_enum)]
+        format: Option<ConfigFormat>,
+
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// Path to the directory with the assets
         #[clap(default_value = DEFAULT_ASSETS)]
         assets_dir: String,
+
+        /// Fail instead of prompting when a value is not given by a flag or found in the
+        /// assets directory
+        #[clap(long)]
+        non_interactive: bool,
+
+        /// Write risky settings (no royalties, immutable metadata, ...) without asking for
+        /// confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// Price of each NFT (in SOL or in SPL tokens)
+        #[clap(long)]
+        price: Option<Price>,
+
+        /// Number of NFTs [default: number of image/metadata pairs in the assets directory]
+        #[clap(long)]
+        number: Option<u64>,
+
+        /// Symbol of the collection [default: symbol of the first metadata file]
+        #[clap(long)]
+        symbol: Option<String>,
+
+        /// Seller fee basis points [default: value of the first metadata file]
+        #[clap(long)]
+        seller_fee_basis_points: Option<u16>,
+
+        /// Go live date (YYYY-MM-DD HH:MM:SS +0000, RFC3339, RFC2822, UNIX timestamp, now or +10m)
+        #[clap(long)]
+        go_live_date: Option<String>,
+
+        /// Creator wallet (or .sol domain) and its royalty share, can be repeated up to 4 times
+        #[clap(long = "creator", value_name = "ADDRESS:SHARE", number_of_values = 1)]
+        creators: Vec<String>,
+
+        /// SOL treasury wallet (or .sol domain)
+        #[clap(long)]
+        sol_treasury_account: Option<String>,
+
+        /// SPL token mint of the price, replaces the SOL treasury
+        #[clap(long)]
+        spl_token: Option<String>,
+
+        /// SPL token account receiving the payments
+        #[clap(long)]
+        spl_token_account: Option<String>,
+
+        /// Gatekeeper network address
+        #[clap(long)]
+        gatekeeper_network: Option<String>,
+
+        /// Whether the gatekeeper token expires on each mint [default: false]
+        #[clap(long)]
+        gatekeeper_expire_on_use: Option<bool>,
+
+        /// Whitelist token mint (not checked on-chain)
+        #[clap(long)]
+        whitelist_mint: Option<String>,
+
+        /// Whether the whitelist token is burned on each mint [default: false]
+        #[clap(long)]
+        whitelist_burn: Option<bool>,
+
+        /// Whether whitelist token holders can mint before the go live date [default: false]
+        #[clap(long)]
+        whitelist_presale: Option<bool>,
+
+        /// Discount price for whitelist token holders
+        #[clap(long)]
+        whitelist_discount_price: Option<Price>,
+
+        /// Date to stop the mint (same formats as the go live date)
+        #[clap(long, conflicts_with = "end_settings_amount")]
+        end_settings_date: Option<String>,
+
+        /// Number of mints after which the mint stops
+        #[clap(long)]
+        end_settings_amount: Option<u64>,
+
+        /// Name of the hidden settings, the mint index is appended to it
+        #[clap(long)]
+        hidden_name: Option<String>,
+
+        /// URI of the hidden settings
+        #[clap(long)]
+        hidden_uri: Option<String>,
+
+        /// Hash of the hidden settings (32 hexadecimal characters) [default: computed from
+        /// the metadata files of the assets directory]
+        #[clap(long)]
+        hidden_hash: Option<String>,
+
+        /// Upload method: bundlr, aws or nft_storage [default: bundlr]
+        #[clap(long)]
+        upload_method: Option<UploadMethod>,
+
+        /// AWS S3 bucket of the aws upload method
+        #[clap(long)]
+        aws_s3_bucket: Option<String>,
+
+        /// AWS region of the bucket [default: region of the AWS environment]
+        #[clap(long)]
+        aws_region: Option<String>,
+
+        /// Authentication token of the nft_storage upload method
+        #[clap(long)]
+        nft_storage_auth_token: Option<String>,
+
+        /// Whether the magic hat retains the update authority of the NFTs [default: true]
+        #[clap(long)]
+        retain_authority: Option<bool>,
+
+        /// Whether the NFTs metadata is mutable [default: true]
+        #[clap(long)]
+        is_mutable: Option<bool>,
     },
     /// Create a magic hat deployment from assets
     Launch {
@@ -40,7 +177,7 @@ pub enum Commands {
         assets_dir: String,
 
         /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// Path to the config file
@@ -48,11 +185,11 @@ pub enum Commands {
         config: String,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
         /// Strict mode: validate against JSON metadata standard exactly
@@ -62,15 +199,20 @@ pub enum Commands {
     /// Mint one NFT from magic hat
     Mint {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
+        /// Directory or comma-separated list of keypair files to mint with (round-robin),
+        /// takes precedence over --keypair
+        #[clap(long)]
+        keypairs: Option<String>,
+
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
         /// Amount of NFTs to be minted in bulk
@@ -80,52 +222,278 @@ pub enum Commands {
         /// Address of magic hat to mint from.
         #[clap(long)]
         magic_hat: Option<String>,
+
+        /// Path to the config file, used for default mint settings
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Priority fee in micro-lamports per compute unit [default: config "priorityFee"]
+        #[clap(long)]
+        priority_fee: Option<u64>,
+
+        /// Compute unit limit for the mint transaction [default: config "computeUnits"]
+        #[clap(long)]
+        compute_units: Option<u32>,
+
+        /// Number of times to retry a mint that failed with a bot tax or a retryable RPC error
+        #[clap(long, default_value = "0")]
+        retry: u32,
+
+        /// Token account holding the whitelist token [default: payer's associated token account]
+        #[clap(long)]
+        whitelist_token_account: Option<String>,
+
+        /// Gateway token account [default: payer's gateway token for the gatekeeper network]
+        #[clap(long)]
+        gateway_token: Option<String>,
+
+        /// Simulate the mint transaction without sending it
+        #[clap(long)]
+        simulate: bool,
+
+        /// Wait until the magic hat goes live instead of failing
+        #[clap(long)]
+        wait_for_live: bool,
+
+        /// Fetch the off-chain JSON metadata of the minted NFTs
+        #[clap(long)]
+        fetch_json: bool,
+
+        /// Delay (in milliseconds) between consecutive mints in bulk mode
+        #[clap(long, default_value = "0")]
+        delay_ms: u64,
+
+        /// Resume an interrupted bulk mint, minting only the remaining items
+        #[clap(long, conflicts_with = "restart")]
+        resume: bool,
+
+        /// Discard the progress of an interrupted bulk mint and start a new one
+        #[clap(long)]
+        restart: bool,
+
+        /// Create the payment token account if it does not exist (SPL token payments)
+        #[clap(long)]
+        create_ata: bool,
+
+        /// Skip the set collection instruction (for debugging)
+        #[clap(long)]
+        no_collection: bool,
+
+        /// Number of mints to send in parallel (single keypair only)
+        #[clap(long, default_value = "1", conflicts_with = "keypairs")]
+        parallel: usize,
+
+        /// Path to the file where minted NFTs are recorded (one JSON record per line)
+        #[clap(long, default_value = DEFAULT_MINT_OUT)]
+        out: String,
+
+        /// Output format of the command
+        #[clap(long, arg_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// List the mint addresses of all NFTs minted by a magic hat
+    MintList {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Address of magic hat to list the mints of
+        #[clap(long)]
+        magic_hat: Option<String>,
+
+        /// Include the name and URI of each NFT
+        #[clap(long)]
+        metadata: bool,
+
+        /// Path to the file where the list is written, defaults to stdout
+        #[clap(long)]
+        out: Option<String>,
+
+        /// Output format of the command
+        #[clap(long, arg_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+
+    /// Mint NFTs from magic hat directly to a list of addresses
+    Airdrop {
+        /// Path to the JSON file mapping addresses to the quantity of NFTs to receive
+        #[clap(long)]
+        list: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Address of magic hat to mint from.
+        #[clap(long)]
+        magic_hat: Option<String>,
+
+        /// Path to the file tracking the airdrop progress
+        #[clap(long, default_value = DEFAULT_AIRDROP_RESULTS)]
+        results: String,
+
+        /// Number of mints to send in parallel
+        #[clap(long, default_value = "1")]
+        parallel: usize,
     },
 
     /// Update the magic hat config on-chain
     Update {
-        /// Path to the config file, defaults to "config.json"
+        /// Path to the config file
         #[clap(short, long, default_value = DEFAULT_CONFIG)]
         config: String,
 
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
-        /// Pubkey for the new authority
+        /// Address of magic hat to update.
+        #[clap(long)]
+        magic_hat: Option<String>,
+
+        /// Use the config file as the base of the update when field flags are specified
+        #[clap(long)]
+        from_config: bool,
+
+        /// New treasury wallet (SOL wallet or SPL token account), defaults to the current one
+        #[clap(long)]
+        treasury: Option<String>,
+
+        /// Recompute the treasury wallet from the config file
+        #[clap(long, conflicts_with = "treasury")]
+        update_treasury: bool,
+
+        /// New price (in SOL or in SPL tokens)
+        #[clap(long)]
+        price: Option<Price>,
+
+        /// New go live date (RFC3339, RFC2822, YYYY-MM-DD HH:MM, UNIX timestamp, now or +10m/+2h)
+        #[clap(long)]
+        go_live_date: Option<String>,
+
+        /// Timezone of a go live date without an offset (e.g. Europe/Paris), defaults to the
+        /// config timezone or UTC
+        #[clap(long)]
+        timezone: Option<String>,
+
+        /// New seller fee basis points
+        #[clap(long)]
+        seller_fee_basis_points: Option<u16>,
+
+        /// Whether the NFTs metadata is mutable
+        #[clap(long)]
+        is_mutable: Option<bool>,
+
+        /// Whether the magic hat retains the update authority of the NFTs
+        #[clap(long)]
+        retain_authority: Option<bool>,
+
+        /// New symbol
+        #[clap(long)]
+        symbol: Option<String>,
+
+        /// New name of the hidden settings
+        #[clap(long)]
+        hidden_name: Option<String>,
+
+        /// New URI of the hidden settings
+        #[clap(long)]
+        hidden_uri: Option<String>,
+
+        /// New hash of the hidden settings (32 hexadecimal characters)
+        #[clap(long)]
+        hidden_hash: Option<String>,
+
+        /// Send the update even when the configuration is unchanged
+        #[clap(long)]
+        force: bool,
+
+        /// Simulate the update transaction without sending it
+        #[clap(long)]
+        simulate: bool,
+
+        /// Send the update without asking for confirmation
         #[clap(short, long)]
-        new_authority: Option<String>,
+        yes: bool,
+    },
+
+    /// Transfer the authority of the magic hat to a new address
+    UpdateAuthority {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
 
         /// Address of magic hat to update.
         #[clap(long)]
         magic_hat: Option<String>,
+
+        /// Pubkey for the new authority
+        #[clap(short, long)]
+        new_authority: String,
+
+        /// Allow a new authority that is not on the ed25519 curve (PDA)
+        #[clap(long)]
+        allow_pda: bool,
+
+        /// Skip re-typing the new authority to confirm
+        #[clap(short, long)]
+        yes: bool,
     },
 
     /// Deploy cache items into magic hat config on-chain
     Deploy {
-        /// Path to the config file, defaults to "config.json"
+        /// Path to the config file
         #[clap(short, long, default_value = DEFAULT_CONFIG)]
         config: String,
 
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
+
+        /// Deploy risky config settings without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
     },
 
     /// Upload assets to storage and creates the cache config
@@ -139,15 +507,15 @@ pub enum Commands {
         config: String,
 
         /// Path to the keypair file [default: solana config or "~/.config/solana/id.json"]
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
     },
 
@@ -158,16 +526,52 @@ pub enum Commands {
         magic_hat: Option<String>,
 
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// List available magic hats, no withdraw performed
         #[clap(long)]
         list: bool,
+
+        /// Print the reclaimable funds without withdrawing them
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Withdraw from all magic hats without asking for confirmation
+        #[clap(short, long)]
+        yes: bool,
+
+        /// File with the magic hats to withdraw from (one id per line or JSON array)
+        #[clap(long, conflicts_with_all = &["magic_hat", "list"])]
+        machines_file: Option<String>,
+
+        /// Number of withdraws sent concurrently
+        #[clap(long, default_value = "1")]
+        parallel: usize,
+
+        /// Also drain magic hats that are not sold out
+        #[clap(long)]
+        allow_unminted: bool,
+
+        /// Output format of the listing (with --list)
+        #[clap(long, arg_enum, default_value = "text")]
+        output: ListOutputFormat,
+
+        /// File to write the listing to (with --list), defaults to stdout
+        #[clap(long, requires = "list")]
+        out: Option<String>,
+
+        /// Sort the listing by balance, items redeemed or go live date
+        #[clap(long, arg_enum)]
+        sort: Option<ListSort>,
+
+        /// Wallet that receives the reclaimed funds, defaults to the authority
+        #[clap(long)]
+        destination: Option<String>,
     },
 
     /// Validate JSON metadata files
@@ -179,49 +583,192 @@ pub enum Commands {
         /// Strict mode: validate against JSON metadata standard exactly
         #[clap(long)]
         strict: bool,
+
+        /// Path to the validation report file
+        #[clap(long, default_value = DEFAULT_VALIDATION_REPORT)]
+        report: String,
+
+        /// Path to the config file, checks the creators of each item against it
+        #[clap(long)]
+        config: Option<String>,
+
+        /// Skip the image size and dimension checks
+        #[clap(long)]
+        skip_image_checks: bool,
+
+        /// Hash the image files to detect identical images under different indices
+        #[clap(long)]
+        check_duplicates: bool,
+
+        /// Do not report items sharing the same name
+        #[clap(long)]
+        allow_duplicate_names: bool,
+
+        /// Warn about seller fee basis points above this value
+        #[clap(long, default_value = DEFAULT_SELLER_FEE_THRESHOLD)]
+        seller_fee_threshold: u16,
+
+        /// Hidden settings mode: validate the placeholder of the config, the assets
+        /// are the (optional) reveal set [default: enabled when the config has hidden settings]
+        #[clap(long, requires = "config")]
+        hidden: bool,
+
+        /// Fix common metadata issues in place (missing symbol, seller fee as a
+        /// string, whitespace in names, wrong image extension)
+        #[clap(long)]
+        fix: bool,
+
+        /// Print the fixes without changing any file
+        #[clap(long, requires = "fix")]
+        dry_run: bool,
+
+        /// Do not keep a .bak copy of the fixed files
+        #[clap(long, requires = "fix")]
+        no_backup: bool,
     },
 
     /// Verify uploaded data
     Verify {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
+
+        /// Address of the magic hat to verify (takes precedence over the cache); without
+        /// a matching cache file only the on-chain data is checked
+        #[clap(long)]
+        magic_hat: Option<String>,
+
+        /// Path to the config file, used to check the collection mint (defaults to
+        /// "config.json" when it exists)
+        #[clap(short, long)]
+        config: Option<String>,
+
+        /// Mark missing or mismatched items as not on-chain in the cache, so that
+        /// `deploy` writes them again
+        #[clap(long)]
+        repair: bool,
+
+        /// Check that the metadata links of the cache are reachable and match the items
+        #[clap(long)]
+        check_links: bool,
+
+        /// Also check the image links of the metadata (requires --check-links)
+        #[clap(long, requires = "check_links")]
+        check_images: bool,
+
+        /// Path to the verify report file
+        #[clap(long, default_value = DEFAULT_VERIFY_REPORT)]
+        report: String,
+
+        /// First index to verify
+        #[clap(long, conflicts_with = "index")]
+        from: Option<usize>,
+
+        /// Last index to verify (inclusive)
+        #[clap(long, conflicts_with = "index")]
+        to: Option<usize>,
+
+        /// Comma-separated list of indices to verify
+        #[clap(long)]
+        index: Option<String>,
+
+        /// Resume the link checks of an interrupted verify
+        #[clap(long, requires = "check_links", conflicts_with = "no_resume")]
+        resume: bool,
+
+        /// Discard the checkpoint of an interrupted verify and check all links again
+        #[clap(long)]
+        no_resume: bool,
     },
 
     /// Show the on-chain config of an existing magic hat
     Show {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
+        /// Path to the config file, used by the cache summary
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Show a summary of the local cache (no connection required)
+        #[clap(
+            long,
+            conflicts_with_all = &["unminted", "config_lines", "balances", "watch", "stats"]
+        )]
+        cache_summary: bool,
+
         /// Address of magic hat
         magic_hat: Option<String>,
+
+        /// Show the unminted indices and availability summary instead of the config
+        #[clap(long, conflicts_with = "config_lines")]
+        unminted: bool,
+
+        /// Show the config lines stored on chain instead of the config
+        #[clap(long, conflicts_with = "balances")]
+        config_lines: bool,
+
+        /// Show the payer, treasury, bundlr and rent balances instead of the config
+        #[clap(long, conflicts_with = "unminted")]
+        balances: bool,
+
+        /// First config line index to show, defaults to 0
+        #[clap(long, requires = "config_lines")]
+        from: Option<u64>,
+
+        /// Last config line index to show, defaults to a page of 50 lines
+        #[clap(long, requires = "config_lines")]
+        to: Option<u64>,
+
+        /// Keep refreshing the countdowns and items redeemed until interrupted
+        #[clap(long, conflicts_with_all = &["unminted", "config_lines", "balances"])]
+        watch: bool,
+
+        /// Show the mint rate and the estimated time to sell out
+        #[clap(long, conflicts_with_all = &["unminted", "config_lines", "balances"])]
+        stats: bool,
+
+        /// Show the raw account data instead of deserializing it
+        #[clap(
+            long,
+            conflicts_with_all = &["unminted", "config_lines", "balances", "watch", "stats"]
+        )]
+        raw: bool,
+
+        /// Number of bytes of the account data shown by --raw
+        #[clap(long, default_value = "256", requires = "raw")]
+        raw_bytes: usize,
+
+        /// Output format of the command
+        #[clap(long, arg_enum, default_value = "text")]
+        output: OutputFormat,
     },
 
     /// Interact with the bundlr network
     Bundlr {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         #[clap(subcommand)]
@@ -233,6 +780,158 @@ pub enum Commands {
         #[clap(subcommand)]
         command: CollectionSubcommands,
     },
+
+    /// Manage the config file
+    Config {
+        #[clap(subcommand)]
+        command: ConfigSubcommands,
+    },
+
+    /// Manage the cache file
+    Cache {
+        #[clap(subcommand)]
+        command: CacheSubcommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CacheSubcommands {
+    /// Rebuild the cache file from the config lines of a deployed magic hat
+    Import {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Address of magic hat to import
+        #[clap(long, required_unless_present = "from", conflicts_with = "from")]
+        magic_hat: Option<String>,
+
+        /// Cache file to convert instead (JSON or SQLite, based on the extension)
+        #[clap(long)]
+        from: Option<String>,
+
+        /// Path to the cache file to write
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        out: String,
+
+        /// Overwrite the cache file if it exists
+        #[clap(long)]
+        force: bool,
+
+        /// Fetch the metadata JSON of each item to fill in the image links
+        #[clap(long)]
+        fetch_images: bool,
+    },
+
+    /// Convert the cache file to another file (JSON or SQLite, based on the extension)
+    Export {
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Path to the cache file to write (e.g. "cache.db" for SQLite)
+        #[clap(long)]
+        out: String,
+
+        /// Overwrite the output file if it exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Merge cache files with different index ranges into a single cache file
+    Merge {
+        /// Cache files to merge
+        #[clap(required = true, min_values = 2)]
+        caches: Vec<String>,
+
+        /// Path to the merged cache file
+        #[clap(long, default_value = DEFAULT_CACHE)]
+        out: String,
+
+        /// Overwrite the merged cache file if it exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Reformat the cache file in place, indented or compact
+    Fmt {
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Write the cache file indented (e.g. to diff it)
+        #[clap(long, conflicts_with = "compact", required_unless_present = "compact")]
+        pretty: bool,
+
+        /// Write the cache file without indentation (smaller and faster to sync)
+        #[clap(long)]
+        compact: bool,
+    },
+
+    /// Check the integrity of the cache file (without network access)
+    Check {
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Path to the config file, used for the expected number of items (defaults
+        /// to "config.json" when it exists)
+        #[clap(short, long)]
+        config: Option<String>,
+
+        /// Fix the mechanical problems (whitespace in names and links, string flags)
+        #[clap(long)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigSubcommands {
+    /// Write the on-chain settings of a magic hat to a config file
+    Dump {
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+
+        /// Path to the cache file, defaults to "cache.json"
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
+        cache: String,
+
+        /// Address of magic hat to dump
+        #[clap(long)]
+        magic_hat: Option<String>,
+
+        /// Path to the config file to write
+        #[clap(long, default_value = DEFAULT_CONFIG)]
+        out: String,
+
+        /// Overwrite the config file if it exists
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Check the config file for errors, and the on-chain accounts when an RPC is specified
+    Check {
+        /// Path to the config file
+        #[clap(short, long, default_value = DEFAULT_CONFIG)]
+        config: String,
+
+        /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
+        keypair: Option<String>,
+
+        /// RPC Url, the on-chain accounts are only checked when specified
+        #[clap(short, long, env = "LADDU_RPC_URL")]
+        rpc_url: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -244,15 +943,15 @@ pub enum CollectionSubcommands {
         collection_mint: String,
 
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
         /// Address of magic hat to update.
@@ -263,15 +962,15 @@ pub enum CollectionSubcommands {
     /// Remove the collection from the magic hat
     Remove {
         /// Path to the keypair file, uses Sol config or defaults to "~/.config/solana/id.json"
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_KEYPAIR")]
         keypair: Option<String>,
 
         /// RPC Url
-        #[clap(short, long)]
+        #[clap(short, long, env = "LADDU_RPC_URL")]
         rpc_url: Option<String>,
 
         /// Path to the cache file, defaults to "cache.json"
-        #[clap(long, default_value = DEFAULT_CACHE)]
+        #[clap(long, default_value = DEFAULT_CACHE, env = "LADDU_CACHE")]
         cache: String,
 
         /// Address of magic hat to update.
@@ -287,3 +986,41 @@ pub enum BundlrAction {
     /// Withdraw funds from bundlr
     Withdraw,
 }
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable output
+    Text,
+    /// JSON output (for scripting)
+    Json,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ListOutputFormat {
+    /// Human-readable table
+    Text,
+    /// JSON array (for scripting)
+    Json,
+    /// CSV with a header row (for spreadsheets)
+    Csv,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+    /// JSON (.json)
+    Json,
+    /// YAML (.yaml or .yml)
+    Yaml,
+    /// TOML (.toml)
+    Toml,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ListSort {
+    /// Largest balance first
+    Balance,
+    /// Most items redeemed first
+    Redeemed,
+    /// Earliest go live date first
+    Date,
+}
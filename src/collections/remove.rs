@@ -27,11 +27,14 @@ pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
     let client = setup_client(&laddu_config)?;
     let program = client.program(MAGIC_HAT_ID);
     // the magic hat id specified takes precedence over the one from the cache
+    let mut cache = None;
     let magic_hat_id = match args.magic_hat {
         Some(magic_hat_id) => magic_hat_id,
         None => {
-            let cache = load_cache(&args.cache, false)?;
-            cache.program.magic_hat
+            let loaded = load_cache(&args.cache, false)?;
+            let magic_hat_id = loaded.program.magic_hat.clone();
+            cache = Some(loaded);
+            magic_hat_id
         }
     };
 
@@ -85,6 +88,12 @@ pub fn process_remove_collection(args: RemoveCollectionArgs) -> Result<()> {
         remove_signature
     ));
 
+    // keeps the collection of the cache in sync, so that verify can check it
+    if let Some(mut cache) = cache {
+        cache.program.collection_mint = None;
+        cache.sync_file()?;
+    }
+
     Ok(())
 }
 
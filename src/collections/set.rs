@@ -30,11 +30,14 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
     let program = client.program(MAGIC_HAT_ID);
 
     // the magic hat id specified takes precedence over the one from the cache
+    let mut cache = None;
     let magic_hat_id = match args.magic_hat {
         Some(magic_hat_id) => magic_hat_id,
         None => {
-            let cache = load_cache(&args.cache, false)?;
-            cache.program.magic_hat
+            let loaded = load_cache(&args.cache, false)?;
+            let magic_hat_id = loaded.program.magic_hat.clone();
+            cache = Some(loaded);
+            magic_hat_id
         }
     };
 
@@ -101,6 +104,12 @@ pub fn process_set_collection(args: SetCollectionArgs) -> Result<()> {
         set_signature
     ));
 
+    // keeps the collection of the cache in sync, so that verify can check it
+    if let Some(mut cache) = cache {
+        cache.program.collection_mint = Some(args.collection_mint.clone());
+        cache.sync_file()?;
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,327 @@
+use anyhow::Result;
+use console::style;
+
+use crate::common::*;
+use crate::config::data::*;
+use crate::config::lint::{lint_risky_settings, RiskySettings};
+use crate::config::parser::get_config_data;
+use crate::magic_hat::MAGIC_HAT_ID;
+use crate::utils::{check_spl_token, check_spl_token_account, spinner_with_style};
+use crate::validate::{
+    check_hidden_settings, check_seller_fee_basis_points, check_seller_fee_sanity, check_symbol,
+};
+
+pub struct ConfigCheckArgs {
+    pub config: String,
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+}
+
+/// Problems found in the config file: errors would make deploy fail, warnings are
+/// valid settings that are probably a mistake.
+#[derive(Default)]
+struct ConfigReport {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+pub fn process_config_check(args: ConfigCheckArgs) -> Result<()> {
+    let steps = if args.rpc_url.is_some() { 2 } else { 1 };
+
+    println!(
+        "{} {}Checking config file",
+        style(format!("[1/{}]", steps)).bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+
+    let config_data = get_config_data(&args.config)?;
+    let mut report = ConfigReport::default();
+    check_offline(&config_data, &mut report);
+
+    // the on-chain checks need an RPC, they are only run when one is specified
+    if args.rpc_url.is_some() {
+        println!(
+            "\n{} {}Checking on-chain accounts",
+            style(format!("[2/{}]", steps)).bold().dim(),
+            LOOKING_GLASS_EMOJI
+        );
+
+        let pb = spinner_with_style();
+        pb.set_message("Connecting...");
+
+        let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+        let client = setup_client(&laddu_config)?;
+        let program = client.program(MAGIC_HAT_ID);
+        check_on_chain(&program, &config_data, &mut report);
+
+        pb.finish_with_message("Done");
+    }
+
+    println!();
+
+    if !report.errors.is_empty() {
+        println!("{}", style("Errors:").red().bold());
+        for error in &report.errors {
+            println!("  - {}", error);
+        }
+    }
+
+    if !report.warnings.is_empty() {
+        println!("{}", style("Warnings:").yellow().bold());
+        for warning in &report.warnings {
+            warn!("{}", warning);
+            println!("  - {}", warning);
+        }
+    }
+
+    if !report.errors.is_empty() {
+        let error = anyhow!(
+            "Config check found {} error(s) and {} warning(s)",
+            report.errors.len(),
+            report.warnings.len()
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    println!(
+        "{}",
+        style(format!(
+            "Config file '{}' is valid ({} warning(s))",
+            args.config,
+            report.warnings.len()
+        ))
+        .green()
+        .bold()
+    );
+
+    Ok(())
+}
+
+/// Checks the settings of the config file that do not need an RPC, following the
+/// same rules as deploy.
+fn check_offline(config_data: &ConfigData, report: &mut ConfigReport) {
+    // the precision of an SPL token price is checked against its mint on chain
+    if config_data.price.is_zero() {
+        report
+            .warnings
+            .push("price is 0, items can be minted for free".to_string());
+    } else if config_data.spl_token.is_none() {
+        if let Err(err) = config_data.price.to_lamports() {
+            report.errors.push(err.to_string());
+        }
+    }
+
+    // an auto number is only known once the assets are uploaded
+    if config_data.number == Some(0) {
+        report
+            .errors
+            .push("number of items must be greater than 0".to_string());
+    }
+
+    let creators = &config_data.creators;
+    if creators.is_empty() || creators.len() > (MAX_CREATOR_LIMIT - 1) {
+        report.errors.push(format!(
+            "the number of creators must be between 1 and {} (found {})",
+            MAX_CREATOR_LIMIT - 1,
+            creators.len()
+        ));
+    }
+    let shares: u32 = creators.iter().map(|creator| creator.share as u32).sum();
+    if !creators.is_empty() && shares != 100 {
+        report.errors.push(format!(
+            "creators' shares must add up to 100 (found {})",
+            shares
+        ));
+    }
+
+    if let Err(err) = check_seller_fee_basis_points(config_data.seller_fee_basis_points) {
+        report.errors.push(err.to_string());
+    } else if let Err(err) = check_seller_fee_sanity(
+        config_data.seller_fee_basis_points,
+        DEFAULT_SELLER_FEE_THRESHOLD.parse().unwrap(),
+    ) {
+        report.warnings.push(err.to_string());
+    }
+
+    if let Err(err) = check_symbol(&config_data.symbol) {
+        report.errors.push(err.to_string());
+    }
+
+    match (
+        config_data.spl_token,
+        config_data.spl_token_account,
+        config_data.sol_treasury_account,
+    ) {
+        (Some(_), _, Some(_)) => report.errors.push(
+            "spl-token and sol-treasury-account cannot both be set, \
+             use spl-token-account to receive the token payments"
+                .to_string(),
+        ),
+        (Some(_), None, None) => report.warnings.push(
+            "spl-token-account is not set, payments go to the associated token account \
+             of the keypair"
+                .to_string(),
+        ),
+        (None, Some(_), _) => report
+            .errors
+            .push("spl-token-account is set without a spl-token".to_string()),
+        _ => (),
+    }
+
+    if let Err(err) = config_data.cluster_rpc_url() {
+        report.errors.push(format!(
+            "{}: expected mainnet-beta, devnet, localnet or a name of rpcEndpoints",
+            err
+        ));
+    }
+
+    if let Some(timezone) = &config_data.timezone {
+        if let Err(err) = parse_timezone(timezone) {
+            report.errors.push(err.to_string());
+        }
+    }
+
+    let timezone = config_data.timezone.as_deref();
+    let go_live_date = match config_data.go_live_date {
+        Some(ref date) => match go_live_date_as_timestamp(date, timezone) {
+            Ok(go_live_date) => Some(go_live_date),
+            Err(err) => {
+                report
+                    .errors
+                    .push(format!("invalid go live date '{}': {}", date, err));
+                None
+            }
+        },
+        None => None,
+    };
+
+    // the same settings that deploy asks to confirm
+    report.warnings.extend(lint_risky_settings(
+        &RiskySettings {
+            retain_authority: config_data.retain_authority,
+            is_mutable: config_data.is_mutable,
+            seller_fee_basis_points: config_data.seller_fee_basis_points,
+            go_live_date,
+        },
+        None,
+    ));
+
+    if let Some(end_settings) = &config_data.end_settings {
+        match end_settings.end_setting_type() {
+            EndSettingType::Amount => {
+                if let Some(number) = config_data.number {
+                    if end_settings.number() > number {
+                        report.errors.push(format!(
+                            "end settings amount {} is greater than the number of items {}",
+                            end_settings.number(),
+                            number
+                        ))
+                    }
+                }
+            }
+            EndSettingType::Date => {
+                if let Some(go_live_date) = go_live_date {
+                    if end_settings.number() as i64 <= go_live_date {
+                        report
+                            .errors
+                            .push("end settings date is not after the go live date".to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(whitelist_mint_settings) = &config_data.whitelist_mint_settings {
+        if let Some(discount_price) = whitelist_mint_settings.discount_price() {
+            // like the price, an SPL token discount price is checked on chain
+            let precision_error = match config_data.spl_token {
+                Some(_) => None,
+                None => discount_price.to_lamports().err(),
+            };
+
+            if let Some(err) = precision_error {
+                report
+                    .errors
+                    .push(format!("whitelist discount price: {}", err));
+            } else if *discount_price > config_data.price {
+                report.warnings.push(format!(
+                    "whitelist discount price {} is greater than the price {}",
+                    discount_price, config_data.price
+                ));
+            }
+        }
+    }
+
+    if let Some(hidden_settings) = &config_data.hidden_settings {
+        let number = config_data.number.unwrap_or_default();
+        for err in check_hidden_settings(hidden_settings, number) {
+            report.errors.push(err.to_string());
+        }
+    }
+
+    // missing settings are rejected when the config is loaded, empty ones are not
+    match &config_data.upload {
+        UploadConfig::Aws(settings) if settings.bucket.trim().is_empty() => report
+            .errors
+            .push("upload method aws needs a bucket".to_string()),
+        UploadConfig::NftStorage(settings) if settings.auth_token.trim().is_empty() => report
+            .errors
+            .push("upload method nft_storage needs an authToken".to_string()),
+        _ => (),
+    }
+}
+
+/// Checks that the token mint and the payment accounts of the config exist on chain.
+fn check_on_chain(program: &Program, config_data: &ConfigData, report: &mut ConfigReport) {
+    if let Some(spl_token) = config_data.spl_token {
+        match check_spl_token(program, &spl_token.to_string()) {
+            Ok(mint) => {
+                if let Err(err) = config_data.price.to_base_units(mint.decimals) {
+                    report.errors.push(err.to_string());
+                }
+
+                let discount_price = config_data
+                    .whitelist_mint_settings
+                    .as_ref()
+                    .and_then(|settings| settings.discount_price());
+                if let Some(Err(err)) =
+                    discount_price.map(|price| price.to_base_units(mint.decimals))
+                {
+                    report
+                        .errors
+                        .push(format!("whitelist discount price: {}", err));
+                }
+            }
+            Err(err) => report
+                .errors
+                .push(format!("spl-token {}: {}", spl_token, err)),
+        }
+    }
+
+    if let Some(spl_token_account) = config_data.spl_token_account {
+        if let Err(err) = check_spl_token_account(program, &spl_token_account.to_string()) {
+            report
+                .errors
+                .push(format!("spl-token-account {}: {}", spl_token_account, err));
+        }
+    }
+
+    // a treasury without an account is valid, it is created by the first payment
+    if let Some(treasury) = config_data.sol_treasury_account {
+        match program
+            .rpc()
+            .get_account_with_commitment(&treasury, program.rpc().commitment())
+        {
+            Ok(response) if response.value.is_none() => report.warnings.push(format!(
+                "sol-treasury-account {} does not exist on chain",
+                treasury
+            )),
+            Ok(_) => (),
+            Err(err) => report.errors.push(format!(
+                "failed to fetch sol-treasury-account {}: {}",
+                treasury, err
+            )),
+        }
+    }
+}
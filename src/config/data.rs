@@ -1,8 +1,11 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_client::solana_sdk::signature::Keypair;
-use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
 pub use anyhow::{anyhow, Result};
-use chrono::DateTime;
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use console::style;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::str::FromStr;
 
@@ -14,6 +17,9 @@ use magic_hat::{
 };
 
 use crate::config::errors::*;
+use crate::config::price::{Price, SOL_DECIMALS};
+use crate::constants::CLUSTER_PRESETS;
+use crate::sns::parse_address;
 
 pub struct LadduConfig {
     pub keypair: Keypair,
@@ -30,46 +36,101 @@ pub struct SolanaConfig {
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigData {
-    pub price: f64,
-    pub number: u64,
+    pub price: Price,
+
+    /// Number of items, detected from the assets directory when `auto` or missing
+    #[serde(default, deserialize_with = "to_option_number")]
+    #[serde(serialize_with = "to_auto_number")]
+    pub number: Option<u64>,
+
     pub gatekeeper: Option<GatekeeperConfig>,
     pub creators: Vec<Creator>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_address")]
     #[serde(serialize_with = "to_option_string")]
     pub sol_treasury_account: Option<Pubkey>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_pubkey")]
     #[serde(serialize_with = "to_option_string")]
     pub spl_token_account: Option<Pubkey>,
 
-    #[serde(deserialize_with = "to_option_pubkey")]
+    #[serde(default, deserialize_with = "to_option_pubkey")]
     #[serde(serialize_with = "to_option_string")]
     pub spl_token: Option<Pubkey>,
 
-    pub go_live_date: String,
+    /// Go live date, a magic hat without one can only be minted by the authority
+    /// (or by whitelisted presale users)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub go_live_date: Option<String>,
+
+    /// Timezone (IANA name) of a go live date without an offset, defaults to UTC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
 
     pub end_settings: Option<EndSettings>,
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub whitelist_mint_settings: Option<WhitelistMintSettings>,
 
     pub hidden_settings: Option<HiddenSettings>,
 
-    pub upload_method: UploadMethod,
+    /// Cluster used when no RPC url is specified: mainnet-beta, devnet, localnet or a
+    /// name of `rpcEndpoints`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<String>,
+
+    /// RPC urls of named clusters, which can also replace the url of a preset
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rpc_endpoints: BTreeMap<String, String>,
+
+    /// Upload method and its settings, Bundlr when not specified
+    #[serde(default)]
+    pub upload: UploadConfig,
+
+    /// Upload method of the configs written before the `upload` section, moved to
+    /// `upload` when the config is loaded
+    #[serde(default, skip_serializing)]
+    pub upload_method: Option<UploadMethod>,
 
     pub retain_authority: bool,
 
     pub is_mutable: bool,
 
-    #[serde(serialize_with = "to_option_string")]
+    #[serde(default, skip_serializing)]
     pub aws_s3_bucket: Option<String>,
 
-    #[serde(serialize_with = "to_option_string")]
+    #[serde(default, skip_serializing)]
     pub nft_storage_auth_token: Option<String>,
 
     pub symbol: String,
 
     pub seller_fee_basis_points: u16,
+
+    /// Default priority fee (in micro-lamports per compute unit) for mint transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<u64>,
+
+    /// Default compute unit limit for mint transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_units: Option<u32>,
+
+    /// Maximum size (in bytes) of the image files, checked by validate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_image_bytes: Option<u64>,
+
+    /// Maximum width or height (in pixels) of the image files, checked by validate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_image_dimension: Option<u32>,
+
+    /// Maximum size (in bytes) of the animation files, checked by validate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_animation_bytes: Option<u64>,
+
+    /// Collection mint expected to be set on the magic hat, checked by verify
+    #[serde(default, deserialize_with = "to_option_pubkey")]
+    #[serde(serialize_with = "to_option_string")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_mint: Option<Pubkey>,
 }
 
 pub fn to_string<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
@@ -96,23 +157,134 @@ pub fn parse_string_as_date(go_live_date: &str) -> Result<String> {
     Ok(date.to_rfc2822())
 }
 
-pub fn go_live_date_as_timestamp(go_live_date: &str) -> Result<i64> {
+/// Examples of the accepted go live date formats, shown when a date is rejected.
+const GO_LIVE_DATE_EXAMPLES: &str = "2022-07-14T02:40:00-04:00 (RFC3339), \
+     2022-07-14 02:40 (in the config timezone, UTC by default), \
+     Thu, 14 Jul 2022 02:40:00 -0400 (RFC2822), 1657780800 (UNIX timestamp), now or +10m";
+
+/// Formats of dates without an offset, interpreted in the config timezone.
+const LOCAL_DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+/// Parses a go live date into a UNIX timestamp.
+///
+/// Besides RFC3339 and RFC2822 dates with an offset and UNIX timestamps, `now`,
+/// offsets relative to the current time (e.g. `+10m`, `+2h`) and dates without an
+/// offset (e.g. `2022-07-14 02:40`) are accepted. The latter are interpreted in
+/// `timezone` (an IANA name such as `Europe/Paris`), or in UTC with a warning.
+pub fn go_live_date_as_timestamp(go_live_date: &str, timezone: Option<&str>) -> Result<i64> {
+    let go_live_date = go_live_date.trim();
     let format;
-    if let Ok(date) = chrono::DateTime::parse_from_rfc2822(go_live_date) {
-        format = date.timestamp();
-    } else if let Ok(date) = chrono::DateTime::parse_from_rfc3339(go_live_date) {
-        format = date.timestamp();
+    if go_live_date.eq_ignore_ascii_case("now") {
+        format = chrono::Utc::now().timestamp();
+    } else if let Some(offset) = go_live_date.strip_prefix('+') {
+        format = chrono::Utc::now().timestamp() + parse_relative_offset(offset)?;
     } else if let Ok(timestamp) = go_live_date.parse::<i64>() {
         format = timestamp;
+    } else if go_live_date.starts_with('-') {
+        return Err(anyhow!(
+            "Invalid go live date: {} (relative offsets must be in the future, e.g. +10m)",
+            go_live_date
+        ));
+    } else if let Ok(date) = DateTime::parse_from_rfc3339(go_live_date) {
+        format = date.timestamp();
+    } else if let Ok(date) = DateTime::parse_from_rfc2822(go_live_date) {
+        format = date.timestamp();
+    } else if let Ok(date) = DateTime::parse_from_str(go_live_date, "%Y-%m-%d %H:%M:%S %z") {
+        format = date.timestamp();
+    } else if let Some(date) = LOCAL_DATE_FORMATS
+        .iter()
+        .find_map(|mask| NaiveDateTime::parse_from_str(go_live_date, mask).ok())
+    {
+        format = local_date_as_timestamp(go_live_date, &date, timezone)?;
     } else {
-        return Err(anyhow!("Invalid date format. Format must be: RFC2822(Fri, 14 Jul 2022 02:40:00 -0400), RFC3339(2022-02-25T13:00:00Z), or UNIX timestamp."));
+        return Err(anyhow!(
+            "Invalid go live date: {}. Accepted formats: {}.",
+            go_live_date,
+            GO_LIVE_DATE_EXAMPLES
+        ));
     };
 
     Ok(format)
 }
 
-pub fn price_as_lamports(price: f64) -> u64 {
-    (price * LAMPORTS_PER_SOL as f64) as u64
+/// Parses an IANA timezone name (e.g. `America/New_York`).
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    Tz::from_str(timezone.trim()).map_err(|_| {
+        anyhow!(
+            "Invalid timezone: {} (expected an IANA name, e.g. UTC, Europe/Paris or America/New_York)",
+            timezone
+        )
+    })
+}
+
+/// Converts a date without an offset to a timestamp in the timezone. Dates that
+/// happen twice or not at all because of a daylight saving time change are rejected.
+fn local_date_as_timestamp(
+    go_live_date: &str,
+    date: &NaiveDateTime,
+    timezone: Option<&str>,
+) -> Result<i64> {
+    let timezone = match timezone {
+        Some(timezone) => parse_timezone(timezone)?,
+        None => {
+            println!(
+                "{} go live date '{}' has no offset and no timezone is set, it is interpreted as UTC",
+                style("Warning:").yellow().bold(),
+                go_live_date
+            );
+            Tz::UTC
+        }
+    };
+
+    match timezone.from_local_datetime(date) {
+        LocalResult::Single(date) => Ok(date.timestamp()),
+        LocalResult::Ambiguous(first, second) => Err(anyhow!(
+            "Ambiguous go live date: {} happens twice in {} ({} and {}), add an offset. Accepted formats: {}.",
+            go_live_date,
+            timezone.name(),
+            first.to_rfc3339(),
+            second.to_rfc3339(),
+            GO_LIVE_DATE_EXAMPLES
+        )),
+        LocalResult::None => Err(anyhow!(
+            "Invalid go live date: {} does not exist in {} (daylight saving time change). Accepted formats: {}.",
+            go_live_date,
+            timezone.name(),
+            GO_LIVE_DATE_EXAMPLES
+        )),
+    }
+}
+
+/// Parses a relative offset such as `10m` into seconds.
+fn parse_relative_offset(offset: &str) -> Result<i64> {
+    let invalid = || {
+        anyhow!(
+            "Invalid relative go live date: +{} (expected a number followed by s, m, h or d, e.g. +10m)",
+            offset
+        )
+    };
+
+    if offset.len() < 2 || !offset.is_ascii() {
+        return Err(invalid());
+    }
+
+    let (amount, unit) = offset.split_at(offset.len() - 1);
+    let amount = amount.parse::<u32>().map_err(|_| invalid())? as i64;
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    Ok(amount * seconds)
 }
 
 fn to_pubkey<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
@@ -123,6 +295,28 @@ where
     Pubkey::from_str(&s).map_err(serde::de::Error::custom)
 }
 
+/// Deserializes a wallet address, given as a pubkey or as a `.sol` domain.
+fn to_address<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = Deserialize::deserialize(deserializer)?;
+    parse_address(&s).map_err(serde::de::Error::custom)
+}
+
+fn to_option_address<'de, D>(deserializer: D) -> Result<Option<Pubkey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = match Deserialize::deserialize(deserializer) {
+        Ok(s) => s,
+        Err(_) => return Ok(None),
+    };
+
+    let pubkey = parse_address(&s).map_err(serde::de::Error::custom)?;
+    Ok(Some(pubkey))
+}
+
 fn to_option_pubkey<'de, D>(deserializer: D) -> Result<Option<Pubkey>, D::Error>
 where
     D: Deserializer<'de>,
@@ -136,8 +330,41 @@ where
     Ok(Some(pubkey))
 }
 
-fn discount_price_to_lamports(discount_price: Option<f64>) -> Option<u64> {
-    discount_price.map(|price| (price * LAMPORTS_PER_SOL as f64) as u64)
+/// Value of `number` for a collection size detected from the assets directory.
+pub const AUTO_NUMBER: &str = "auto";
+
+fn to_option_number<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Value(u64),
+        Text(String),
+    }
+
+    match Option::<Number>::deserialize(deserializer)? {
+        Some(Number::Value(number)) => Ok(Some(number)),
+        Some(Number::Text(text)) if text.trim().eq_ignore_ascii_case(AUTO_NUMBER) => Ok(None),
+        Some(Number::Text(text)) => text.trim().parse::<u64>().map(Some).map_err(|_| {
+            serde::de::Error::custom(format!(
+                "invalid number of items: {} (expected a number or \"{}\")",
+                text, AUTO_NUMBER
+            ))
+        }),
+        None => Ok(None),
+    }
+}
+
+fn to_auto_number<S>(number: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match number {
+        Some(number) => serializer.serialize_u64(*number),
+        None => serializer.serialize_str(AUTO_NUMBER),
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -160,12 +387,24 @@ impl GatekeeperConfig {
         }
     }
 
+    pub fn gatekeeper_network(&self) -> &Pubkey {
+        &self.gatekeeper_network
+    }
+
+    pub fn expire_on_use(&self) -> bool {
+        self.expire_on_use
+    }
+
     pub fn into_magichat_format(&self) -> MagicHatGatekeeperConfig {
         MagicHatGatekeeperConfig {
             gatekeeper_network: self.gatekeeper_network,
             expire_on_use: self.expire_on_use,
         }
     }
+
+    pub fn from_magichat_format(gatekeeper: &MagicHatGatekeeperConfig) -> GatekeeperConfig {
+        GatekeeperConfig::new(gatekeeper.gatekeeper_network, gatekeeper.expire_on_use)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -188,6 +427,12 @@ impl EndSettings {
             number,
         }
     }
+    pub fn end_setting_type(&self) -> &EndSettingType {
+        &self.end_setting_type
+    }
+    pub fn number(&self) -> u64 {
+        self.number
+    }
     pub fn into_magichat_format(&self) -> MagicHatEndSettings {
         MagicHatEndSettings {
             end_setting_type: match self.end_setting_type {
@@ -197,6 +442,16 @@ impl EndSettings {
             number: self.number,
         }
     }
+
+    pub fn from_magichat_format(end_settings: &MagicHatEndSettings) -> EndSettings {
+        EndSettings::new(
+            match end_settings.end_setting_type {
+                MagicHatEndSettingType::Date => EndSettingType::Date,
+                MagicHatEndSettingType::Amount => EndSettingType::Amount,
+            },
+            end_settings.number,
+        )
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -207,7 +462,8 @@ pub struct WhitelistMintSettings {
     #[serde(serialize_with = "to_string")]
     mint: Pubkey,
     presale: bool,
-    discount_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    discount_price: Option<Price>,
 }
 
 impl WhitelistMintSettings {
@@ -215,7 +471,7 @@ impl WhitelistMintSettings {
         mode: WhitelistMintMode,
         mint: Pubkey,
         presale: bool,
-        discount_price: Option<f64>,
+        discount_price: Option<Price>,
     ) -> WhitelistMintSettings {
         WhitelistMintSettings {
             mode,
@@ -224,13 +480,43 @@ impl WhitelistMintSettings {
             discount_price,
         }
     }
-    pub fn into_magichat_format(&self) -> MagicHatWhitelistMintSettings {
-        MagicHatWhitelistMintSettings {
+    pub fn discount_price(&self) -> Option<&Price> {
+        self.discount_price.as_ref()
+    }
+    /// The discount price is charged in the payment token, so it is scaled to the
+    /// token base units when `token_decimals` is set, to lamports otherwise.
+    pub fn into_magichat_format(
+        &self,
+        token_decimals: Option<u8>,
+    ) -> Result<MagicHatWhitelistMintSettings, ConfigError> {
+        let decimals = token_decimals.unwrap_or(SOL_DECIMALS);
+
+        Ok(MagicHatWhitelistMintSettings {
             mode: self.mode.into_magichat_format(),
             mint: self.mint,
             presale: self.presale,
-            discount_price: discount_price_to_lamports(self.discount_price),
-        }
+            discount_price: self
+                .discount_price
+                .as_ref()
+                .map(|price| price.to_base_units(decimals))
+                .transpose()?,
+        })
+    }
+
+    pub fn from_magichat_format(
+        settings: &MagicHatWhitelistMintSettings,
+        token_decimals: Option<u8>,
+    ) -> WhitelistMintSettings {
+        let decimals = token_decimals.unwrap_or(SOL_DECIMALS);
+
+        WhitelistMintSettings::new(
+            WhitelistMintMode::from_magichat_format(&settings.mode),
+            settings.mint,
+            settings.presale,
+            settings
+                .discount_price
+                .map(|price| Price::from_base_units(price, decimals)),
+        )
     }
 }
 
@@ -248,6 +534,13 @@ impl WhitelistMintMode {
             WhitelistMintMode::NeverBurn => MagicHatWhitelistMintMode::NeverBurn,
         }
     }
+
+    pub fn from_magichat_format(mode: &MagicHatWhitelistMintMode) -> WhitelistMintMode {
+        match mode {
+            MagicHatWhitelistMintMode::BurnEveryTime => WhitelistMintMode::BurnEveryTime,
+            MagicHatWhitelistMintMode::NeverBurn => WhitelistMintMode::NeverBurn,
+        }
+    }
 }
 
 impl FromStr for WhitelistMintMode {
@@ -273,6 +566,15 @@ impl HiddenSettings {
     pub fn new(name: String, uri: String, hash: String) -> HiddenSettings {
         HiddenSettings { name, uri, hash }
     }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
     pub fn into_magichat_format(&self) -> MagicHatHiddenSettings {
         MagicHatHiddenSettings {
             name: self.name.clone(),
@@ -284,6 +586,14 @@ impl HiddenSettings {
                 .expect("Hidden settings hash has to be 32 characters long!"),
         }
     }
+
+    pub fn from_magichat_format(settings: &MagicHatHiddenSettings) -> Result<HiddenSettings> {
+        Ok(HiddenSettings::new(
+            settings.name.clone(),
+            settings.uri.clone(),
+            String::from_utf8(settings.hash.to_vec())?,
+        ))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -332,9 +642,144 @@ impl<'de> Deserialize<'de> for UploadMethod {
     }
 }
 
+/// Upload method (`method`) and the settings specific to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method")]
+pub enum UploadConfig {
+    #[serde(rename = "bundlr")]
+    Bundlr(BundlrConfig),
+    #[serde(rename = "aws")]
+    Aws(AwsConfig),
+    #[serde(rename = "nft_storage")]
+    NftStorage(NftStorageConfig),
+}
+
+impl Default for UploadConfig {
+    fn default() -> UploadConfig {
+        UploadConfig::Bundlr(BundlrConfig::default())
+    }
+}
+
+impl UploadConfig {
+    pub fn method(&self) -> UploadMethod {
+        match self {
+            UploadConfig::Bundlr(_) => UploadMethod::Bundlr,
+            UploadConfig::Aws(_) => UploadMethod::AWS,
+            UploadConfig::NftStorage(_) => UploadMethod::NftStorage,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundlrConfig {
+    /// Bundlr node, defaults to the node of the cluster (devnet or mainnet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AwsConfig {
+    #[serde(alias = "awsS3Bucket")]
+    pub bucket: String,
+    /// Region of the bucket, defaults to the region of the AWS environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftStorageConfig {
+    #[serde(alias = "nftStorageAuthToken")]
+    pub auth_token: String,
+}
+
+impl ConfigData {
+    /// Go live date as a unix timestamp, `None` when no date is set.
+    pub fn go_live_timestamp(&self) -> Result<Option<i64>> {
+        match self.go_live_date {
+            Some(ref go_live_date) => Ok(Some(go_live_date_as_timestamp(
+                go_live_date,
+                self.timezone.as_deref(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// RPC url of the cluster of the config, `None` when no cluster is set.
+    pub fn cluster_rpc_url(&self) -> Result<Option<String>, ConfigError> {
+        let cluster = match &self.cluster {
+            Some(cluster) => cluster,
+            None => return Ok(None),
+        };
+
+        if let Some(rpc_url) = self.rpc_endpoints.get(cluster) {
+            return Ok(Some(rpc_url.clone()));
+        }
+
+        CLUSTER_PRESETS
+            .iter()
+            .find(|(name, _)| name == cluster)
+            .map(|(_, rpc_url)| Some(rpc_url.to_string()))
+            .ok_or_else(|| ConfigError::InvalidCluster(cluster.clone()))
+    }
+
+    /// Moves the upload fields of older configs (`uploadMethod`, `awsS3Bucket` and
+    /// `nftStorageAuthToken`) into the `upload` section. When present, they take
+    /// precedence over the section, so they can still be overridden by environment
+    /// variables.
+    pub fn migrate_upload_settings(&mut self) -> Result<(), ConfigError> {
+        let aws_s3_bucket = self.aws_s3_bucket.take();
+        let nft_storage_auth_token = self.nft_storage_auth_token.take();
+
+        let upload_method = match self.upload_method.take() {
+            Some(upload_method) => upload_method,
+            None => {
+                // e.g. only the bucket set by an environment variable
+                match &mut self.upload {
+                    UploadConfig::Aws(aws) => {
+                        if let Some(bucket) = aws_s3_bucket {
+                            aws.bucket = bucket;
+                        }
+                    }
+                    UploadConfig::NftStorage(nft_storage) => {
+                        if let Some(auth_token) = nft_storage_auth_token {
+                            nft_storage.auth_token = auth_token;
+                        }
+                    }
+                    UploadConfig::Bundlr(_) => (),
+                }
+                return Ok(());
+            }
+        };
+
+        let missing = |field: &str| {
+            ConfigError::ParseError(format!(
+                "missing '{}' value for upload method '{}'",
+                field,
+                upload_method.to_string()
+            ))
+        };
+
+        self.upload = match upload_method {
+            UploadMethod::Bundlr => UploadConfig::Bundlr(BundlrConfig::default()),
+            UploadMethod::AWS => UploadConfig::Aws(AwsConfig {
+                bucket: aws_s3_bucket.ok_or_else(|| missing("awsS3Bucket"))?,
+                region: None,
+            }),
+            UploadMethod::NftStorage => UploadConfig::NftStorage(NftStorageConfig {
+                auth_token: nft_storage_auth_token.ok_or_else(|| missing("nftStorageAuthToken"))?,
+            }),
+        };
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Default, Serialize)]
 pub struct Creator {
-    #[serde(deserialize_with = "to_pubkey")]
+    #[serde(deserialize_with = "to_address")]
     #[serde(serialize_with = "to_string")]
     pub address: Pubkey,
     pub share: u8,
@@ -350,6 +795,13 @@ impl Creator {
 
         Ok(creator)
     }
+
+    pub fn from_magichat_format(creator: &MagicHatCreator) -> Creator {
+        Creator {
+            address: creator.address,
+            share: creator.share,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -378,3 +830,93 @@ impl ToString for Cluster {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEW_YORK: Option<&str> = Some("America/New_York");
+
+    #[test]
+    fn parse_dates_with_offset() {
+        assert_eq!(
+            go_live_date_as_timestamp("2022-07-14T02:40:00-04:00", None).unwrap(),
+            1657780800
+        );
+        assert_eq!(
+            go_live_date_as_timestamp("Thu, 14 Jul 2022 02:40:00 -0400", None).unwrap(),
+            1657780800
+        );
+        assert_eq!(
+            go_live_date_as_timestamp("2022-07-14 02:40:00 -0400", NEW_YORK).unwrap(),
+            1657780800
+        );
+    }
+
+    #[test]
+    fn parse_timestamps_and_keywords() {
+        assert_eq!(
+            go_live_date_as_timestamp("1657780800", None).unwrap(),
+            1657780800
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let parsed = go_live_date_as_timestamp("now", None).unwrap();
+        assert!((now..now + 5).contains(&parsed));
+
+        let parsed = go_live_date_as_timestamp("+10m", None).unwrap();
+        assert!((now + 600..now + 605).contains(&parsed));
+
+        assert!(go_live_date_as_timestamp("-10m", None).is_err());
+        assert!(go_live_date_as_timestamp("+10w", None).is_err());
+    }
+
+    #[test]
+    fn parse_local_dates_in_timezone() {
+        assert_eq!(
+            go_live_date_as_timestamp("2022-07-14 02:40", NEW_YORK).unwrap(),
+            1657780800
+        );
+        assert_eq!(
+            go_live_date_as_timestamp("2022-07-14T02:40:00", NEW_YORK).unwrap(),
+            1657780800
+        );
+        // without a timezone the date is read as UTC
+        assert_eq!(
+            go_live_date_as_timestamp("2022-07-14 02:40", None).unwrap(),
+            1657766400
+        );
+        assert!(go_live_date_as_timestamp("2022-07-14 02:40", Some("Mars/Olympus")).is_err());
+    }
+
+    #[test]
+    fn parse_local_dates_at_dst_boundaries() {
+        // the clocks move from 02:00 to 03:00 on 2022-03-13 in New York
+        assert_eq!(
+            go_live_date_as_timestamp("2022-03-13 01:59", NEW_YORK).unwrap(),
+            1647154740
+        );
+        assert_eq!(
+            go_live_date_as_timestamp("2022-03-13 03:00", NEW_YORK).unwrap(),
+            1647154800
+        );
+        let error = go_live_date_as_timestamp("2022-03-13 02:30", NEW_YORK).unwrap_err();
+        assert!(error.to_string().contains("does not exist"));
+
+        // the clocks move from 02:00 back to 01:00 on 2022-11-06 in New York
+        let error = go_live_date_as_timestamp("2022-11-06 01:30", NEW_YORK).unwrap_err();
+        assert!(error.to_string().contains("Ambiguous"));
+        assert_eq!(
+            go_live_date_as_timestamp("2022-11-06 02:00", NEW_YORK).unwrap(),
+            1667718000
+        );
+    }
+
+    #[test]
+    fn reject_unknown_formats() {
+        for date in ["14/07/2022", "2022-07-14", "tomorrow", ""] {
+            let error = go_live_date_as_timestamp(date, None).unwrap_err();
+            assert!(error.to_string().contains("Accepted formats"), "{}", date);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use console::style;
+use spl_token::id as token_program_id;
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+use crate::cache::load_cache;
+use crate::common::*;
+use crate::config::data::*;
+use crate::config::price::Price;
+use crate::magic_hat::get_magic_hat_state;
+use crate::utils::{check_spl_token, spinner_with_style};
+
+pub struct ConfigDumpArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub magic_hat: Option<String>,
+    pub out: String,
+    pub force: bool,
+}
+
+pub fn process_config_dump(args: ConfigDumpArgs) -> Result<()> {
+    if Path::new(&args.out).exists() && !args.force {
+        let error = anyhow!(
+            "File {} already exists, use --force to overwrite it",
+            args.out
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    // the magic hat id specified takes precedence over the one from the cache
+
+    let magic_hat_id = match args.magic_hat {
+        Some(magic_hat_id) => magic_hat_id,
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.magic_hat
+        }
+    };
+
+    let magichat_pubkey = match Pubkey::from_str(&magic_hat_id) {
+        Ok(magichat_pubkey) => magichat_pubkey,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat_id);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    println!(
+        "{} {}Loading Magic Hat",
+        style("[1/1]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&laddu_config)?;
+    let magic_hat_state = get_magic_hat_state(&laddu_config, &magichat_pubkey)?;
+
+    let token_decimals = match magic_hat_state.token_mint {
+        Some(token_mint) => {
+            let token_program = client.program(token_program_id());
+            Some(check_spl_token(&token_program, &token_mint.to_string())?.decimals)
+        }
+        None => None,
+    };
+
+    pb.finish_with_message("Done");
+
+    let mut warnings = Vec::new();
+    let config_data = dump_config_data(&magic_hat_state, token_decimals, &mut warnings)?;
+
+    fs::write(&args.out, serde_json::to_string_pretty(&config_data)?)?;
+
+    // these values are not stored on chain
+    warnings.push(
+        "the upload settings are not stored on chain (bundlr is written), \
+         review them before uploading"
+            .to_string(),
+    );
+
+    println!();
+    for warning in warnings {
+        warn!("{}", warning);
+        println!("{} {}", style("Warning:").yellow().bold(), warning);
+    }
+
+    println!(
+        "\n{} {}",
+        style("Config written to").bold(),
+        style(&args.out).bold()
+    );
+
+    Ok(())
+}
+
+/// Format an on-chain go live timestamp as an RFC3339 date in UTC.
+fn timestamp_as_go_live_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(timestamp, 0), Utc).to_rfc3339()
+}
+
+/// Convert the on-chain magic hat data back into the config file format.
+fn dump_config_data(
+    state: &MagicHat,
+    token_decimals: Option<u8>,
+    warnings: &mut Vec<String>,
+) -> Result<ConfigData> {
+    let data = &state.data;
+
+    // prices are exact decimals, any amount of base units can be written back
+    let price = match token_decimals {
+        Some(decimals) => Price::from_base_units(data.price, decimals),
+        None => Price::from_lamports(data.price),
+    };
+
+    if data.go_live_date.is_none() {
+        warnings.push(
+            "go live date is not set on chain, only the authority (or whitelisted presale \
+             users) can mint"
+                .to_string(),
+        );
+    }
+    let go_live_date = data.go_live_date.map(timestamp_as_go_live_date);
+
+    let hidden_settings = match data.hidden_settings {
+        Some(ref hidden_settings) => Some(HiddenSettings::from_magichat_format(hidden_settings)?),
+        None => None,
+    };
+
+    // the treasury is either a SOL wallet or the token account receiving the
+    // SPL token payments
+    let (sol_treasury_account, spl_token_account) = match state.token_mint {
+        Some(_) => (None, Some(state.wallet)),
+        None => (Some(state.wallet), None),
+    };
+
+    Ok(ConfigData {
+        price,
+        number: Some(data.items_available),
+        gatekeeper: data
+            .gatekeeper
+            .as_ref()
+            .map(GatekeeperConfig::from_magichat_format),
+        creators: data
+            .creators
+            .iter()
+            .map(Creator::from_magichat_format)
+            .collect(),
+        sol_treasury_account,
+        spl_token_account,
+        spl_token: state.token_mint,
+        go_live_date,
+        timezone: None,
+        end_settings: data
+            .end_settings
+            .as_ref()
+            .map(EndSettings::from_magichat_format),
+        whitelist_mint_settings: data
+            .whitelist_mint_settings
+            .as_ref()
+            .map(|settings| WhitelistMintSettings::from_magichat_format(settings, token_decimals)),
+        hidden_settings,
+        cluster: None,
+        rpc_endpoints: BTreeMap::new(),
+        upload: UploadConfig::default(),
+        upload_method: None,
+        retain_authority: data.retain_authority,
+        is_mutable: data.is_mutable,
+        aws_s3_bucket: None,
+        nft_storage_auth_token: None,
+        symbol: data.symbol.clone(),
+        seller_fee_basis_points: data.seller_fee_basis_points,
+        priority_fee: None,
+        compute_units: None,
+        max_image_bytes: None,
+        max_image_dimension: None,
+        max_animation_bytes: None,
+        collection_mint: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn magic_hat_state(go_live_date: Option<i64>) -> MagicHat {
+        MagicHat {
+            authority: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            token_mint: None,
+            items_redeemed: 0,
+            data: magic_hat::MagicHatData {
+                uuid: "ABCDEF".to_string(),
+                price: 1_500_000_000,
+                symbol: "HAT".to_string(),
+                seller_fee_basis_points: 500,
+                max_supply: 0,
+                is_mutable: true,
+                retain_authority: true,
+                go_live_date,
+                end_settings: None,
+                creators: vec![magic_hat::Creator {
+                    address: Pubkey::new_unique(),
+                    verified: false,
+                    share: 100,
+                }],
+                hidden_settings: None,
+                whitelist_mint_settings: Some(magic_hat::WhitelistMintSettings {
+                    mode: magic_hat::WhitelistMintMode::NeverBurn,
+                    mint: Pubkey::new_unique(),
+                    presale: true,
+                    discount_price: Some(500_000_000),
+                }),
+                items_available: 10,
+                gatekeeper: None,
+            },
+        }
+    }
+
+    #[test]
+    fn config_data_round_trips() {
+        for go_live_date in [Some(1657780800), None] {
+            let state = magic_hat_state(go_live_date);
+            let config_data = dump_config_data(&state, None, &mut Vec::new()).unwrap();
+            let json = serde_json::to_string_pretty(&config_data).unwrap();
+
+            // an unset go live date is left out instead of written as an invalid date
+            assert_eq!(json.contains("goLiveDate"), go_live_date.is_some());
+
+            let parsed: ConfigData = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string_pretty(&parsed).unwrap(), json);
+
+            assert_eq!(parsed.price.to_lamports().unwrap(), state.data.price);
+            assert_eq!(parsed.go_live_timestamp().unwrap(), go_live_date);
+            assert_eq!(parsed.number, Some(state.data.items_available));
+            assert_eq!(parsed.sol_treasury_account, Some(state.wallet));
+            assert_eq!(
+                parsed
+                    .whitelist_mint_settings
+                    .unwrap()
+                    .into_magichat_format(None)
+                    .unwrap()
+                    .discount_price,
+                Some(500_000_000)
+            );
+        }
+    }
+
+    #[test]
+    fn go_live_date_round_trips() {
+        // a summer date, and the seconds around the New York DST changes
+        for timestamp in [1657780800, 1647154740, 1647154800, 1667714400, 1667718000] {
+            let date = timestamp_as_go_live_date(timestamp);
+
+            assert_eq!(go_live_date_as_timestamp(&date, None).unwrap(), timestamp);
+            // the dumped date has an offset, so the config timezone does not apply
+            assert_eq!(
+                go_live_date_as_timestamp(&date, Some("America/New_York")).unwrap(),
+                timestamp
+            );
+        }
+    }
+}
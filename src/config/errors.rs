@@ -19,4 +19,13 @@ pub enum ConfigError {
 
     #[error("Invalid upload method '{0}'")]
     InvalidUploadMethod(String),
+
+    #[error("Invalid price '{0}', expected a positive decimal number")]
+    InvalidPrice(String),
+
+    #[error("Price {0} has more decimals than the {1} supported by the payment mint")]
+    PriceTooPrecise(String, u8),
+
+    #[error("Price {0} is too large")]
+    PriceOverflow(String),
 }
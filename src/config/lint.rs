@@ -0,0 +1,130 @@
+use console::{style, user_attended};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+
+use magic_hat::MagicHatData;
+
+use crate::common::*;
+use crate::config::data::ConfigData;
+use crate::utils::{is_non_interactive, NON_INTERACTIVE_ENV};
+
+/// The settings that are valid but almost always a mistake, shared by create-config,
+/// deploy and update so the three commands warn about the same things.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskySettings {
+    pub retain_authority: bool,
+    pub is_mutable: bool,
+    pub seller_fee_basis_points: u16,
+    pub go_live_date: Option<i64>,
+}
+
+impl RiskySettings {
+    pub fn from_config(config_data: &ConfigData) -> Result<Self> {
+        Ok(RiskySettings {
+            retain_authority: config_data.retain_authority,
+            is_mutable: config_data.is_mutable,
+            seller_fee_basis_points: config_data.seller_fee_basis_points,
+            go_live_date: config_data.go_live_timestamp()?,
+        })
+    }
+
+    pub fn from_magic_hat_data(data: &MagicHatData) -> Self {
+        RiskySettings {
+            retain_authority: data.retain_authority,
+            is_mutable: data.is_mutable,
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            go_live_date: data.go_live_date,
+        }
+    }
+}
+
+/// Returns an explanation for each risky setting. When the current settings are
+/// given, only the values that change are reported.
+pub fn lint_risky_settings(
+    settings: &RiskySettings,
+    current: Option<&RiskySettings>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if !settings.retain_authority && current.map_or(true, |current| current.retain_authority) {
+        warnings.push(
+            "retainAuthority is false: the update authority of each NFT goes to its minter, \
+             so the collection cannot be set on-chain and the metadata cannot be fixed later"
+                .to_string(),
+        );
+    }
+
+    if !settings.is_mutable && current.map_or(true, |current| current.is_mutable) {
+        warnings.push(
+            "isMutable is false: the metadata of each NFT can never be changed, \
+             not even to fix a broken image link"
+                .to_string(),
+        );
+    }
+
+    if settings.seller_fee_basis_points == 0
+        && current.map_or(true, |current| current.seller_fee_basis_points != 0)
+    {
+        warnings.push(
+            "sellerFeeBasisPoints is 0: creators receive no royalties on secondary sales"
+                .to_string(),
+        );
+    }
+
+    if let Some(go_live_date) = settings.go_live_date {
+        let changed = current.map_or(true, |current| current.go_live_date != Some(go_live_date));
+        if changed && go_live_date < chrono::Utc::now().timestamp() {
+            warnings.push(
+                "goLiveDate is in the past: the mint is live as soon as the Magic Hat is deployed"
+                    .to_string(),
+            );
+        }
+    }
+
+    warnings
+}
+
+/// Prints the risky settings and asks to go ahead with them. Returns false when the
+/// user declines; `yes` (or running non-interactively) accepts them after printing.
+pub fn confirm_risky_settings(
+    settings: &RiskySettings,
+    current: Option<&RiskySettings>,
+    yes: bool,
+) -> Result<bool> {
+    let warnings = lint_risky_settings(settings, current);
+
+    if warnings.is_empty() {
+        return Ok(true);
+    }
+
+    println!(
+        "{}",
+        style("WARNING: the config has settings that are probably a mistake:")
+            .yellow()
+            .bold()
+    );
+    for warning in &warnings {
+        warn!("{}", warning);
+        println!("  - {}", warning);
+    }
+    println!();
+
+    if yes || is_non_interactive() {
+        return Ok(true);
+    }
+
+    // fails instead of blocking on a prompt that nobody can answer
+    if !user_attended() {
+        let error = anyhow!(
+            "Risky config settings require confirmation; use --yes or set {} \
+             when running without a terminal",
+            NON_INTERACTIVE_ENV
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt("Continue with these settings?")
+        .default(false)
+        .interact()?)
+}
@@ -1,7 +1,15 @@
+pub mod check;
 pub mod data;
+pub mod dump;
 pub mod errors;
+pub mod lint;
 pub mod parser;
+pub mod price;
 
+pub use check::*;
 pub use data::*;
+pub use dump::*;
 pub use errors::*;
+pub use lint::*;
 pub use parser::*;
+pub use price::*;
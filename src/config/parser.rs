@@ -1,15 +1,44 @@
 use anyhow::Result;
+use serde_json::json;
 use std::fs::metadata;
 use std::fs::OpenOptions;
-use std::io::ErrorKind;
-use tracing::error;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use tracing::{debug, error};
 
+use crate::cli::ConfigFormat;
 use crate::config::data::*;
 use crate::config::errors::ConfigError;
 
+impl ConfigFormat {
+    /// Format of a config file based on its extension, JSON when it is not known.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ConfigFormat {
+        match path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .as_deref()
+        {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// Extension of the config files in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Toml => "toml",
+        }
+    }
+}
+
 pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
     // checks that the config file exists and it is readable
-    let f = match OpenOptions::new().read(true).open(config_path) {
+    let mut f = match OpenOptions::new().read(true).open(config_path) {
         Ok(f) => f,
         Err(err) => {
             let error = match err.kind() {
@@ -28,13 +57,143 @@ pub fn get_config_data(config_path: &str) -> Result<ConfigData, ConfigError> {
         return Err(error);
     }
 
-    let config_data: ConfigData = match serde_json::from_reader(f) {
+    let mut contents = String::new();
+    if f.read_to_string(&mut contents).is_err() {
+        let error = ConfigError::PermissionError(config_path.to_string());
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    // the error of each parser includes the line and column of the problem
+    let config_data: Result<ConfigData, String> = match ConfigFormat::from_path(config_path) {
+        ConfigFormat::Json => serde_json::from_str(&contents).map_err(|err| err.to_string()),
+        ConfigFormat::Yaml => serde_yaml::from_str(&contents).map_err(|err| err.to_string()),
+        ConfigFormat::Toml => toml::from_str(&contents).map_err(|err| err.to_string()),
+    };
+
+    let mut config_data: ConfigData = match config_data {
         Ok(config_data) => config_data,
         Err(err) => {
-            let error = ConfigError::ParseError(err.to_string());
+            let error = ConfigError::ParseError(err);
             error!("{:?}", error);
             return Err(error);
         }
     };
+
+    // older configs have the upload fields at the top level, they are moved before and
+    // after the environment variables, which can set them too
+    if let Err(error) = config_data.migrate_upload_settings() {
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let mut config_data = apply_env_overrides(config_data)?;
+
+    if let Err(error) = config_data.migrate_upload_settings() {
+        error!("{:?}", error);
+        return Err(error);
+    }
+
     Ok(config_data)
 }
+
+/// Fields of the config which can be overridden by an environment variable.
+pub const CONFIG_FIELDS: &[&str] = &[
+    "price",
+    "number",
+    "gatekeeper",
+    "creators",
+    "solTreasuryAccount",
+    "splTokenAccount",
+    "splToken",
+    "goLiveDate",
+    "timezone",
+    "endSettings",
+    "whitelistMintSettings",
+    "hiddenSettings",
+    "cluster",
+    "rpcEndpoints",
+    "upload",
+    "uploadMethod",
+    "retainAuthority",
+    "isMutable",
+    "awsS3Bucket",
+    "nftStorageAuthToken",
+    "symbol",
+    "sellerFeeBasisPoints",
+    "priorityFee",
+    "computeUnits",
+    "maxImageBytes",
+    "maxImageDimension",
+    "maxAnimationBytes",
+    "collectionMint",
+];
+
+/// Name of the environment variable overriding a config field (e.g. `goLiveDate`
+/// is overridden by `LADDU_GO_LIVE_DATE`).
+pub fn env_var_name(field: &str) -> String {
+    let mut name = String::from("LADDU_");
+    for c in field.chars() {
+        if c.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+/// Config fields set by an environment variable, with the name and value of the
+/// variable.
+pub fn config_env_overrides() -> Vec<(&'static str, String, String)> {
+    CONFIG_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let name = env_var_name(field);
+            std::env::var(&name).ok().map(|value| (*field, name, value))
+        })
+        .collect()
+}
+
+/// Applies the environment variables to the config values: a CLI flag takes
+/// precedence over an environment variable, which takes precedence over the config
+/// file. Values are read as JSON (e.g. numbers, booleans or objects), falling back to
+/// a plain string.
+fn apply_env_overrides(config_data: ConfigData) -> Result<ConfigData, ConfigError> {
+    let overrides = config_env_overrides();
+    if overrides.is_empty() {
+        return Ok(config_data);
+    }
+
+    let to_error = |err: serde_json::Error| ConfigError::ParseError(err.to_string());
+    let mut value = serde_json::to_value(&config_data).map_err(to_error)?;
+
+    for (field, name, raw) in &overrides {
+        let parsed = serde_json::from_str(raw).unwrap_or_else(|_| json!(raw));
+        value[*field] = parsed;
+        // e.g. a symbol made of digits is still a string
+        if serde_json::from_value::<ConfigData>(value.clone()).is_err() {
+            value[*field] = json!(raw);
+        }
+
+        match serde_json::from_value::<ConfigData>(value.clone()) {
+            Ok(_) => debug!("Config value {} set by {}", field, name),
+            Err(err) => {
+                let error = ConfigError::ParseError(format!("invalid value of {}: {}", name, err));
+                error!("{:?}", error);
+                return Err(error);
+            }
+        }
+    }
+
+    serde_json::from_value(value).map_err(to_error)
+}
+
+/// Serializes the config in the given format.
+pub fn config_to_string(config_data: &ConfigData, format: ConfigFormat) -> Result<String> {
+    Ok(match format {
+        ConfigFormat::Json => serde_json::to_string_pretty(config_data)?,
+        ConfigFormat::Yaml => serde_yaml::to_string(config_data)?,
+        // TOML needs the plain values before the tables, which the toml value orders
+        ConfigFormat::Toml => toml::to_string_pretty(&toml::Value::try_from(config_data)?)?,
+    })
+}
@@ -0,0 +1,290 @@
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::config::errors::ConfigError;
+
+/// Number of decimals of SOL (1 SOL = 10^9 lamports).
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Price of the config as an exact decimal number. The price is kept as its digits
+/// and scaled to lamports or token base units with integer arithmetic, so values
+/// like 0.1 are not subject to floating point rounding.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Price {
+    /// Integer part, without leading zeros (empty for zero)
+    integer: String,
+    /// Fractional part, without trailing zeros
+    fraction: String,
+}
+
+impl Price {
+    /// Price of an amount in base units of a mint with the given decimals.
+    pub fn from_base_units(amount: u64, decimals: u8) -> Price {
+        let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+        Price::from_parts(integer, fraction)
+    }
+
+    pub fn from_lamports(lamports: u64) -> Price {
+        Price::from_base_units(lamports, SOL_DECIMALS)
+    }
+
+    /// Scales the price to base units of a mint with the given decimals, failing if
+    /// the price has more decimals than the mint supports.
+    pub fn to_base_units(&self, decimals: u8) -> Result<u64, ConfigError> {
+        if self.fraction.len() > decimals as usize {
+            return Err(ConfigError::PriceTooPrecise(self.to_string(), decimals));
+        }
+
+        let digits = format!(
+            "{}{:0<width$}",
+            self.integer,
+            self.fraction,
+            width = decimals as usize
+        );
+        let digits = digits.trim_start_matches('0');
+
+        if digits.is_empty() {
+            return Ok(0);
+        }
+        digits
+            .parse::<u64>()
+            .map_err(|_| ConfigError::PriceOverflow(self.to_string()))
+    }
+
+    pub fn to_lamports(&self) -> Result<u64, ConfigError> {
+        self.to_base_units(SOL_DECIMALS)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.integer.is_empty() && self.fraction.is_empty()
+    }
+
+    fn from_parts(integer: &str, fraction: &str) -> Price {
+        Price {
+            integer: integer.trim_start_matches('0').to_string(),
+            fraction: fraction.trim_end_matches('0').to_string(),
+        }
+    }
+}
+
+impl FromStr for Price {
+    type Err = ConfigError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let value = input.trim();
+        let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (integer.is_empty() && fraction.is_empty())
+            || !is_digits(integer)
+            || !is_digits(fraction)
+        {
+            return Err(ConfigError::InvalidPrice(input.to_string()));
+        }
+
+        Ok(Price::from_parts(integer, fraction))
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let integer = if self.integer.is_empty() {
+            "0"
+        } else {
+            &self.integer
+        };
+
+        if self.fraction.is_empty() {
+            write!(f, "{}", integer)
+        } else {
+            write!(f, "{}.{}", integer, self.fraction)
+        }
+    }
+}
+
+impl Ord for Price {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // without leading zeros, a longer integer part is a larger number
+        self.integer
+            .len()
+            .cmp(&other.integer.len())
+            .then_with(|| self.integer.cmp(&other.integer))
+            .then_with(|| self.fraction.cmp(&other.fraction))
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Prices are written as numbers, unless the number would not read back as the
+/// same decimal (e.g. too many digits), in which case a string is used.
+impl Serialize for Price {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.to_string();
+        match value.parse::<f64>() {
+            Ok(number) if number.to_string() == value => serializer.serialize_f64(number),
+            _ => serializer.serialize_str(&value),
+        }
+    }
+}
+
+/// Prices are read from a number or a decimal string. A number is taken as its
+/// shortest decimal representation, which is what was written in the file.
+impl<'de> Deserialize<'de> for Price {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PriceVisitor;
+
+        impl<'de> Visitor<'de> for PriceVisitor {
+            type Value = Price;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a positive decimal number or string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Price, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Price, E> {
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Price, E> {
+                if !value.is_finite() {
+                    return Err(E::custom(ConfigError::InvalidPrice(value.to_string())));
+                }
+                self.visit_str(&value.to_string())
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Price, E> {
+                Price::from_str(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(PriceVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(value: &str) -> Price {
+        Price::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn parse_whole_and_fractional_values() {
+        assert_eq!(price("1").to_lamports().unwrap(), 1_000_000_000);
+        assert_eq!(price("0.1").to_lamports().unwrap(), 100_000_000);
+        assert_eq!(price("2.675").to_lamports().unwrap(), 2_675_000_000);
+        assert_eq!(price("0.000000001").to_lamports().unwrap(), 1);
+        assert_eq!(price(" 007.50 ").to_string(), "7.5");
+        assert_eq!(price(".5").to_string(), "0.5");
+        assert_eq!(price("3.").to_string(), "3");
+        assert!(price("0.000").is_zero());
+        assert_eq!(price("0").to_lamports().unwrap(), 0);
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        for value in ["", ".", "-1", "1.2.3", "1e9", "one", "1,5"] {
+            assert!(
+                matches!(Price::from_str(value), Err(ConfigError::InvalidPrice(_))),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn reject_too_many_decimal_places() {
+        assert!(matches!(
+            price("0.0000000001").to_lamports(),
+            Err(ConfigError::PriceTooPrecise(_, SOL_DECIMALS))
+        ));
+        assert!(matches!(
+            price("1.5").to_base_units(0),
+            Err(ConfigError::PriceTooPrecise(_, 0))
+        ));
+        // trailing zeros do not count as decimals
+        assert_eq!(price("1.50000000000").to_base_units(1).unwrap(), 15);
+    }
+
+    #[test]
+    fn reject_overflowing_values() {
+        assert_eq!(
+            Price::from_base_units(u64::MAX, SOL_DECIMALS)
+                .to_lamports()
+                .unwrap(),
+            u64::MAX
+        );
+        assert!(matches!(
+            price("18446744073.709551616").to_lamports(),
+            Err(ConfigError::PriceOverflow(_))
+        ));
+        assert!(matches!(
+            price("18446744073709551616").to_base_units(0),
+            Err(ConfigError::PriceOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn scale_to_token_decimals() {
+        // a token without decimals
+        assert_eq!(price("42").to_base_units(0).unwrap(), 42);
+        assert_eq!(Price::from_base_units(42, 0).to_string(), "42");
+
+        // a token with the same decimals as SOL
+        assert_eq!(price("0.5").to_base_units(9).unwrap(), 500_000_000);
+        assert_eq!(Price::from_base_units(1, 9).to_string(), "0.000000001");
+
+        // USDC-like token
+        assert_eq!(price("1.25").to_base_units(6).unwrap(), 1_250_000);
+    }
+
+    #[test]
+    fn round_trip_base_units() {
+        for decimals in [0, 2, 6, 9] {
+            for amount in [0, 1, 10, 123_456_789, 1_000_000_000, u64::MAX] {
+                let price = Price::from_base_units(amount, decimals);
+                assert_eq!(price.to_base_units(decimals).unwrap(), amount);
+                assert_eq!(Price::from_str(&price.to_string()).unwrap(), price);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_serialization() {
+        for value in [
+            "0",
+            "1",
+            "0.1",
+            "2.675",
+            "1.000000001",
+            "18446744073.709551615",
+        ] {
+            let json = serde_json::to_string(&price(value)).unwrap();
+            assert_eq!(serde_json::from_str::<Price>(&json).unwrap(), price(value));
+        }
+        assert_eq!(serde_json::from_str::<Price>("0.1").unwrap(), price("0.1"));
+        assert_eq!(
+            serde_json::from_str::<Price>("\"2.5\"").unwrap(),
+            price("2.5")
+        );
+    }
+
+    #[test]
+    fn compare_prices() {
+        assert!(price("10") > price("9.99"));
+        assert!(price("0.5") < price("0.51"));
+        assert_eq!(price("1.0"), price("01"));
+    }
+}
@@ -68,6 +68,30 @@ pub const DEFAULT_CACHE: &str = "cache.json";
 /// Default path for config file.
 pub const DEFAULT_CONFIG: &str = "config.json";
 
+/// Default path for the minted NFTs output file (one JSON record per line).
+pub const DEFAULT_MINT_OUT: &str = "mints.json";
+
+/// Default path for the bulk mint progress file.
+pub const DEFAULT_MINT_STATE: &str = "mint-state.json";
+
+/// Default path for the airdrop results file.
+pub const DEFAULT_AIRDROP_RESULTS: &str = "airdrop-results.json";
+
+/// Default path for the verify report file.
+pub const DEFAULT_VERIFY_REPORT: &str = "verify-report.json";
+
+/// Default path for the verify checkpoint file.
+pub const DEFAULT_VERIFY_STATE: &str = "verify-state.json";
+
+/// Default path for the validation report file.
+pub const DEFAULT_VALIDATION_REPORT: &str = "validation-report.json";
+
+/// Seller fee basis points above which validate warns about the royalties.
+pub const DEFAULT_SELLER_FEE_THRESHOLD: &str = "2000";
+
+/// File name (without extension) of the collection metadata and image.
+pub const COLLECTION_FILENAME: &str = "collection";
+
 /// Default path for keypair file.
 pub const DEFAULT_KEYPATH: &str = "~/.config/solana/id.json";
 
@@ -80,10 +104,26 @@ pub const BUNDLR_MAINNET: &str = "https://node1.bundlr.network";
 /// Default RPC endpoint for devnet.
 pub const DEFAULT_RPC_DEVNET: &str = "https://psytrbhymqlkfrhudd.dev.genesysgo.net:8899/";
 
+/// Clusters of the config and their public RPC endpoints.
+pub const CLUSTER_PRESETS: &[(&str, &str)] = &[
+    ("mainnet-beta", "https://api.mainnet-beta.solana.com"),
+    ("devnet", "https://api.devnet.solana.com"),
+    ("localnet", "http://127.0.0.1:8899"),
+];
+
 pub const CIVIC_NETWORK: &str = "ignREusXmGrscGNUesoU9mxfds9AiYTezUKex2PsZV6";
 
 pub const ENCORE_NETWORK: &str = "tibePmPaoTgrs929rWpu755EXaxC7M3SthVCf6GzjZt";
 
+pub const CIVIC_UNIQUENESS_NETWORK: &str = "uniqobk8oGh4XBLMqM68K8M2zNu3CdYX7q5go7whQiv";
+
+/// Known gatekeeper networks, offered by create-config.
+pub const GATEKEEPER_NETWORKS: &[(&str, &str)] = &[
+    ("Civic Pass (captcha)", CIVIC_NETWORK),
+    ("Civic Pass (uniqueness)", CIVIC_UNIQUENESS_NETWORK),
+    ("Verify by Encore", ENCORE_NETWORK),
+];
+
 pub const LOOKING_GLASS_EMOJI: Emoji<'_, '_> = Emoji("🔍 ", "");
 
 pub const MAGICHAT_EMOJI: Emoji<'_, '_> = Emoji("🍬 ", "");
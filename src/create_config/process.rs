@@ -1,28 +1,35 @@
 use anchor_lang::prelude::Pubkey;
 use anyhow::{anyhow, Result};
-use chrono::DateTime;
+use chrono::{offset::LocalResult, DateTime, TimeZone, Utc};
 use console::{style, Style};
 use dialoguer::Confirm;
 use dialoguer::{theme::ColorfulTheme, Input, MultiSelect, Select};
 use std::{
     default::Default,
+    fmt::Display,
     fs::{File, OpenOptions},
+    io::Write,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
 };
+use tracing::error;
 use url::Url;
 
+use crate::cli::ConfigFormat;
 use crate::config::{
-    parse_string_as_date, ConfigData, Creator, EndSettingType, EndSettings, GatekeeperConfig,
-    HiddenSettings, UploadMethod, WhitelistMintMode, WhitelistMintSettings,
+    config_to_string, confirm_risky_settings, get_config_data, go_live_date_as_timestamp,
+    lint_risky_settings, parse_string_as_date, AwsConfig, BundlrConfig, ConfigData, Creator,
+    EndSettingType, EndSettings, GatekeeperConfig, HiddenSettings, NftStorageConfig, Price,
+    RiskySettings, UploadConfig, UploadMethod, WhitelistMintMode, WhitelistMintSettings,
 };
 use crate::constants::*;
 use crate::magic_hat::MAGIC_HAT_ID;
 use crate::setup::{laddu_setup, setup_client};
-use crate::upload::list_files;
+use crate::sns::parse_address;
+use crate::upload::{get_asset_pairs, list_files};
 use crate::utils::{check_spl_token, check_spl_token_account};
-use crate::validate::Metadata;
+use crate::validate::{check_hidden_settings, reveal_hash_from_dir, Metadata, HIDDEN_HASH_LENGTH};
 
 /// Default name of the first metadata file.
 const DEFAULT_METADATA: &str = "0.json";
@@ -37,11 +44,70 @@ pub struct CreateConfigArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub config: Option<String>,
+    pub format: Option<ConfigFormat>,
     pub assets_dir: String,
+    pub non_interactive: bool,
+    pub yes: bool,
+    pub price: Option<Price>,
+    pub number: Option<u64>,
+    pub symbol: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+    pub go_live_date: Option<String>,
+    pub creators: Vec<String>,
+    pub sol_treasury_account: Option<String>,
+    pub spl_token: Option<String>,
+    pub spl_token_account: Option<String>,
+    pub gatekeeper_network: Option<String>,
+    pub gatekeeper_expire_on_use: Option<bool>,
+    pub whitelist_mint: Option<String>,
+    pub whitelist_burn: Option<bool>,
+    pub whitelist_presale: Option<bool>,
+    pub whitelist_discount_price: Option<Price>,
+    pub end_settings_date: Option<String>,
+    pub end_settings_amount: Option<u64>,
+    pub hidden_name: Option<String>,
+    pub hidden_uri: Option<String>,
+    pub hidden_hash: Option<String>,
+    pub upload_method: Option<UploadMethod>,
+    pub aws_s3_bucket: Option<String>,
+    pub aws_region: Option<String>,
+    pub nft_storage_auth_token: Option<String>,
+    pub retain_authority: Option<bool>,
+    pub is_mutable: Option<bool>,
+}
+
+/// Values found in the assets directory, which answer their prompts when the config
+/// is created without prompting.
+#[derive(Default)]
+struct AssetDefaults {
+    number: Option<u64>,
+    symbol: Option<String>,
+    seller_fee_basis_points: Option<u16>,
+    /// Whether the hidden settings hash can be computed from the metadata files
+    reveal_hash: bool,
 }
 
 pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     let mut config_data: ConfigData = ConfigData::default();
+    let file_path = match (args.config, args.format) {
+        (Some(config), _) => config,
+        (None, Some(format)) => Path::new(DEFAULT_CONFIG)
+            .with_extension(format.extension())
+            .to_string_lossy()
+            .to_string(),
+        (None, None) => DEFAULT_CONFIG.to_string(),
+    };
+    let format = args
+        .format
+        .unwrap_or_else(|| ConfigFormat::from_path(&file_path));
+    // settings of an existing config file which are offered to be kept
+    let existing_end_settings = if Path::new(&file_path).is_file() {
+        get_config_data(&file_path)
+            .ok()
+            .and_then(|config_data| config_data.end_settings)
+    } else {
+        None
+    };
     let theme = ColorfulTheme {
         prompt_style: Style::new(),
         checked_item_prefix: style("✔".to_string()).green().force_styling(true),
@@ -59,10 +125,17 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         }
     };
 
-    let float_validator = |input: &String| -> Result<(), String> {
-        if !input.is_empty() && input.parse::<f64>().is_err() {
+    // wallets can also be given as a .sol domain, resolved when validated
+    let address_validator = |input: &String| -> Result<(), String> {
+        parse_address(input)
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    };
+
+    let price_validator = |input: &String| -> Result<(), String> {
+        if !input.is_empty() && Price::from_str(input).is_err() {
             Err(format!(
-                "Couldn't parse price input of '{}' to a float.",
+                "Couldn't parse price input of '{}' to a decimal number.",
                 input
             ))
         } else {
@@ -77,17 +150,6 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         }
     };
 
-    let date_validator = |input: &String| -> Result<(), String> {
-        if parse_string_as_date(input).is_err() {
-            Err(format!(
-                "Couldn't parse input of '{}' to a valid date.",
-                input
-            ))
-        } else {
-            Ok(())
-        }
-    };
-
     let url_validator = |input: &String| -> Result<(), String> {
         if Url::parse(input).is_err() {
             Err(format!(
@@ -155,37 +217,80 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         seller_fee = metadata.seller_fee_basis_points;
     }
 
+    let num_pairs = get_asset_pairs(&args.assets_dir)
+        .map(|pairs| pairs.len())
+        .unwrap_or_default();
+
+    // the prompts are skipped when every required value is given by a flag, values
+    // found in the assets directory are then used for the flags not given
+    let interactive =
+        !args.non_interactive && !missing_values(&args, &AssetDefaults::default()).is_empty();
+
+    if !interactive {
+        let defaults = AssetDefaults {
+            number: (num_pairs > 0).then(|| num_pairs as u64),
+            symbol: (num_files > 0).then(|| symbol.clone()),
+            seller_fee_basis_points: (num_files > 0).then(|| seller_fee),
+            reveal_hash: num_files > 0,
+        };
+        let missing = missing_values(&args, &defaults);
+
+        if !missing.is_empty() {
+            let error = anyhow!(
+                "Missing value(s) to create the config without prompting: {}",
+                missing.join(", ")
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+    }
+
     // price
 
-    config_data.price = Input::with_theme(&theme)
-        .with_prompt("What is the price of each NFT?")
-        .validate_with(float_validator)
-        .interact()
-        .unwrap()
-        .parse::<f64>()
-        .expect("Failed to parse string into u64 that should have already been validated.");
+    config_data.price = match args.price.clone() {
+        Some(price) => price,
+        None => Input::with_theme(&theme)
+            .with_prompt("What is the price of each NFT?")
+            .validate_with(price_validator)
+            .interact()
+            .unwrap()
+            .parse::<Price>()
+            .expect("Failed to parse string into a price that should have already been validated."),
+    };
 
     // number
 
-    config_data.number = if num_files > 0 && (num_files % 2) == 0 && Confirm::with_theme(&theme)
-        .with_prompt(
-            format!(
-                "Found {} file pairs in \"{}\". Is this how many NFTs you will have in your Magic Hat?", num_files / 2, args.assets_dir,
-            )
-        )
-        .interact()? {
-        (num_files / 2) as u64
-    } else {
-        Input::with_theme(&theme)
-            .with_prompt("How many NFTs will you have in your Magic Hat?")
-            .validate_with(number_validator)
+    // the prompt is prefilled with the image/metadata pairs of the assets directory
+    let mut number_input = Input::<String>::with_theme(&theme);
+    number_input
+        .with_prompt("How many NFTs will you have in your Magic Hat?")
+        .validate_with(number_validator);
+    if num_pairs > 0 {
+        println!(
+            "Found {} image/metadata pair(s) in \"{}\".",
+            num_pairs, args.assets_dir
+        );
+        number_input.default(num_pairs.to_string());
+    }
+    let num_items = match args.number {
+        Some(number) => number,
+        None if !interactive => num_pairs as u64,
+        None => number_input
             .interact()
-            .unwrap().parse::<u64>().expect("Failed to parse number into u64 that should have already been validated.")
+            .unwrap()
+            .parse::<u64>()
+            .expect("Failed to parse number into u64 that should have already been validated."),
     };
+    config_data.number = Some(num_items);
 
     // symbol
 
-    config_data.symbol = if num_files > 0
+    config_data.symbol = if let Some(symbol) = args.symbol.clone() {
+        check_flag("symbol", &symbol, symbol_validator)?;
+        symbol
+    } else if !interactive {
+        symbol
+    } else if num_files > 0
         && Confirm::with_theme(&theme)
             .with_prompt(format!(
                 "Found {} in your metadata file. Is this value correct?",
@@ -211,7 +316,16 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
 
     // seller_fee_basis_points
 
-    config_data.seller_fee_basis_points = if num_files > 0 && Confirm::with_theme(&theme)
+    config_data.seller_fee_basis_points = if let Some(fee) = args.seller_fee_basis_points {
+        check_flag(
+            "seller-fee-basis-points",
+            &fee.to_string(),
+            seller_fee_basis_points_validator,
+        )?;
+        fee
+    } else if !interactive {
+        seller_fee
+    } else if num_files > 0 && Confirm::with_theme(&theme)
         .with_prompt(
             format!(
                 "Found value {} for seller fee basis points in your metadata file. Is this value correct?", seller_fee,
@@ -231,7 +345,9 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             .expect("Failed to parse number into u16 that should have already been validated.")
     };
 
-    let date= Input::with_theme(&theme)
+    let date = match args.go_live_date.clone() {
+        Some(date) => date,
+        None => Input::with_theme(&theme)
     .with_prompt("What is your go live date? Enter it this format, YYYY-MM-DD HH:MM:SS [+/-]UTC-OFFSET or type 'now' for \
      current time. For example 2022-05-02 18:00:00 +0000 for May 2, 2022 18:00:00 UTC.")
      .validate_with(|input: &String| {
@@ -242,38 +358,50 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         }
     })
     .interact()
-    .unwrap();
+    .unwrap(),
+    };
 
-    config_data.go_live_date = if date.contains("now") {
+    config_data.go_live_date = Some(if date.contains("now") {
         let current_time = chrono::Utc::now();
         current_time.format("%d %b %Y %H:%M:%S %z").to_string()
-    } else {
-        let date = DateTime::parse_from_str(&date, DATE_MASK)?;
+    } else if let Ok(date) = DateTime::parse_from_str(&date, DATE_MASK) {
         date.format("%d %b %Y %H:%M:%S %z").to_string()
-    };
+    } else {
+        // the flag also accepts the other go live date formats, kept as given
+        go_live_date_as_timestamp(&date, None)?;
+        date
+    });
     // creators
 
-    let num_creators = Input::with_theme(&theme)
-        .with_prompt("How many creator wallets do you have? (max limit of 4)")
-        .validate_with(number_validator)
-        .validate_with({
-            |input: &String| match input.parse::<u8>().unwrap() {
-                1 | 2 | 3 | 4 => Ok(()),
-                _ => Err("Number of creator wallets must be between 1 and 4, inclusive."),
-            }
-        })
-        .interact()
-        .unwrap()
-        .parse::<u8>()
-        .expect("Failed to parse number into u8 that should have already been validated.");
+    if !args.creators.is_empty() {
+        config_data.creators = parse_creators(&args.creators)?;
+    }
+
+    let num_creators = if !args.creators.is_empty() {
+        0
+    } else {
+        Input::with_theme(&theme)
+            .with_prompt("How many creator wallets do you have? (max limit of 4)")
+            .validate_with(number_validator)
+            .validate_with({
+                |input: &String| match input.parse::<u8>().unwrap() {
+                    1 | 2 | 3 | 4 => Ok(()),
+                    _ => Err("Number of creator wallets must be between 1 and 4, inclusive."),
+                }
+            })
+            .interact()
+            .unwrap()
+            .parse::<u8>()
+            .expect("Failed to parse number into u8 that should have already been validated.")
+    };
 
     let mut total_share = 0;
 
     (0..num_creators).into_iter().for_each(|i| {
-        let address = Pubkey::from_str(
+        let address = parse_address(
             &Input::with_theme(&theme)
-                .with_prompt(format!("Enter creator wallet address #{}", i + 1))
-                .validate_with(pubkey_validator)
+                .with_prompt(format!("Enter creator wallet address #{} (or .sol domain)", i + 1))
+                .validate_with(address_validator)
                 .interact()
                 .unwrap(),
         )
@@ -320,10 +448,32 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
         "Hidden Settings",
     ];
 
-    let choices = MultiSelect::with_theme(&theme)
-        .with_prompt("Which extra features do you want to use? (use [SPACEBAR] to select options you want and hit [ENTER] when done)")
-        .items(&extra_functions_options)
-        .interact()?;
+    // the features of the flags are selected
+    let selected = [
+        args.spl_token.is_some(),
+        args.gatekeeper_network.is_some(),
+        args.whitelist_mint.is_some(),
+        args.end_settings_date.is_some() || args.end_settings_amount.is_some(),
+        args.hidden_name.is_some() || args.hidden_uri.is_some() || args.hidden_hash.is_some(),
+    ];
+
+    let choices = if interactive {
+        MultiSelect::with_theme(&theme)
+            .with_prompt("Which extra features do you want to use? (use [SPACEBAR] to select options you want and hit [ENTER] when done)")
+            .items(&extra_functions_options)
+            .defaults(&[
+                selected[SPL_INDEX],
+                selected[GATEKEEPER_INDEX],
+                selected[WL_INDEX],
+                selected[END_SETTINGS_INDEX] || existing_end_settings.is_some(),
+                selected[HIDDEN_SETTINGS_INDEX],
+            ])
+            .interact()?
+    } else {
+        (0..selected.len())
+            .filter(|index| selected[*index])
+            .collect()
+    };
 
     // SPL token mint
 
@@ -333,67 +483,102 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
 
     if choices.contains(&SPL_INDEX) {
         config_data.sol_treasury_account = None;
-        config_data.spl_token = Some(
-            Pubkey::from_str(
-                &Input::with_theme(&theme)
-                    .with_prompt("What is your SPL token mint address?")
-                    .validate_with(pubkey_validator)
-                    .validate_with(|input: &String| -> Result<()> {
-                        check_spl_token(&program, input)?;
-                        Ok(())
-                    })
-                    .interact()
-                    .unwrap(),
-            )
-            .expect("Failed to parse string into pubkey that should have already been validated."),
-        );
-        config_data.spl_token_account = Some(
-                Pubkey::from_str(
-                    &Input::with_theme(&theme)
-                        .with_prompt("What is your SPL token account address (the account that will hold the SPL token mints)?")
-                        .validate_with(pubkey_validator)
-                        .validate_with(|input: &String| -> Result<()> {
-                            check_spl_token_account(&program, input)
-                        })
-                        .interact()
-                        .unwrap(),
-                )
-                    .expect("Failed to parse string into pubkey that should have already been validated."),
-            )
+        let spl_token = match &args.spl_token {
+            Some(spl_token) => {
+                check_flag("spl-token", spl_token, pubkey_validator)?;
+                check_spl_token(&program, spl_token)?;
+                spl_token.clone()
+            }
+            None => Input::with_theme(&theme)
+                .with_prompt("What is your SPL token mint address?")
+                .validate_with(pubkey_validator)
+                .validate_with(|input: &String| -> Result<()> {
+                    check_spl_token(&program, input)?;
+                    Ok(())
+                })
+                .interact()
+                .unwrap(),
+        };
+        config_data.spl_token =
+            Some(Pubkey::from_str(&spl_token).expect(
+                "Failed to parse string into pubkey that should have already been validated.",
+            ));
+        let spl_token_account = match &args.spl_token_account {
+            Some(spl_token_account) => {
+                check_flag("spl-token-account", spl_token_account, pubkey_validator)?;
+                check_spl_token_account(&program, spl_token_account)?;
+                spl_token_account.clone()
+            }
+            None => Input::with_theme(&theme)
+                .with_prompt("What is your SPL token account address (the account that will hold the SPL token mints)?")
+                .validate_with(pubkey_validator)
+                .validate_with(|input: &String| -> Result<()> {
+                    check_spl_token_account(&program, input)
+                })
+                .interact()
+                .unwrap(),
+        };
+        config_data.spl_token_account =
+            Some(Pubkey::from_str(&spl_token_account).expect(
+                "Failed to parse string into pubkey that should have already been validated.",
+            ));
     } else {
         config_data.spl_token = None;
         config_data.spl_token_account = None;
-        config_data.sol_treasury_account = Some(
-            Pubkey::from_str(
+        config_data.sol_treasury_account = Some(match &args.sol_treasury_account {
+            Some(treasury) => parse_address(treasury)?,
+            None => parse_address(
                 &Input::with_theme(&theme)
-                    .with_prompt("What is your SOL treasury address?")
-                    .validate_with(pubkey_validator)
+                    .with_prompt("What is your SOL treasury address (or .sol domain)?")
+                    .validate_with(address_validator)
                     .interact()
                     .unwrap(),
             )
             .expect("Failed to parse string into pubkey that should have already been validated."),
-        );
+        });
     };
 
     // gatekeeper
 
     config_data.gatekeeper = if choices.contains(&GATEKEEPER_INDEX) {
-        let gatekeeper_options = vec!["Civic Pass", "Verify by Encore"];
-        let civic_network = Pubkey::from_str(CIVIC_NETWORK).unwrap();
-        let encore_network = Pubkey::from_str(ENCORE_NETWORK).unwrap();
-        let selection = Select::with_theme(&theme)
-            .with_prompt("Which gatekeeper network do you want to use? ")
-            .items(&gatekeeper_options)
-            .default(0)
-            .interact()?;
-        let gatekeeper_network = match selection {
-            0 => civic_network,
-            1 => encore_network,
-            _ => civic_network,
+        let mut gatekeeper_options: Vec<String> = GATEKEEPER_NETWORKS
+            .iter()
+            .map(|(name, network)| format!("{} ({})", name, network))
+            .collect();
+        gatekeeper_options.push("Custom network address".to_string());
+
+        let gatekeeper_network = if let Some(network) = &args.gatekeeper_network {
+            check_flag("gatekeeper-network", network, pubkey_validator)?;
+            Pubkey::from_str(network).expect(
+                "Failed to parse string into pubkey that should have already been validated.",
+            )
+        } else {
+            let selection = Select::with_theme(&theme)
+                .with_prompt("Which gatekeeper network do you want to use? ")
+                .items(&gatekeeper_options)
+                .default(0)
+                .interact()?;
+            match GATEKEEPER_NETWORKS.get(selection) {
+                Some((_, network)) => Pubkey::from_str(network).unwrap(),
+                None => Pubkey::from_str(
+                    &Input::with_theme(&theme)
+                        .with_prompt("What is the address of the gatekeeper network?")
+                        .validate_with(pubkey_validator)
+                        .interact()
+                        .unwrap(),
+                )
+                .expect(
+                    "Failed to parse string into pubkey that should have already been validated.",
+                ),
+            }
         };
 
-        let expire_on_use = Confirm::with_theme(&theme)
-            .with_prompt("To help prevent bots even more, do you want to expire the gatekeeper token on each mint?").interact()?;
+        let expire_on_use = match args.gatekeeper_expire_on_use {
+            Some(expire_on_use) => expire_on_use,
+            None if !interactive => false,
+            None => Confirm::with_theme(&theme)
+                .with_prompt("To help prevent bots even more, do you want to expire the gatekeeper token on each mint?").interact()?,
+        };
         Some(GatekeeperConfig::new(gatekeeper_network, expire_on_use))
     } else {
         None
@@ -402,46 +587,74 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // whitelist mint settings
 
     config_data.whitelist_mint_settings = if choices.contains(&WL_INDEX) {
-        let mint = Pubkey::from_str(
-            &Input::with_theme(&theme)
-                .with_prompt("What is your WL token mint address?")
-                .validate_with(pubkey_validator)
-                .interact()
-                .unwrap(),
-        )
-        .expect("Failed to parse string into pubkey that should have already been validated.");
+        // the WL token might not be created yet, so the on-chain check is optional (and
+        // skipped for a mint given by flag)
+        let mint = if let Some(mint) = &args.whitelist_mint {
+            check_flag("whitelist-mint", mint, pubkey_validator)?;
+            Pubkey::from_str(mint).expect(
+                "Failed to parse string into pubkey that should have already been validated.",
+            )
+        } else {
+            let check_mint = Confirm::with_theme(&theme)
+                .with_prompt("Do you want to check that the WL token mint exists on-chain?")
+                .default(true)
+                .interact()?;
+            Pubkey::from_str(
+                &Input::with_theme(&theme)
+                    .with_prompt("What is your WL token mint address?")
+                    .validate_with(pubkey_validator)
+                    .validate_with(|input: &String| -> Result<()> {
+                        if check_mint {
+                            check_spl_token(&program, input)?;
+                        }
+                        Ok(())
+                    })
+                    .interact()
+                    .unwrap(),
+            )
+            .expect("Failed to parse string into pubkey that should have already been validated.")
+        };
 
-        let whitelist_mint_mode: WhitelistMintMode = if Confirm::with_theme(&theme)
-            .with_prompt("Do you want the whitelist token to be burned on each mint?")
-            .interact()?
-        {
+        let burn = match args.whitelist_burn {
+            Some(burn) => burn,
+            None if !interactive => false,
+            None => Confirm::with_theme(&theme)
+                .with_prompt("Do you want the whitelist token to be burned on each mint?")
+                .interact()?,
+        };
+        let whitelist_mint_mode: WhitelistMintMode = if burn {
             WhitelistMintMode::BurnEveryTime
         } else {
             WhitelistMintMode::NeverBurn
         };
 
-        let presale = Confirm::with_theme(&theme)
-            .with_prompt("Do you want to enable presale mint with your whitelist token?")
-            .interact()?;
-        let discount_price: Option<f64> = if presale {
-            let price = Input::with_theme(&theme)
-                    .with_prompt(
-                        "What is the discount price for the presale? Hit [ENTER] to not set a discount price.",
-                    )
-                    .allow_empty(true)
-                    .validate_with(float_validator)
-                    .interact()
-                    .unwrap();
-            if price.is_empty() {
-                // the discount price can be set to null
-                None
-            } else {
-                Some(price.parse::<f64>().expect(
-                    "Failed to parse string into f64 that should have already been validated.",
-                ))
-            }
-        } else {
+        let presale = match args.whitelist_presale {
+            Some(presale) => presale,
+            None if !interactive => false,
+            None => Confirm::with_theme(&theme)
+                .with_prompt("Do you want to enable presale mint with your whitelist token?")
+                .interact()?,
+        };
+        // the discount price applies to every WL token holder, with or without presale
+        let price = match &args.whitelist_discount_price {
+            Some(price) => price.to_string(),
+            None if !interactive => String::new(),
+            None => Input::with_theme(&theme)
+                .with_prompt(
+                    "What is the discount price for WL token holders? Hit [ENTER] to not set a discount price.",
+                )
+                .allow_empty(true)
+                .validate_with(price_validator)
+                .interact()
+                .unwrap(),
+        };
+        let discount_price: Option<Price> = if price.is_empty() {
+            // the discount price is left out of the config
             None
+        } else {
+            Some(price.parse::<Price>().expect(
+                "Failed to parse string into a price that should have already been validated.",
+            ))
         };
         Some(WhitelistMintSettings::new(
             whitelist_mint_mode,
@@ -456,47 +669,100 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // end settings
 
     config_data.end_settings = if choices.contains(&END_SETTINGS_INDEX) {
-        let end_settings_options = vec!["Date", "Amount"];
-        let end_setting_type = match Select::with_theme(&theme)
-            .with_prompt("What end settings type do you want to use?")
-            .items(&end_settings_options)
-            .default(0)
-            .interact()
-            .unwrap()
-        {
-            0 => EndSettingType::Date,
-            1 => EndSettingType::Amount,
-            _ => EndSettingType::Date,
-        };
-
-        let number = match end_setting_type {
-            EndSettingType::Amount => Input::with_theme(&theme)
-                .with_prompt("What is the amount to stop the mint?")
-                .validate_with(number_validator)
-                .validate_with(|num: &String| {
-                    if num.parse::<u64>().unwrap() < config_data.number {
-                        Ok(())
-                    } else {
-                        Err("Your end settings amount cannot be more than the number of items in your Magic Hat.")
-                    }
-                })
-                .interact()
-                .unwrap()
-                .parse::<u64>()
-                .expect("Failed to parse number into u64 that should have already been validated."),
-            EndSettingType::Date => {
-                let date = Input::with_theme(&theme)
-                    .with_prompt("What is the date to stop the mint? Enter it this format, YYYY-MM-DD HH:MM:SS [+/-]UTC-OFFSET. \
-                    For example 2022-05-02 18:00:00 +0000 for May 2, 2022 18:00:00 UTC.")
-                    .validate_with(date_validator)
-                    .interact()
-                    .unwrap();
-                    let date_time = DateTime::parse_from_str(&date, DATE_MASK)?;
-                    date_time.timestamp() as u64
+        if let Some(amount) = args.end_settings_amount {
+            if amount == 0 || amount > num_items {
+                let error = anyhow!(
+                    "Invalid value '{}' for --end-settings-amount: must be between 1 and the number of items ({})",
+                    amount,
+                    num_items
+                );
+                error!("{:?}", error);
+                return Err(error);
             }
-        };
+            Some(EndSettings::new(EndSettingType::Amount, amount))
+        } else if let Some(date) = &args.end_settings_date {
+            let go_live = config_data.go_live_timestamp()?.unwrap_or_default();
+            let end_date = parse_end_date(date)?;
+            if end_date <= go_live {
+                let error = anyhow!(
+                    "Invalid value '{}' for --end-settings-date: must be after the go live date",
+                    date
+                );
+                error!("{:?}", error);
+                return Err(error);
+            }
+            Some(EndSettings::new(EndSettingType::Date, end_date as u64))
+        } else {
+            // the end settings of the existing config are kept unless the user changes them
+            let keep = match &existing_end_settings {
+                Some(end_settings) => Confirm::with_theme(&theme)
+                    .with_prompt(format!(
+                        "Do you want to keep the end settings of \"{}\" ({})?",
+                        file_path,
+                        describe_end_settings(end_settings)
+                    ))
+                    .default(true)
+                    .interact()?,
+                None => false,
+            };
+
+            if keep {
+                existing_end_settings.clone()
+            } else {
+                let end_settings_options = vec!["Date", "Amount"];
+                let end_setting_type = match Select::with_theme(&theme)
+                    .with_prompt("What end settings type do you want to use?")
+                    .items(&end_settings_options)
+                    .default(0)
+                    .interact()
+                    .unwrap()
+                {
+                    0 => EndSettingType::Date,
+                    1 => EndSettingType::Amount,
+                    _ => EndSettingType::Date,
+                };
+
+                let number = match end_setting_type {
+                EndSettingType::Amount => Input::with_theme(&theme)
+                    .with_prompt("What is the amount to stop the mint?")
+                    .validate_with(number_validator)
+                    .validate_with(|num: &String| {
+                        let amount = num.parse::<u64>().unwrap();
+                        if amount > 0 && amount <= num_items {
+                            Ok(())
+                        } else {
+                            Err(format!(
+                                "Your end settings amount must be between 1 and the number of items in your Magic Hat ({}).",
+                                num_items
+                            ))
+                        }
+                    })
+                    .interact()
+                    .unwrap()
+                    .parse::<u64>()
+                    .expect("Failed to parse number into u64 that should have already been validated."),
+                EndSettingType::Date => {
+                    // the end date is parsed as the go live date, and must come after it
+                    let go_live = config_data.go_live_timestamp()?.unwrap_or_default();
+                    let date = Input::with_theme(&theme)
+                        .with_prompt("What is the date to stop the mint? Enter it this format, YYYY-MM-DD HH:MM:SS [+/-]UTC-OFFSET, \
+                        as RFC3339 or relative to now (e.g. +7d). For example 2022-05-02 18:00:00 +0000 for May 2, 2022 18:00:00 UTC.")
+                        .validate_with(|input: &String| -> Result<(), String> {
+                            match parse_end_date(input) {
+                                Ok(timestamp) if timestamp > go_live => Ok(()),
+                                Ok(_) => Err("The end date must be after the go live date.".to_string()),
+                                Err(_) => Err(format!("Couldn't parse input of '{}' to a valid date.", input)),
+                            }
+                        })
+                        .interact()
+                        .unwrap();
+                    parse_end_date(&date)? as u64
+                }
+            };
 
-        Some(EndSettings::new(end_setting_type, number))
+                Some(EndSettings::new(end_setting_type, number))
+            }
+        }
     } else {
         None
     };
@@ -504,41 +770,100 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // hidden settings
 
     config_data.hidden_settings = if choices.contains(&HIDDEN_SETTINGS_INDEX) {
-        let name = Input::with_theme(&theme)
+        // the mint number is appended to the name, which must still fit on-chain
+        let max_name_length =
+            MAX_NAME_LENGTH - num_items.max(1).to_string().len().min(MAX_NAME_LENGTH);
+        let name = match args.hidden_name.clone() {
+            Some(name) => name,
+            None => Input::with_theme(&theme)
             .with_prompt("What is the prefix name for your hidden settings mints? The mint index will be appended at the end of the name.")
             .validate_with(|name: &String| {
-                if name.len() > (MAX_NAME_LENGTH - 7) {
-                    Err("Your hidden settings name probably cannot be longer than 25 characters.")
-                } else {
-                    Ok(())
-                }
-            })
-            .interact()
-            .unwrap();
-        let uri = Input::with_theme(&theme)
-            .with_prompt("What is URI to be used for each mint?")
-            .validate_with(|uri: &String| {
-                if uri.len() > MAX_URI_LENGTH {
-                    Err("The URI cannot be longer than 200 characters.")
-                } else {
-                    Ok(())
-                }
-            })
-            .validate_with(url_validator)
-            .interact()
-            .unwrap();
-        let hash = Input::with_theme(&theme)
-            .with_prompt("What is the hash value for your hidden settings?")
-            .validate_with(|hash: &String| {
-                if hash.len() != 32 {
-                    Err("Your hidden settings hash has to be 32 characters long.")
+                if name.len() > max_name_length {
+                    Err(format!(
+                        "Your hidden settings name cannot be longer than {} characters.",
+                        max_name_length
+                    ))
                 } else {
                     Ok(())
                 }
             })
             .interact()
-            .unwrap();
-        Some(HiddenSettings::new(name, uri, hash))
+            .unwrap(),
+        };
+        let uri = match args.hidden_uri.clone() {
+            Some(uri) => uri,
+            None => Input::with_theme(&theme)
+                .with_prompt("What is URI to be used for each mint?")
+                .validate_with(|uri: &String| {
+                    if uri.len() > MAX_URI_LENGTH {
+                        Err("The URI cannot be longer than 200 characters.")
+                    } else {
+                        Ok(())
+                    }
+                })
+                .validate_with(url_validator)
+                .interact()
+                .unwrap(),
+        };
+
+        let assets_dir = Path::new(&args.assets_dir);
+        let hash_options = vec![
+            format!(
+                "Compute it from the metadata files in '{}'",
+                args.assets_dir
+            ),
+            "Enter the hash".to_string(),
+        ];
+        let compute_hash = args.hidden_hash.is_none()
+            && num_files > 0
+            && (!interactive
+                || Select::with_theme(&theme)
+                    .with_prompt("How do you want to set the hash of your hidden settings?")
+                    .items(&hash_options)
+                    .default(0)
+                    .interact()
+                    .unwrap()
+                    == 0);
+
+        let hash = if let Some(hash) = args.hidden_hash.clone() {
+            hash
+        } else if compute_hash {
+            let hash = reveal_hash_from_dir(assets_dir)?;
+            println!("Reveal set hash: {}", style(&hash).bold());
+            hash
+        } else {
+            Input::with_theme(&theme)
+                .with_prompt("What is the hash value for your hidden settings?")
+                .validate_with(|hash: &String| {
+                    if hash.len() != HIDDEN_HASH_LENGTH
+                        || !hash.chars().all(|c| c.is_ascii_hexdigit())
+                    {
+                        Err("Your hidden settings hash has to be 32 hex characters long.")
+                    } else {
+                        Ok(())
+                    }
+                })
+                .interact()
+                .unwrap()
+        };
+
+        let hidden_settings = HiddenSettings::new(name, uri, hash);
+
+        let errors = check_hidden_settings(&hidden_settings, num_items);
+        if !errors.is_empty() {
+            let error = anyhow!(
+                "Invalid hidden settings: {}",
+                errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+
+        Some(hidden_settings)
     } else {
         None
     };
@@ -546,48 +871,103 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     // upload method
 
     let upload_options = vec!["Bundlr", "AWS", "NFT Storage"];
-    config_data.upload_method = match Select::with_theme(&theme)
-        .with_prompt("What upload method do you want to use?")
-        .items(&upload_options)
-        .default(0)
-        .interact()
-        .unwrap()
-    {
-        0 => UploadMethod::Bundlr,
-        1 => UploadMethod::AWS,
-        2 => UploadMethod::NftStorage,
-        _ => UploadMethod::Bundlr,
+    let upload_method = match args.upload_method.clone() {
+        Some(upload_method) => upload_method,
+        None if !interactive => UploadMethod::Bundlr,
+        None => match Select::with_theme(&theme)
+            .with_prompt("What upload method do you want to use?")
+            .items(&upload_options)
+            .default(0)
+            .interact()
+            .unwrap()
+        {
+            1 => UploadMethod::AWS,
+            2 => UploadMethod::NftStorage,
+            _ => UploadMethod::Bundlr,
+        },
     };
 
-    if config_data.upload_method == UploadMethod::AWS {
-        config_data.aws_s3_bucket = Some(
-            Input::with_theme(&theme)
-                .with_prompt("What is the AWS S3 bucket name?")
-                .interact()
-                .unwrap(),
-        );
-    }
+    config_data.upload = match upload_method {
+        UploadMethod::AWS => {
+            let bucket = match args.aws_s3_bucket.clone() {
+                Some(bucket) => bucket,
+                None => Input::with_theme(&theme)
+                    .with_prompt("What is the AWS S3 bucket name?")
+                    .interact()
+                    .unwrap(),
+            };
+            let region: String = match args.aws_region.clone() {
+                Some(region) => region,
+                None if !interactive => String::new(),
+                None => Input::with_theme(&theme)
+                    .with_prompt("What is the AWS region of the bucket? Hit [ENTER] to use the region of the AWS environment.")
+                    .allow_empty(true)
+                    .interact()
+                    .unwrap(),
+            };
 
-    if config_data.upload_method == UploadMethod::NftStorage {
-        config_data.nft_storage_auth_token = Some(
-            Input::with_theme(&theme)
-                .with_prompt("What is the NFT Storage authentication token?")
-                .interact()
-                .unwrap(),
-        );
-    }
+            UploadConfig::Aws(AwsConfig {
+                bucket,
+                region: if region.is_empty() {
+                    None
+                } else {
+                    Some(region)
+                },
+            })
+        }
+        UploadMethod::NftStorage => UploadConfig::NftStorage(NftStorageConfig {
+            auth_token: match args.nft_storage_auth_token.clone() {
+                Some(auth_token) => auth_token,
+                None => Input::with_theme(&theme)
+                    .with_prompt("What is the NFT Storage authentication token?")
+                    .interact()
+                    .unwrap(),
+            },
+        }),
+        UploadMethod::Bundlr => UploadConfig::Bundlr(BundlrConfig::default()),
+    };
 
     // retain authority
 
-    config_data.retain_authority = Confirm::with_theme(&theme)
-        .with_prompt("Do you want to retain update authority on your NFTs? We HIGHLY recommend you choose yes.")
-        .interact()?;
+    config_data.retain_authority = match args.retain_authority {
+        Some(retain_authority) => retain_authority,
+        None if !interactive => true,
+        None => Confirm::with_theme(&theme)
+            .with_prompt("Do you want to retain update authority on your NFTs? We HIGHLY recommend you choose yes.")
+            .interact()?,
+    };
 
     // is mutable
 
-    config_data.is_mutable = Confirm::with_theme(&theme)
-        .with_prompt("Do you want your NFTs to remain mutable? We HIGHLY recommend you choose yes.")
-        .interact()?;
+    config_data.is_mutable = match args.is_mutable {
+        Some(is_mutable) => is_mutable,
+        None if !interactive => true,
+        None => Confirm::with_theme(&theme)
+            .with_prompt(
+                "Do you want your NFTs to remain mutable? We HIGHLY recommend you choose yes.",
+            )
+            .interact()?,
+    };
+
+    // risky settings are confirmed before they are written, without prompts they need --yes
+
+    let risky_settings = RiskySettings::from_config(&config_data)?;
+
+    if args.non_interactive && !args.yes {
+        let warnings = lint_risky_settings(&risky_settings, None);
+
+        if !warnings.is_empty() {
+            let error = anyhow!(
+                "Risky config settings require --yes with --non-interactive: {}",
+                warnings.join("; ")
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+    } else if !confirm_risky_settings(&risky_settings, None, args.yes)? {
+        println!("Config file not saved.");
+        return Ok(());
+    }
 
     // saving configuration file
 
@@ -598,12 +978,9 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
     );
 
     let mut save_file = true;
-    let file_path = match args.config {
-        Some(config) => config,
-        None => DEFAULT_CONFIG.to_string(),
-    };
 
-    if Path::new(&file_path).is_file() {
+    // without prompts, an existing file is overwritten
+    if interactive && Path::new(&file_path).is_file() {
         save_file = Select::with_theme(&theme)
             .with_prompt(format!("The file \"{}\" already exists. Do you want to overwrite it with the new config or log the new config to the console?", file_path))
             .items(&["Overwrite the file", "Log to console"])
@@ -621,13 +998,12 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
             .open(Path::new(&file_path));
 
         match file {
-            Ok(f) => {
+            Ok(mut f) => {
                 println!(
                     "{}",
                     style(format!("Saving config to file: \"{}\"\n", file_path))
                 );
-                serde_json::to_writer_pretty(f, &config_data)
-                    .expect("Unable to convert config to JSON!");
+                f.write_all(config_to_string(&config_data, format)?.as_bytes())?;
 
                 println!(
                     "{} {}",
@@ -645,23 +1021,174 @@ pub fn process_create_config(args: CreateConfigArgs) -> Result<()> {
                         .bold()
                         .red()
                 );
-                println!(
-                    "{}",
-                    style(
-                        serde_json::to_string_pretty(&config_data)
-                            .expect("Unable to convert config to JSON.")
-                    )
-                    .red()
-                );
+                println!("{}", style(config_to_string(&config_data, format)?).red());
             }
         }
     } else {
         println!("{}\n", style("Logging config to console:").dim());
+        println!("{}", config_to_string(&config_data, format)?);
+    }
+
+    print_gatekeeper_summary(&config_data);
+
+    Ok(())
+}
+
+/// Flags of the values needed to create the config without prompting. Values found
+/// in the assets directory are not missing.
+fn missing_values(args: &CreateConfigArgs, defaults: &AssetDefaults) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+
+    if args.price.is_none() {
+        missing.push("--price");
+    }
+    if args.number.or(defaults.number).is_none() {
+        missing.push("--number");
+    }
+    if args.symbol.is_none() && defaults.symbol.is_none() {
+        missing.push("--symbol");
+    }
+    if args
+        .seller_fee_basis_points
+        .or(defaults.seller_fee_basis_points)
+        .is_none()
+    {
+        missing.push("--seller-fee-basis-points");
+    }
+    if args.go_live_date.is_none() {
+        missing.push("--go-live-date");
+    }
+    if args.creators.is_empty() {
+        missing.push("--creator");
+    }
+
+    // payments go to the SPL token account, or to the SOL treasury
+    if args.spl_token.is_some() {
+        if args.spl_token_account.is_none() {
+            missing.push("--spl-token-account");
+        }
+    } else if args.sol_treasury_account.is_none() {
+        missing.push("--sol-treasury-account");
+    }
+
+    if args.hidden_name.is_some() || args.hidden_uri.is_some() || args.hidden_hash.is_some() {
+        if args.hidden_name.is_none() {
+            missing.push("--hidden-name");
+        }
+        if args.hidden_uri.is_none() {
+            missing.push("--hidden-uri");
+        }
+        if args.hidden_hash.is_none() && !defaults.reveal_hash {
+            missing.push("--hidden-hash");
+        }
+    }
+
+    match args.upload_method {
+        Some(UploadMethod::AWS) if args.aws_s3_bucket.is_none() => missing.push("--aws-s3-bucket"),
+        Some(UploadMethod::NftStorage) if args.nft_storage_auth_token.is_none() => {
+            missing.push("--nft-storage-auth-token")
+        }
+        _ => (),
+    }
+
+    missing
+}
+
+/// Checks a value given by flag with the validator of its prompt.
+fn check_flag<E: Display>(
+    flag: &str,
+    value: &str,
+    validator: impl Fn(&String) -> Result<(), E>,
+) -> Result<()> {
+    if let Err(err) = validator(&value.to_string()) {
+        let error = anyhow!("Invalid value '{}' for --{}: {}", value, flag, err);
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Parses the `--creator ADDRESS:SHARE` flags, following the rules of the prompts:
+/// 1 to 4 creators whose shares add up to 100.
+fn parse_creators(values: &[String]) -> Result<Vec<Creator>> {
+    if values.len() > 4 {
+        let error = anyhow!(
+            "Number of creator wallets must be between 1 and 4, found {}",
+            values.len()
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let creators = values
+        .iter()
+        .map(|value| {
+            let (address, share) = value
+                .rsplit_once(':')
+                .ok_or_else(|| anyhow!("Invalid creator '{}', expected ADDRESS:SHARE", value))?;
+            let share = share
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| anyhow!("Invalid royalty share of creator '{}'", value))?;
+
+            Ok(Creator {
+                address: parse_address(address)?,
+                share,
+            })
+        })
+        .collect::<Result<Vec<Creator>>>()?;
+
+    let total_share: u32 = creators.iter().map(|creator| creator.share as u32).sum();
+    if total_share != 100 {
+        let error = anyhow!(
+            "Royalty share for all creators must total 100 percent, found {}",
+            total_share
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    Ok(creators)
+}
+
+/// Prints the gatekeeper network of the config with its name, so that the address
+/// can be double checked.
+fn print_gatekeeper_summary(config_data: &ConfigData) {
+    if let Some(gatekeeper) = &config_data.gatekeeper {
+        let network = gatekeeper.gatekeeper_network().to_string();
+        let name = GATEKEEPER_NETWORKS
+            .iter()
+            .find(|(_, address)| *address == network)
+            .map(|(name, _)| *name)
+            .unwrap_or("custom network");
+
         println!(
-            "{}",
-            serde_json::to_string_pretty(&config_data).expect("Unable to convert config to JSON.")
+            "\n{} {} ({}), expire on use: {}",
+            style("Gatekeeper network:").bold(),
+            name,
+            network,
+            gatekeeper.expire_on_use()
         );
     }
+}
 
-    Ok(())
+/// Parses the end date of the mint: the date format of the prompts, or any format
+/// accepted for the go live date (RFC3339, RFC2822, UNIX timestamp or relative to
+/// now, e.g. `+7d`).
+fn parse_end_date(input: &str) -> Result<i64> {
+    match DateTime::parse_from_str(input.trim(), DATE_MASK) {
+        Ok(date) => Ok(date.timestamp()),
+        Err(_) => go_live_date_as_timestamp(input, None),
+    }
+}
+
+fn describe_end_settings(end_settings: &EndSettings) -> String {
+    match end_settings.end_setting_type() {
+        EndSettingType::Date => match Utc.timestamp_opt(end_settings.number() as i64, 0) {
+            LocalResult::Single(date) => format!("ends on {}", date.format("%d %b %Y %H:%M:%S %z")),
+            _ => format!("ends at timestamp {}", end_settings.number()),
+        },
+        EndSettingType::Amount => format!("ends after {} mint(s)", end_settings.number()),
+    }
 }
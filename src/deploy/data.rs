@@ -5,5 +5,6 @@ pub struct DeployArgs {
     pub cache: String,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
+    pub yes: bool,
     pub interrupted: Arc<AtomicBool>,
 }
@@ -29,10 +29,11 @@ pub use mpl_token_metadata::state::{
 
 use crate::cache::*;
 use crate::common::*;
-use crate::config::{data::*, parser::get_config_data};
+use crate::config::{data::*, lint::*, parser::get_config_data};
 use crate::deploy::data::*;
 use crate::deploy::errors::*;
-use crate::magic_hat::{parse_config_price, MAGIC_HAT_ID};
+use crate::magic_hat::{magic_hat_account_size, parse_config_price, token_decimals, MAGIC_HAT_ID};
+use crate::program_errors::decode_program_error;
 use crate::setup::{laddu_setup, setup_client};
 use crate::utils::*;
 use crate::validate::parser::{check_name, check_seller_fee_basis_points, check_symbol, check_url};
@@ -85,13 +86,33 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
 
     let laddu_config = Arc::new(laddu_setup(args.keypair, args.rpc_url)?);
     let client = setup_client(&laddu_config)?;
-    let config_data = get_config_data(&args.config)?;
+
+    // deploying on mainnet spends real SOL, the cluster is shown before anything is sent
+    println!(
+        "{} {} {}",
+        style("Deploying to").bold(),
+        cluster_name(&laddu_config.rpc_url),
+        style(format!("({})", laddu_config.rpc_url)).dim()
+    );
+    let mut config_data = get_config_data(&args.config)?;
 
     let magic_hat_address = &cache.program.magic_hat;
 
     // checks the magic hat data
 
-    let num_items = config_data.number;
+    // an auto number is the one detected by upload from the assets directory
+    let num_items = match config_data.number.or(cache.program.number) {
+        Some(number) => number,
+        None => {
+            let error = anyhow!(
+                "Number of items is \"{}\" but the cache has no detected number, run 'upload' first",
+                AUTO_NUMBER
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+    config_data.number = Some(num_items);
     let hidden = config_data.hidden_settings.is_some();
 
     if num_items != (cache.items.0.len() as u64) {
@@ -105,6 +126,14 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
         check_seller_fee_basis_points(config_data.seller_fee_basis_points)?;
     }
 
+    // the settings of an existing Magic Hat are changed with 'update', which has its own check
+    if magic_hat_address.is_empty()
+        && !confirm_risky_settings(&RiskySettings::from_config(&config_data)?, None, args.yes)?
+    {
+        println!("Deploy cancelled.");
+        return Ok(());
+    }
+
     let magichat_pubkey = if magic_hat_address.is_empty() {
         println!(
             "{} {}Creating Magic Hat",
@@ -169,7 +198,10 @@ pub async fn process_deploy(args: DeployArgs) -> Result<()> {
             &magichat_pubkey.to_string()
         );
 
-        cache.program = CacheProgram::new_from_cm(&magichat_pubkey);
+        cache.program = CacheProgram {
+            number: cache.program.number,
+            ..CacheProgram::new_from_cm(&magichat_pubkey)
+        };
         cache.sync_file()?;
 
         spinner.finish_and_clear();
@@ -256,17 +288,20 @@ fn create_magic_hat_data(
     config: &ConfigData,
     uuid: String,
 ) -> Result<MagicHatData> {
-    let go_live_date = Some(go_live_date_as_timestamp(&config.go_live_date)?);
+    let go_live_date = config.go_live_timestamp()?;
 
     let end_settings = config
         .end_settings
         .as_ref()
         .map(|s| s.into_magichat_format());
 
-    let whitelist_mint_settings = config
-        .whitelist_mint_settings
-        .as_ref()
-        .map(|s| s.into_magichat_format());
+    // the discount price is charged in the payment token
+    let whitelist_mint_settings = match config.whitelist_mint_settings {
+        Some(ref settings) => {
+            Some(settings.into_magichat_format(token_decimals(client, config.spl_token)?)?)
+        }
+        None => None,
+    };
 
     let hidden_settings = config
         .hidden_settings
@@ -317,7 +352,9 @@ fn create_magic_hat_data(
         creators,
         whitelist_mint_settings,
         hidden_settings,
-        items_available: config.number,
+        items_available: config
+            .number
+            .ok_or_else(|| anyhow!("Number of items not resolved"))?,
         gatekeeper,
     };
 
@@ -387,17 +424,7 @@ fn initialize_magic_hat(
     program: Program,
 ) -> Result<Signature> {
     let payer = program.payer();
-    let items_available = magic_hat_data.items_available;
-
-    let magichat_account_size = if magic_hat_data.hidden_settings.is_some() {
-        CONFIG_ARRAY_START
-    } else {
-        CONFIG_ARRAY_START
-            + 4
-            + items_available as usize * CONFIG_LINE_SIZE
-            + 8
-            + 2 * (items_available as usize / 8 + 1)
-    };
+    let magichat_account_size = magic_hat_account_size(&magic_hat_data);
 
     info!(
         "Initializing Magic Hat with account size of: {} and address of: {}",
@@ -449,7 +476,7 @@ fn initialize_magic_hat(
         });
     }
 
-    let sig = tx.send()?;
+    let sig = tx.send().map_err(decode_program_error)?;
 
     Ok(sig)
 }
@@ -593,7 +620,8 @@ async fn add_config_lines(config: Arc<LadduConfig>, tx_info: TxInfo) -> Result<V
             config_lines,
         })
         .signer(&tx_info.payer)
-        .send()?;
+        .send()
+        .map_err(decode_program_error)?;
 
     Ok(indices)
 }
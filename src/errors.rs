@@ -20,6 +20,15 @@ pub enum CacheError {
 
     #[error("Failed to parse cache file with error: {0}")]
     CacheFileWrongFormat(String),
+
+    #[error("Cache file '{0}' is truncated, it was not completely written.")]
+    CacheFileTruncated(String),
+
+    #[error("Cache file version {0} was written by a newer version of laddu (this one supports up to version {1}), update laddu to use it.")]
+    CacheFileTooNew(u32, u32),
+
+    #[error("Cache file '{0}' is locked by {1}. Wait for it to finish, or use --force-unlock if that process is gone.")]
+    CacheFileLocked(String, String),
 }
 
 #[derive(Debug, Error)]
@@ -4,7 +4,9 @@ use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
 use std::sync::{atomic::AtomicBool, Arc};
 
-use crate::common::LAUNCH_EMOJI;
+use crate::common::{
+    DEFAULT_SELLER_FEE_THRESHOLD, DEFAULT_VALIDATION_REPORT, DEFAULT_VERIFY_REPORT, LAUNCH_EMOJI,
+};
 use crate::config::parser::get_config_data;
 use crate::create_config::{process_create_config, CreateConfigArgs};
 use crate::deploy::{process_deploy, DeployArgs};
@@ -41,6 +43,14 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
 
             let create_config_args = CreateConfigArgs {
                 config: Some(args.config.clone()),
+                skip_image_checks: false,
+                check_duplicates: false,
+                allow_duplicate_names: false,
+                seller_fee_threshold: DEFAULT_SELLER_FEE_THRESHOLD.parse()?,
+                hidden: false,
+                fix: false,
+                dry_run: false,
+                no_backup: false,
                 keypair: args.keypair.clone(),
                 rpc_url: args.rpc_url.clone(),
                 assets_dir: args.assets_dir.clone(),
@@ -57,6 +67,8 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
     let validate_args = ValidateArgs {
         assets_dir: args.assets_dir.clone(),
         strict: args.strict,
+        report: DEFAULT_VALIDATION_REPORT.to_string(),
+        config: Some(args.config.clone()),
     };
 
     process_validate(validate_args)?;
@@ -81,6 +93,7 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
         keypair: args.keypair.clone(),
         rpc_url: args.rpc_url.clone(),
         cache: args.cache.clone(),
+        yes: false,
         interrupted: args.interrupted.clone(),
     };
 
@@ -92,9 +105,20 @@ pub async fn process_launch(args: LaunchArgs) -> Result<()> {
         keypair: args.keypair.clone(),
         rpc_url: args.rpc_url.clone(),
         cache: args.cache.clone(),
+        magic_hat: None,
+        config: Some(args.config.clone()),
+        repair: true,
+        check_links: false,
+        check_images: false,
+        report: DEFAULT_VERIFY_REPORT.to_string(),
+        from: None,
+        to: None,
+        index: None,
+        resume: false,
+        no_resume: true,
     };
 
-    process_verify(verify_args)?;
+    process_verify(verify_args).await?;
 
     Ok(())
 }
@@ -1,3 +1,4 @@
+pub mod airdrop;
 pub mod bundlr;
 pub mod cache;
 pub mod cli;
@@ -11,10 +12,14 @@ pub mod errors;
 pub mod launch;
 pub mod magic_hat;
 pub mod mint;
+pub mod mint_list;
 pub mod parse;
 pub mod pdas;
+pub mod program_errors;
+pub mod project;
 pub mod setup;
 pub mod show;
+pub mod sns;
 pub mod update;
 pub mod upload;
 pub mod utils;
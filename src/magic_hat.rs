@@ -6,7 +6,8 @@ use magic_hat::{MagicHat, MagicHatData, WhitelistMintMode, WhitelistMintSettings
 use spl_token::id as token_program_id;
 
 use crate::config::data::LadduConfig;
-use crate::config::{price_as_lamports, ConfigData};
+use crate::config::{ConfigData, Price, SOL_DECIMALS};
+use crate::constants::{CONFIG_ARRAY_START, CONFIG_LINE_SIZE};
 use crate::setup::setup_client;
 use crate::utils::check_spl_token;
 
@@ -22,19 +23,41 @@ pub struct ConfigStatus {
 }
 
 pub fn parse_config_price(client: &Client, config: &ConfigData) -> Result<u64> {
-    let parsed_price = if let Some(spl_token) = config.spl_token {
-        let token_program = client.program(token_program_id());
-        let token_mint = check_spl_token(&token_program, &spl_token.to_string())?;
+    parse_price(client, &config.price, config.spl_token)
+}
+
+/// Convert a price to lamports or, when an SPL token is used for payment, to
+/// the token base units. Prices with more decimals than the mint are rejected.
+pub fn parse_price(client: &Client, price: &Price, spl_token: Option<Pubkey>) -> Result<u64> {
+    let decimals = token_decimals(client, spl_token)?.unwrap_or(SOL_DECIMALS);
+    Ok(price.to_base_units(decimals)?)
+}
 
-        match (config.price as u64).checked_mul(10u64.pow(token_mint.decimals.into())) {
-            Some(price) => price,
-            None => return Err(anyhow!("Price math overflow")),
+/// Decimals of the SPL token used for payment, `None` when paying in SOL.
+pub fn token_decimals(client: &Client, spl_token: Option<Pubkey>) -> Result<Option<u8>> {
+    match spl_token {
+        Some(spl_token) => {
+            let token_program = client.program(token_program_id());
+            Ok(Some(
+                check_spl_token(&token_program, &spl_token.to_string())?.decimals,
+            ))
         }
-    } else {
-        price_as_lamports(config.price)
-    };
+        None => Ok(None),
+    }
+}
 
-    Ok(parsed_price)
+/// Size (in bytes) of the magic hat account for the given data.
+pub fn magic_hat_account_size(data: &MagicHatData) -> usize {
+    if data.hidden_settings.is_some() {
+        CONFIG_ARRAY_START
+    } else {
+        let items_available = data.items_available as usize;
+        CONFIG_ARRAY_START
+            + 4
+            + items_available * CONFIG_LINE_SIZE
+            + 8
+            + 2 * (items_available / 8 + 1)
+    }
 }
 
 pub fn get_magic_hat_state(laddu_config: &LadduConfig, magic_hat_id: &Pubkey) -> Result<MagicHat> {
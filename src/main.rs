@@ -9,24 +9,40 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{ArgMatches, Command, CommandFactory, FromArgMatches};
 use console::style;
 use tracing::subscriber::set_global_default;
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_subscriber::{self, filter::LevelFilter, prelude::*, EnvFilter};
 
+use laddu_cli::airdrop::{process_airdrop, AirdropArgs};
 use laddu_cli::bundlr::{process_bundlr, BundlrArgs};
-use laddu_cli::cli::{Cli, CollectionSubcommands, Commands};
+use laddu_cli::cache::{
+    process_cache_check, process_cache_export, process_cache_fmt, process_cache_import,
+    process_cache_merge, release_cache_locks, CacheCheckArgs, CacheExportArgs, CacheFmtArgs,
+    CacheImportArgs, CacheLock, CacheMergeArgs,
+};
+use laddu_cli::cli::{CacheSubcommands, Cli, CollectionSubcommands, Commands, ConfigSubcommands};
 use laddu_cli::collections::{
     process_remove_collection, process_set_collection, RemoveCollectionArgs, SetCollectionArgs,
 };
+use laddu_cli::config::{
+    config_env_overrides, process_config_check, process_config_dump, ConfigCheckArgs,
+    ConfigDumpArgs,
+};
 use laddu_cli::constants::{COMPLETE_EMOJI, ERROR_EMOJI};
 use laddu_cli::create_config::{process_create_config, CreateConfigArgs};
 use laddu_cli::deploy::{process_deploy, DeployArgs};
 use laddu_cli::launch::{process_launch, LaunchArgs};
 use laddu_cli::mint::{process_mint, MintArgs};
+use laddu_cli::mint_list::{process_mint_list, MintListArgs};
+use laddu_cli::project::{argument_value, env_arguments, ProjectPaths};
+use laddu_cli::setup::configure_cluster;
 use laddu_cli::show::{process_show, ShowArgs};
-use laddu_cli::update::{process_update, UpdateArgs};
+use laddu_cli::sns::configure_sns;
+use laddu_cli::update::{
+    process_update, process_update_authority, UpdateArgs, UpdateAuthorityArgs,
+};
 use laddu_cli::upload::{process_upload, UploadArgs};
 use laddu_cli::validate::{process_validate, ValidateArgs};
 use laddu_cli::verify::{process_verify, VerifyArgs};
@@ -86,7 +102,9 @@ async fn main() {
 }
 
 async fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let command = Cli::command();
+    let matches = command.clone().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     let log_level_error: Result<()> = Err(anyhow!(
         "Invalid log level: {:?}.\n Valid levels are: trace, debug, info, warn, error.",
@@ -116,6 +134,8 @@ async fn run() -> Result<()> {
                 ERROR_EMOJI,
                 style("Error running command (re-run needed):").red(),
             );
+            // the lock is not released by unwinding when exiting here
+            release_cache_locks();
             // finished the program with an error code to the OS
             std::process::exit(1);
         }
@@ -124,17 +144,98 @@ async fn run() -> Result<()> {
     })
     .expect("Error setting Ctrl-C handler");
 
+    let paths = ProjectPaths::discover(&command, &matches, cli.no_discovery);
+
+    // the RPC of the config cluster is used when --rpc-url is not specified
+    let config = argument_value(&command, &matches, "config").map(|c| paths.resolve("config", c));
+    configure_cluster(paths.resolve_config(config).as_deref())?;
+
+    // .sol domains are resolved through the RPC of the command
+    configure_sns(!cli.no_sns, argument_value(&command, &matches, "rpc_url"));
+
+    if matches!(cli.log_level.as_deref(), Some("debug") | Some("trace")) {
+        print_env_values(&command, &matches);
+    }
+
+    // commands writing the cache hold its lock until they finish
+    let _lock = match cache_to_lock(&cli.command) {
+        Some((arg, cache)) => Some(CacheLock::acquire(
+            &paths.resolve(arg, cache),
+            &format!("laddu {}", matches.subcommand_name().unwrap_or_default()),
+            cli.force_unlock,
+        )?),
+        None => None,
+    };
+
     match cli.command {
         Commands::CreateConfig {
             config,
+            format,
             keypair,
             rpc_url,
             assets_dir,
+            non_interactive,
+            yes,
+            price,
+            number,
+            symbol,
+            seller_fee_basis_points,
+            go_live_date,
+            creators,
+            sol_treasury_account,
+            spl_token,
+            spl_token_account,
+            gatekeeper_network,
+            gatekeeper_expire_on_use,
+            whitelist_mint,
+            whitelist_burn,
+            whitelist_presale,
+            whitelist_discount_price,
+            end_settings_date,
+            end_settings_amount,
+            hidden_name,
+            hidden_uri,
+            hidden_hash,
+            upload_method,
+            aws_s3_bucket,
+            aws_region,
+            nft_storage_auth_token,
+            retain_authority,
+            is_mutable,
         } => process_create_config(CreateConfigArgs {
             config,
+            format,
             keypair,
             rpc_url,
             assets_dir,
+            non_interactive,
+            yes,
+            price,
+            number,
+            symbol,
+            seller_fee_basis_points,
+            go_live_date,
+            creators,
+            sol_treasury_account,
+            spl_token,
+            spl_token_account,
+            gatekeeper_network,
+            gatekeeper_expire_on_use,
+            whitelist_mint,
+            whitelist_burn,
+            whitelist_presale,
+            whitelist_discount_price,
+            end_settings_date,
+            end_settings_amount,
+            hidden_name,
+            hidden_uri,
+            hidden_hash,
+            upload_method,
+            aws_s3_bucket,
+            aws_region,
+            nft_storage_auth_token,
+            retain_authority,
+            is_mutable,
         })?,
         Commands::Launch {
             assets_dir,
@@ -145,11 +246,11 @@ async fn run() -> Result<()> {
             strict,
         } => {
             process_launch(LaunchArgs {
-                assets_dir,
-                config,
+                assets_dir: paths.resolve("assets_dir", assets_dir),
+                config: paths.resolve("config", config),
                 keypair,
                 rpc_url,
-                cache,
+                cache: paths.resolve("cache", cache),
                 strict,
                 interrupted: interrupted.clone(),
             })
@@ -157,43 +258,171 @@ async fn run() -> Result<()> {
         }
         Commands::Mint {
             keypair,
+            keypairs,
             rpc_url,
             cache,
             number,
             magic_hat,
-        } => process_mint(MintArgs {
+            config,
+            priority_fee,
+            compute_units,
+            retry,
+            whitelist_token_account,
+            gateway_token,
+            simulate,
+            wait_for_live,
+            fetch_json,
+            delay_ms,
+            resume,
+            restart,
+            create_ata,
+            no_collection,
+            parallel,
+            out,
+            output,
+        } => {
+            process_mint(MintArgs {
+                keypair,
+                keypairs,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                number,
+                magic_hat,
+                config: paths.resolve("config", config),
+                priority_fee,
+                compute_units,
+                retry,
+                whitelist_token_account,
+                gateway_token,
+                simulate,
+                wait_for_live,
+                fetch_json,
+                delay_ms,
+                resume,
+                restart,
+                create_ata,
+                no_collection,
+                parallel,
+                out,
+                output,
+                interrupted: interrupted.clone(),
+            })
+            .await?
+        }
+        Commands::MintList {
             keypair,
             rpc_url,
             cache,
-            number,
             magic_hat,
+            metadata,
+            out,
+            output,
+        } => process_mint_list(MintListArgs {
+            keypair,
+            rpc_url,
+            cache: paths.resolve("cache", cache),
+            magic_hat,
+            metadata,
+            out,
+            output,
         })?,
+        Commands::Airdrop {
+            list,
+            keypair,
+            rpc_url,
+            cache,
+            magic_hat,
+            results,
+            parallel,
+        } => {
+            process_airdrop(AirdropArgs {
+                keypair,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                magic_hat,
+                list,
+                results,
+                parallel,
+                interrupted: interrupted.clone(),
+            })
+            .await?
+        }
         Commands::Update {
             config,
             keypair,
             rpc_url,
             cache,
-            new_authority,
             magic_hat,
+            from_config,
+            treasury,
+            update_treasury,
+            price,
+            go_live_date,
+            timezone,
+            seller_fee_basis_points,
+            is_mutable,
+            retain_authority,
+            symbol,
+            hidden_name,
+            hidden_uri,
+            hidden_hash,
+            force,
+            simulate,
+            yes,
         } => process_update(UpdateArgs {
-            config,
+            config: paths.resolve("config", config),
+            explicit_config: !paths.is_default("config"),
+            keypair,
+            rpc_url,
+            cache: paths.resolve("cache", cache),
+            magic_hat,
+            from_config,
+            treasury,
+            update_treasury,
+            price,
+            go_live_date,
+            timezone,
+            seller_fee_basis_points,
+            is_mutable,
+            retain_authority,
+            symbol,
+            hidden_name,
+            hidden_uri,
+            hidden_hash,
+            force,
+            simulate,
+            yes,
+        })?,
+        Commands::UpdateAuthority {
             keypair,
             rpc_url,
             cache,
+            magic_hat,
             new_authority,
+            allow_pda,
+            yes,
+        } => process_update_authority(UpdateAuthorityArgs {
+            keypair,
+            rpc_url,
+            cache: paths.resolve("cache", cache),
             magic_hat,
+            new_authority,
+            allow_pda,
+            yes,
         })?,
         Commands::Deploy {
             config,
             keypair,
             rpc_url,
             cache,
+            yes,
         } => {
             process_deploy(DeployArgs {
-                config,
+                config: paths.resolve("config", config),
                 keypair,
                 rpc_url,
-                cache,
+                cache: paths.resolve("cache", cache),
+                yes,
                 interrupted: interrupted.clone(),
             })
             .await?
@@ -206,49 +435,147 @@ async fn run() -> Result<()> {
             cache,
         } => {
             process_upload(UploadArgs {
-                assets_dir,
-                config,
+                assets_dir: paths.resolve("assets_dir", assets_dir),
+                config: paths.resolve("config", config),
                 keypair,
                 rpc_url,
-                cache,
+                cache: paths.resolve("cache", cache),
                 interrupted: interrupted.clone(),
             })
             .await?
         }
-        Commands::Validate { assets_dir, strict } => {
-            process_validate(ValidateArgs { assets_dir, strict })?
-        }
+        Commands::Validate {
+            assets_dir,
+            strict,
+            report,
+            config,
+            skip_image_checks,
+            check_duplicates,
+            allow_duplicate_names,
+            seller_fee_threshold,
+            hidden,
+            fix,
+            dry_run,
+            no_backup,
+        } => process_validate(ValidateArgs {
+            assets_dir: paths.resolve("assets_dir", assets_dir),
+            strict,
+            report,
+            config,
+            skip_image_checks,
+            check_duplicates,
+            allow_duplicate_names,
+            seller_fee_threshold,
+            hidden,
+            fix,
+            dry_run,
+            no_backup,
+        })?,
         Commands::Withdraw {
             magic_hat,
             keypair,
             rpc_url,
             list,
-        } => process_withdraw(WithdrawArgs {
-            magic_hat,
-            keypair,
-            rpc_url,
-            list,
-        })?,
+            dry_run,
+            yes,
+            machines_file,
+            parallel,
+            allow_unminted,
+            output,
+            out,
+            sort,
+            destination,
+        } => {
+            process_withdraw(WithdrawArgs {
+                magic_hat,
+                keypair,
+                rpc_url,
+                list,
+                dry_run,
+                yes,
+                machines_file,
+                parallel,
+                allow_unminted,
+                output,
+                out,
+                sort,
+                destination,
+            })
+            .await?
+        }
         Commands::Verify {
             keypair,
             rpc_url,
             cache,
-        } => process_verify(VerifyArgs {
-            keypair,
-            rpc_url,
-            cache,
-        })?,
-        Commands::Show {
-            keypair,
-            rpc_url,
-            cache,
             magic_hat,
-        } => process_show(ShowArgs {
+            config,
+            repair,
+            check_links,
+            check_images,
+            report,
+            from,
+            to,
+            index,
+            resume,
+            no_resume,
+        } => {
+            process_verify(VerifyArgs {
+                keypair,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                magic_hat,
+                config: paths.resolve_config(config),
+                repair,
+                check_links,
+                check_images,
+                report,
+                from,
+                to,
+                index,
+                resume,
+                no_resume,
+            })
+            .await?
+        }
+        Commands::Show {
             keypair,
             rpc_url,
             cache,
+            config,
+            cache_summary,
             magic_hat,
-        })?,
+            unminted,
+            config_lines,
+            balances,
+            from,
+            to,
+            watch,
+            stats,
+            raw,
+            raw_bytes,
+            output,
+        } => {
+            process_show(ShowArgs {
+                keypair,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                config: paths.resolve("config", config),
+                cache_summary,
+                magic_hat,
+                unminted,
+                config_lines,
+                balances,
+                from,
+                to,
+                watch,
+                stats,
+                raw,
+                raw_bytes,
+                output,
+                interrupted: interrupted.clone(),
+            })
+            .await?
+        }
         Commands::Collection { command } => match command {
             CollectionSubcommands::Set {
                 collection_mint,
@@ -260,7 +587,7 @@ async fn run() -> Result<()> {
                 collection_mint,
                 keypair,
                 rpc_url,
-                cache,
+                cache: paths.resolve("cache", cache),
                 magic_hat,
             })?,
             CollectionSubcommands::Remove {
@@ -269,12 +596,85 @@ async fn run() -> Result<()> {
                 cache,
                 magic_hat,
             } => process_remove_collection(RemoveCollectionArgs {
+                keypair,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                magic_hat,
+            })?,
+        },
+        Commands::Config { command } => match command {
+            ConfigSubcommands::Dump {
                 keypair,
                 rpc_url,
                 cache,
                 magic_hat,
+                out,
+                force,
+            } => process_config_dump(ConfigDumpArgs {
+                keypair,
+                rpc_url,
+                cache: paths.resolve("cache", cache),
+                magic_hat,
+                out: paths.resolve("out", out),
+                force,
+            })?,
+            ConfigSubcommands::Check {
+                config,
+                keypair,
+                rpc_url,
+            } => process_config_check(ConfigCheckArgs {
+                config: paths.resolve("config", config),
+                keypair,
+                rpc_url,
             })?,
         },
+        Commands::Cache { command } => match command {
+            CacheSubcommands::Import {
+                keypair,
+                rpc_url,
+                magic_hat,
+                from,
+                out,
+                force,
+                fetch_images,
+            } => {
+                process_cache_import(CacheImportArgs {
+                    keypair,
+                    rpc_url,
+                    magic_hat,
+                    from,
+                    out: paths.resolve("out", out),
+                    force,
+                    fetch_images,
+                })
+                .await?
+            }
+            CacheSubcommands::Export { cache, out, force } => {
+                process_cache_export(CacheExportArgs {
+                    cache: paths.resolve("cache", cache),
+                    out,
+                    force,
+                })?
+            }
+            CacheSubcommands::Merge { caches, out, force } => {
+                process_cache_merge(CacheMergeArgs {
+                    caches,
+                    out: paths.resolve("out", out),
+                    force,
+                })?
+            }
+            CacheSubcommands::Fmt { cache, pretty, .. } => process_cache_fmt(CacheFmtArgs {
+                cache: paths.resolve("cache", cache),
+                pretty,
+            })?,
+            CacheSubcommands::Check { cache, config, fix } => {
+                process_cache_check(CacheCheckArgs {
+                    cache: paths.resolve("cache", cache),
+                    config: paths.resolve_config(config),
+                    fix,
+                })?
+            }
+        },
         Commands::Bundlr {
             keypair,
             rpc_url,
@@ -291,3 +691,58 @@ async fn run() -> Result<()> {
 
     Ok(())
 }
+
+/// Prints the arguments and config values set by environment variables. Only the
+/// names are printed, values can be secrets (e.g. the NFT Storage token).
+fn print_env_values(command: &Command, matches: &ArgMatches) {
+    let arguments = env_arguments(command, matches);
+    let overrides = config_env_overrides();
+
+    if arguments.is_empty() && overrides.is_empty() {
+        return;
+    }
+
+    println!("{}", style("Values from the environment:").dim());
+    for (id, env) in arguments {
+        println!(
+            "{}",
+            style(format!("  --{} from {}", id.replace('_', "-"), env)).dim()
+        );
+    }
+    for (field, env, _) in overrides {
+        println!(
+            "{}",
+            style(format!("  config {} from {}", field, env)).dim()
+        );
+    }
+}
+
+/// Cache file written by the command (and the argument it comes from), which needs
+/// to be locked while the command runs.
+fn cache_to_lock(command: &Commands) -> Option<(&'static str, String)> {
+    match command {
+        Commands::Launch { cache, .. }
+        | Commands::Deploy { cache, .. }
+        | Commands::Upload { cache, .. }
+        | Commands::Verify {
+            cache,
+            repair: true,
+            ..
+        } => Some(("cache", cache.clone())),
+        Commands::Collection { command } => match command {
+            CollectionSubcommands::Set { cache, .. }
+            | CollectionSubcommands::Remove { cache, .. } => Some(("cache", cache.clone())),
+        },
+        Commands::Cache { command } => match command {
+            CacheSubcommands::Import { out, .. }
+            | CacheSubcommands::Export { out, .. }
+            | CacheSubcommands::Merge { out, .. } => Some(("out", out.clone())),
+            CacheSubcommands::Fmt { cache, .. }
+            | CacheSubcommands::Check {
+                cache, fix: true, ..
+            } => Some(("cache", cache.clone())),
+            CacheSubcommands::Check { .. } => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,282 @@
+use anchor_client::{
+    solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Keypair},
+    Client,
+};
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::utils::write_atomic;
+
+/// Default compute unit limit of a transaction instruction, used to estimate
+/// the priority fee when no explicit limit is requested.
+pub const DEFAULT_COMPUTE_UNITS: u32 = 200_000;
+
+/// Delay (in milliseconds) before retrying a failed mint; it grows linearly
+/// with the number of attempts.
+pub const MINT_RETRY_BACKOFF_MS: u64 = 500;
+
+/// Estimated lamports (rent and fees) needed to pay for the accounts created
+/// by a mint, excluding the price.
+pub const MINT_COST_ESTIMATE: u64 = 15_000_000;
+
+/// Maximum time (in seconds) to wait for a mint to be confirmed and its
+/// metadata account to be available.
+pub const METADATA_POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Initial delay (in milliseconds) between metadata account checks.
+pub const METADATA_POLL_INITIAL_MS: u64 = 250;
+
+/// Maximum delay (in seconds) between metadata account checks.
+pub const METADATA_POLL_MAX_DELAY_SECS: u64 = 4;
+
+/// Interval (in seconds) between state refreshes while waiting for go live.
+pub const WAIT_REFRESH_SECS: i64 = 30;
+
+/// Interval (in seconds) between state refreshes close to the go live date.
+pub const WAIT_REFRESH_NEAR_SECS: i64 = 3;
+
+/// Time (in seconds) before the go live date considered close to it.
+pub const WAIT_BOUNDARY_SECS: i64 = 60;
+
+/// Headroom (in percent) added to the simulated compute units of a mint.
+pub const COMPUTE_UNITS_HEADROOM: u64 = 20;
+
+/// Maximum compute unit limit of a transaction.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Options that control how the mint transaction is assembled.
+#[derive(Clone, Debug, Default)]
+pub struct MintOptions {
+    /// Priority fee (in micro-lamports per compute unit).
+    pub priority_fee: Option<u64>,
+    /// Compute unit limit requested for the transaction.
+    pub compute_units: Option<u32>,
+    /// Number of times a failed mint is retried.
+    pub retry: u32,
+    /// Token account holding the whitelist token (overrides the payer's ATA).
+    pub whitelist_token_account: Option<Pubkey>,
+    /// Gateway token account (overrides the payer's derived gateway token).
+    pub gateway_token: Option<Pubkey>,
+    /// Wallet receiving the NFT (defaults to the payer).
+    pub receiver: Option<Pubkey>,
+    /// Create the payment token account when it does not exist.
+    pub create_ata: bool,
+    /// Delay (in milliseconds) before sending the mint transaction.
+    pub delay_ms: u64,
+}
+
+impl MintOptions {
+    /// Return the priority fee in lamports paid by a mint transaction with the
+    /// specified compute unit limit (the default limit when not set).
+    pub fn priority_fee_lamports(&self, compute_units: Option<u32>) -> Option<u64> {
+        self.priority_fee.map(|fee| {
+            let units = compute_units.unwrap_or(DEFAULT_COMPUTE_UNITS) as u128;
+            // micro-lamports to lamports, rounding up
+            ((fee as u128 * units + 999_999) / 1_000_000) as u64
+        })
+    }
+}
+
+/// Instructions (and the new mint signer) of a mint transaction.
+pub struct MintTransaction {
+    pub instructions: Vec<Instruction>,
+    pub nft_mint: Keypair,
+    pub token_account: Pubkey,
+    pub metadata: Pubkey,
+}
+
+/// Wallet used to pay for mints.
+pub struct MintWallet {
+    pub pubkey: Pubkey,
+    pub client: Arc<Client>,
+    pub minted: u64,
+    pub skipped: bool,
+}
+
+impl MintWallet {
+    pub fn new(pubkey: Pubkey, client: Arc<Client>) -> Self {
+        MintWallet {
+            pubkey,
+            client,
+            minted: 0,
+            skipped: false,
+        }
+    }
+}
+
+/// Record of a successful mint.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintRecord {
+    pub mint: String,
+    pub token_account: String,
+    pub signature: String,
+    pub timestamp: i64,
+    pub magic_hat: String,
+    #[serde(default)]
+    pub payer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_fee: Option<u64>,
+}
+
+/// Load the mint records stored in the output file (if any), one JSON record
+/// per line.
+pub fn load_mint_records<P: AsRef<Path>>(path: P) -> Result<Vec<MintRecord>> {
+    let path = path.as_ref();
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        // blank lines (e.g. a trailing newline) are ignored
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|err| {
+            anyhow!(
+                "Failed to parse mint record on line {} of '{}': {}",
+                index + 1,
+                path.display(),
+                err
+            )
+        })?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Append a mint record as a new line of the output file, flushing it to disk
+/// so no history is lost if the process is interrupted.
+pub fn append_mint_record<P: AsRef<Path>>(path: P, record: &MintRecord) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path.as_ref())?;
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.sync_all()?;
+
+    Ok(())
+}
+
+/// Progress of a bulk mint.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintProgress {
+    pub requested: u64,
+    pub completed: u64,
+    pub payers: Vec<String>,
+    pub signatures: Vec<String>,
+}
+
+/// Tracks the progress of bulk mints (keyed by magic hat id) in a state file,
+/// so that an interrupted mint can be resumed.
+pub struct MintProgressTracker {
+    path: String,
+    magic_hat: String,
+    entries: IndexMap<String, MintProgress>,
+}
+
+impl MintProgressTracker {
+    pub fn load(path: &str, magic_hat: &str) -> Result<Self> {
+        let entries = if Path::new(path).exists() {
+            let file = File::open(path)?;
+            serde_json::from_reader(file)
+                .map_err(|err| anyhow!("Failed to parse mint state file '{}': {}", path, err))?
+        } else {
+            IndexMap::new()
+        };
+
+        Ok(MintProgressTracker {
+            path: path.to_string(),
+            magic_hat: magic_hat.to_string(),
+            entries,
+        })
+    }
+
+    /// Start tracking a new mint, replacing any previous progress.
+    pub fn start(&mut self, requested: u64, payers: Vec<String>) -> Result<()> {
+        self.entries.insert(
+            self.magic_hat.clone(),
+            MintProgress {
+                requested,
+                completed: 0,
+                payers,
+                signatures: Vec::new(),
+            },
+        );
+        self.save()
+    }
+
+    /// Number of mints remaining of an interrupted mint, if there is one.
+    pub fn unfinished(&self) -> Option<u64> {
+        self.entries
+            .get(&self.magic_hat)
+            .map(|progress| progress.requested.saturating_sub(progress.completed))
+    }
+
+    /// Resume a previous mint, returning the number of mints remaining. The
+    /// parameters must match the ones of the interrupted mint.
+    pub fn resume(&self, requested: u64, payers: &[String]) -> Result<u64> {
+        let progress = match self.entries.get(&self.magic_hat) {
+            Some(progress) => progress,
+            None => {
+                return Err(anyhow!(
+                    "No interrupted mint found for Magic Hat {}",
+                    self.magic_hat
+                ))
+            }
+        };
+
+        if progress.requested != requested {
+            return Err(anyhow!(
+                "Cannot resume: the interrupted mint requested {} item(s), not {}",
+                progress.requested,
+                requested
+            ));
+        }
+
+        if progress.payers != payers {
+            return Err(anyhow!(
+                "Cannot resume: the interrupted mint used different payer wallet(s)"
+            ));
+        }
+
+        Ok(progress.requested.saturating_sub(progress.completed))
+    }
+
+    /// Record a completed mint; the progress is removed once all mints are
+    /// completed.
+    pub fn complete(&mut self, signature: &str) -> Result<()> {
+        if let Some(progress) = self.entries.get_mut(&self.magic_hat) {
+            progress.completed += 1;
+            progress.signatures.push(signature.to_string());
+
+            if progress.completed >= progress.requested {
+                self.entries.remove(&self.magic_hat);
+            }
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&self.entries)?.as_bytes(),
+        )
+    }
+}
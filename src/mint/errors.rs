@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MintError {
+    #[error("Minting most likely failed with a bot tax. Check the transaction link for more details: {1}")]
+    BotTax(String, String),
+
+    #[error("Whitelist token account '{0}' not found")]
+    WhitelistTokenAccountNotFound(String),
+
+    #[error("Whitelist token account '{0}' holds the wrong mint (expected='{1}', found='{2}')")]
+    WhitelistTokenWrongMint(String, String, String),
+
+    #[error("Whitelist token account '{0}' has a zero balance")]
+    WhitelistTokenZeroBalance(String),
+
+    #[error("Payer '{1}' is neither the owner nor a delegate of whitelist token account '{0}'")]
+    WhitelistTokenNotAuthorized(String, String),
+
+    #[error("Gateway token '{0}' not found (the payer needs a valid gateway token to mint)")]
+    GatewayTokenNotFound(String),
+
+    #[error("Gateway token '{0}' is not valid: {1}")]
+    InvalidGatewayToken(String, String),
+
+    #[error(
+        "Payment token account '{0}' for mint '{1}' not found (use --create-ata to create it)"
+    )]
+    PaymentTokenAccountNotFound(String, String),
+
+    #[error("Payment token account '{0}' holds the wrong mint (expected='{1}', found='{2}')")]
+    PaymentTokenWrongMint(String, String, String),
+
+    #[error("Payment token account '{0}' is missing {1} token(s) to pay the price of {2}")]
+    NotEnoughPaymentTokens(String, String, String),
+}
@@ -1,3 +1,7 @@
+pub mod data;
+pub mod errors;
 pub mod process;
 
+pub use data::*;
+pub use errors::*;
 pub use process::*;
@@ -1,23 +1,42 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    cmp,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 use anchor_client::{
     solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        packet::PACKET_DATA_SIZE,
         program_pack::Pack,
         pubkey::Pubkey,
         signature::{Keypair, Signature, Signer},
         system_instruction, system_program, sysvar,
+        transaction::TransactionError,
     },
-    Client,
+    Client, ClientError,
 };
 use anchor_lang::prelude::AccountMeta;
 use anyhow::Result;
 use chrono::Utc;
 use console::style;
+use futures::future::select_all;
 use magic_hat::instruction as nft_instruction;
 use magic_hat::{accounts as nft_accounts, CollectionPDA};
 use magic_hat::{EndSettingType, MagicHat, MagicHatError, WhitelistMintMode};
 use mpl_token_metadata::pda::find_collection_authority_account;
-use solana_client::rpc_response::Response;
+use rand::Rng;
+use solana_client::{
+    client_error::{ClientError as RpcClientError, ClientErrorKind},
+    rpc_config::RpcSimulateTransactionConfig,
+    rpc_response::Response,
+};
+use solana_program::program_option::COption;
 use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
 use spl_token::{
     instruction::{initialize_mint, mint_to},
@@ -26,25 +45,76 @@ use spl_token::{
 };
 
 use crate::cache::load_cache;
+use crate::cli::OutputFormat;
 use crate::common::*;
-use crate::config::Cluster;
+use crate::config::{get_config_data, Cluster, LadduConfig};
 use crate::magic_hat::MAGIC_HAT_ID;
 use crate::magic_hat::*;
+use crate::mint::data::*;
+use crate::mint::errors::*;
 use crate::pdas::*;
+use crate::program_errors::decode_program_error;
 use crate::utils::*;
 
 pub struct MintArgs {
     pub keypair: Option<String>,
+    pub keypairs: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
     pub number: Option<u64>,
     pub magic_hat: Option<String>,
+    pub config: String,
+    pub priority_fee: Option<u64>,
+    pub compute_units: Option<u32>,
+    pub retry: u32,
+    pub whitelist_token_account: Option<String>,
+    pub gateway_token: Option<String>,
+    pub simulate: bool,
+    pub wait_for_live: bool,
+    pub fetch_json: bool,
+    pub delay_ms: u64,
+    pub resume: bool,
+    pub restart: bool,
+    pub create_ata: bool,
+    pub no_collection: bool,
+    pub parallel: usize,
+    pub out: String,
+    pub output: OutputFormat,
+    pub interrupted: Arc<AtomicBool>,
 }
 
-pub fn process_mint(args: MintArgs) -> Result<()> {
-    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+pub async fn process_mint(args: MintArgs) -> Result<()> {
+    let keypair_paths = match args.keypairs {
+        Some(ref keypairs) => Some(parse_keypair_paths(keypairs)?),
+        None => None,
+    };
+
+    // when multiple keypairs are specified, the first one is used to load the
+    // magic hat information; --keypair (possibly set through LADDU_KEYPAIR) is
+    // ignored in that case
+    let keypair = match keypair_paths {
+        Some(ref paths) => Some(paths[0].clone()),
+        None => args.keypair,
+    };
+
+    let laddu_config = laddu_setup(keypair, args.rpc_url.clone())?;
     let client = Arc::new(setup_client(&laddu_config)?);
 
+    let mut wallets = vec![MintWallet::new(
+        laddu_config.keypair.pubkey(),
+        Arc::clone(&client),
+    )];
+
+    if let Some(paths) = keypair_paths {
+        for path in paths.iter().skip(1) {
+            let config = laddu_setup(Some(path.clone()), args.rpc_url.clone())?;
+            wallets.push(MintWallet::new(
+                config.keypair.pubkey(),
+                Arc::new(setup_client(&config)?),
+            ));
+        }
+    }
+
     // the magic hat id specified takes precedence over the one from the cache
 
     let magic_hat_id = match args.magic_hat {
@@ -64,31 +134,131 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
         }
     };
 
-    println!(
-        "{} {}Loading Magic Hat",
-        style("[1/2]").bold().dim(),
-        LOOKING_GLASS_EMOJI
-    );
-    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+    // in JSON mode only the mint records are printed to stdout, progress
+    // messages are still drawn on stderr
+    let text_output = args.output == OutputFormat::Text;
+
+    if text_output {
+        println!(
+            "{} {}Loading Magic Hat",
+            style("[1/2]").bold().dim(),
+            LOOKING_GLASS_EMOJI
+        );
+        println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+    }
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let magic_hat_state = Arc::new(get_magic_hat_state(&laddu_config, &magichat_pubkey)?);
+    let mut magic_hat_state = Arc::new(get_magic_hat_state(&laddu_config, &magichat_pubkey)?);
 
-    let collection_pda_info =
-        Arc::new(get_collection_pda(&magichat_pubkey, &client.program(MAGIC_HAT_ID)).ok());
+    let collection_pda_info = Arc::new(if args.no_collection {
+        warn!("Skipping the set collection instruction (--no-collection)");
+        None
+    } else {
+        get_collection_pda(&magichat_pubkey, &client.program(MAGIC_HAT_ID)).ok()
+    });
 
     pb.finish_with_message("Done");
 
-    println!(
-        "{} {}Minting from Magic Hat",
-        style("[2/2]").bold().dim(),
-        MAGICHAT_EMOJI
-    );
-    println!("Magic Hat ID: {}", &magic_hat_id);
+    if text_output {
+        println!(
+            "{} {}Minting from Magic Hat",
+            style("[2/2]").bold().dim(),
+            MAGICHAT_EMOJI
+        );
+        println!("Magic Hat ID: {}", &magic_hat_id);
+    }
+
+    // command-line options take precedence over the config file values
+    let config_data = if Path::new(&args.config).exists() {
+        Some(get_config_data(&args.config)?)
+    } else {
+        None
+    };
+
+    let options = MintOptions {
+        priority_fee: args
+            .priority_fee
+            .or_else(|| config_data.as_ref().and_then(|c| c.priority_fee)),
+        compute_units: args
+            .compute_units
+            .or_else(|| config_data.as_ref().and_then(|c| c.compute_units)),
+        retry: args.retry,
+        whitelist_token_account: match args.whitelist_token_account {
+            Some(ref token_account) => match Pubkey::from_str(token_account) {
+                Ok(pubkey) => Some(pubkey),
+                Err(_) => {
+                    let error =
+                        anyhow!("Failed to parse whitelist token account: {}", token_account);
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+            },
+            None => None,
+        },
+        create_ata: args.create_ata,
+        delay_ms: args.delay_ms,
+        gateway_token: match args.gateway_token {
+            Some(ref gateway_token) => match Pubkey::from_str(gateway_token) {
+                Ok(pubkey) => Some(pubkey),
+                Err(_) => {
+                    let error = anyhow!("Failed to parse gateway token: {}", gateway_token);
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+            },
+            None => None,
+        },
+    };
+
+    if args.wait_for_live {
+        // handles ctrl-c while waiting instead of exiting right away
+        args.interrupted.store(false, Ordering::SeqCst);
+
+        magic_hat_state = wait_for_go_live(
+            &client,
+            magichat_pubkey,
+            magic_hat_state,
+            &options,
+            &args.interrupted,
+        )?;
+
+        args.interrupted.store(true, Ordering::SeqCst);
+    }
+
+    if args.simulate {
+        info!("Simulating mint from Magic Hat: {}", &magic_hat_id);
+
+        return simulate_mint(
+            Arc::clone(&client),
+            magichat_pubkey,
+            Arc::clone(&magic_hat_state),
+            Arc::clone(&collection_pda_info),
+            &options,
+        );
+    }
+
+    let requested = args.number.unwrap_or(1);
+    let payers: Vec<String> = wallets.iter().map(|w| w.pubkey.to_string()).collect();
+    let mut progress = MintProgressTracker::load(DEFAULT_MINT_STATE, &magic_hat_id)?;
+
+    let number = if args.resume {
+        let remaining = progress.resume(requested, &payers)?;
+        eprintln!(
+            "Resuming interrupted mint: {} of {} item(s) remaining",
+            remaining, requested
+        );
+
+        if remaining == 0 {
+            return Ok(());
+        }
+
+        remaining
+    } else {
+        requested
+    };
 
-    let number = args.number.unwrap_or(1);
     let available = magic_hat_state.data.items_available - magic_hat_state.items_redeemed;
 
     if number > available || number == 0 {
@@ -97,23 +267,58 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
         return Err(error);
     }
 
+    // the progress of an interrupted mint is only replaced once the request
+    // is valid, and only when explicitly asked to
+    if !args.resume {
+        if let Some(remaining) = progress.unfinished() {
+            if !args.restart {
+                let error = anyhow!(
+                    "An interrupted mint with {} item(s) remaining exists for Magic Hat {}, \
+                    use --resume to continue it or --restart to discard it",
+                    remaining,
+                    magic_hat_id
+                );
+                error!("{:?}", error);
+                return Err(error);
+            }
+        }
+
+        progress.start(requested, payers)?;
+    }
+
     info!("Minting NFT from Magic Hat: {}", &magic_hat_id);
     info!("Magic Hat program id: {:?}", MAGIC_HAT_ID);
 
-    if number == 1 {
+    let mut records: Vec<MintRecord> = Vec::new();
+
+    if number == 1 && wallets.len() == 1 {
         let pb = spinner_with_style();
         pb.set_message(format!(
             "{} item(s) remaining",
             magic_hat_state.data.items_available - magic_hat_state.items_redeemed
         ));
 
-        let result = match mint(
+        let result = match mint_with_retry(
             Arc::clone(&client),
             magichat_pubkey,
             Arc::clone(&magic_hat_state),
             Arc::clone(&collection_pda_info),
+            &options,
         ) {
-            Ok(signature) => format!("{} {}", style("Signature:").bold(), signature),
+            Ok(record) => {
+                append_mint_record(&args.out, &record)?;
+                progress.complete(&record.signature)?;
+                let mut message = format!("{} {}", style("Signature:").bold(), record.signature);
+                if let Some(fee) = record.priority_fee {
+                    message.push_str(&format!(
+                        "\n{} {} lamports",
+                        style("Priority fee:").bold(),
+                        fee
+                    ));
+                }
+                records.push(record);
+                message
+            }
             Err(err) => {
                 pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
                 error!("{:?}", err);
@@ -122,50 +327,885 @@ pub fn process_mint(args: MintArgs) -> Result<()> {
         };
 
         pb.finish_with_message(result);
+    } else if args.parallel > 1 {
+        let pb = progress_bar_with_style(number);
+        let jobs = vec![options.clone(); number as usize];
+        let mut errors = Vec::new();
+
+        let skipped = mint_parallel(
+            Arc::new(laddu_config),
+            magichat_pubkey,
+            Arc::clone(&magic_hat_state),
+            Arc::clone(&collection_pda_info),
+            jobs,
+            args.parallel,
+            |_, result| {
+                match result {
+                    Ok(record) => {
+                        append_mint_record(&args.out, &record)?;
+                        progress.complete(&record.signature)?;
+                        records.push(record);
+                        pb.inc(1);
+                    }
+                    Err(err) => {
+                        error!("{:?}", err);
+                        errors.push(err);
+                    }
+                }
+                // stops dispatching new mints after the first error
+                Ok(errors.is_empty())
+            },
+        )
+        .await?;
+
+        if let Some(err) = errors.into_iter().next() {
+            pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
+            return Err(err);
+        }
+
+        if skipped > 0 {
+            pb.abandon_with_message(format!(
+                "{}",
+                style(format!("Magic Hat is empty: {} mint(s) not sent ", skipped))
+                    .yellow()
+                    .bold()
+            ));
+        } else {
+            pb.finish();
+        }
+
+        if options.priority_fee.is_some() && text_output {
+            // each record holds the fee for the compute unit limit of its transaction
+            let fees: u64 = records
+                .iter()
+                .filter_map(|record| record.priority_fee)
+                .sum();
+            println!("{} {} lamports", style("Priority fees paid:").bold(), fees);
+        }
     } else {
         let pb = progress_bar_with_style(number);
+        // the ETA includes the delay between mints since it is part of each step
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("[{elapsed_precise}] {msg}{wide_bar} {pos}/{len} (ETA {eta})"),
+        );
 
-        for _i in 0..number {
-            if let Err(err) = mint(
-                Arc::clone(&client),
+        // estimated lamports needed by a wallet to pay for a mint
+        let mint_cost = MINT_COST_ESTIMATE
+            + if magic_hat_state.token_mint.is_none() {
+                magic_hat_state.data.price
+            } else {
+                0
+            };
+        let mut next = 0;
+
+        for i in 0..number {
+            if i > 0 && args.delay_ms > 0 {
+                thread::sleep(delay_with_jitter(args.delay_ms));
+            }
+
+            let index = match next_wallet(&mut wallets, &mut next, mint_cost, &pb) {
+                Some(index) => index,
+                None => {
+                    pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
+                    let error = anyhow!("No wallet has enough balance to continue minting");
+                    error!("{:?}", error);
+                    print_wallet_summary(&wallets);
+                    return Err(error);
+                }
+            };
+
+            match mint_with_retry(
+                Arc::clone(&wallets[index].client),
                 magichat_pubkey,
                 Arc::clone(&magic_hat_state),
                 Arc::clone(&collection_pda_info),
+                &options,
             ) {
-                pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
-                error!("{:?}", err);
-                return Err(err);
+                Ok(record) => {
+                    append_mint_record(&args.out, &record)?;
+                    progress.complete(&record.signature)?;
+                    records.push(record);
+                    wallets[index].minted += 1;
+                }
+                Err(err) => {
+                    pb.abandon_with_message(format!("{}", style("Mint failed ").red().bold()));
+                    error!("{:?}", err);
+                    return Err(err);
+                }
             }
 
             pb.inc(1);
         }
 
         pb.finish();
+
+        if wallets.len() > 1 && text_output {
+            print_wallet_summary(&wallets);
+        }
+
+        if options.priority_fee.is_some() && text_output {
+            // each record holds the fee for the compute unit limit of its transaction
+            let fees: u64 = records
+                .iter()
+                .filter_map(|record| record.priority_fee)
+                .sum();
+            println!("{} {} lamports", style("Priority fees paid:").bold(), fees);
+        }
+    }
+
+    if args.output == OutputFormat::Text {
+        let program = client.program(MAGIC_HAT_ID);
+
+        for record in &records {
+            print_minted_nft(&program, record, &magic_hat_state, args.fetch_json).await;
+        }
+    }
+
+    // sent to stderr so the JSON output stays parseable
+    eprintln!("\nMinted NFTs recorded to {}", style(&args.out).bold());
+
+    if args.output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
     }
 
     Ok(())
 }
 
-pub fn mint(
-    client: Arc<Client>,
+/// Print the details of a minted NFT, optionally including the image and
+/// attributes from its off-chain JSON metadata.
+async fn print_minted_nft(
+    program: &Program,
+    record: &MintRecord,
+    magic_hat_state: &MagicHat,
+    fetch_json: bool,
+) {
+    let mint = match Pubkey::from_str(&record.mint) {
+        Ok(mint) => mint,
+        Err(_) => return,
+    };
+
+    println!("\n{} {}", style("Mint:").bold(), record.mint);
+
+    let metadata = match get_metadata_pda(&mint, program) {
+        Ok((_, metadata)) => metadata,
+        Err(err) => {
+            warn!("{:?}", err);
+            println!("{} {}", style("Failed to fetch metadata:").red(), err);
+            return;
+        }
+    };
+
+    let name = metadata.data.name.trim_matches(char::from(0));
+    let uri = metadata.data.uri.trim_matches(char::from(0));
+
+    println!("{} {}", style("Name:").bold(), name);
+    println!("{} {}", style("URI:").bold(), uri);
+
+    if let Some(hidden_settings) = &magic_hat_state.data.hidden_settings {
+        println!(
+            "{}",
+            style("Metadata is the hidden settings placeholder (revealed later)").dim()
+        );
+        println!(
+            "{} {}",
+            style("Hidden settings name:").bold(),
+            hidden_settings.name
+        );
+        return;
+    }
+
+    if fetch_json {
+        let http_client = HttpClient::new();
+        let json = match http_client.get(uri).send().await {
+            Ok(response) => response.json::<Value>().await,
+            Err(err) => Err(err),
+        };
+
+        match json {
+            Ok(json) => {
+                if let Some(image) = json["image"].as_str() {
+                    println!("{} {}", style("Image:").bold(), image);
+                }
+
+                if let Some(attributes) = json["attributes"].as_array() {
+                    println!("{}", style("Attributes:").bold());
+                    for attribute in attributes {
+                        println!(
+                            "  {}: {}",
+                            attribute["trait_type"].as_str().unwrap_or("-"),
+                            attribute["value"]
+                        );
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Failed to fetch JSON metadata from {}: {:?}", uri, err);
+                println!("{} {}", style("Failed to fetch JSON metadata:").red(), err);
+            }
+        }
+    }
+}
+
+/// Wait until the magic hat is live, refreshing its state periodically to pick
+/// up changes to the go live date. The wait is skipped when the payer is the
+/// authority or holds a whitelist token during presale.
+fn wait_for_go_live(
+    client: &Client,
+    magic_hat_id: Pubkey,
+    magic_hat_state: Arc<MagicHat>,
+    options: &MintOptions,
+    interrupted: &AtomicBool,
+) -> Result<Arc<MagicHat>> {
+    let program = client.program(MAGIC_HAT_ID);
+    let payer = program.payer();
+
+    if magic_hat_state.authority == payer {
+        return Ok(magic_hat_state);
+    }
+
+    if let Some(wl_mint_settings) = &magic_hat_state.data.whitelist_mint_settings {
+        // presale whitelist holders can mint before go live
+        if wl_mint_settings.presale
+            && whitelist_token_balance(&program, &payer, wl_mint_settings, options) > 0
+        {
+            return Ok(magic_hat_state);
+        }
+    }
+
+    let pb = spinner_with_style();
+    let mut magic_hat_state = magic_hat_state;
+    let mut last_refresh = Utc::now().timestamp();
+
+    loop {
+        if interrupted.load(Ordering::SeqCst) {
+            pb.abandon_with_message(format!("{}", style("Wait cancelled").red().bold()));
+            return Err(anyhow!("Mint cancelled while waiting for go live"));
+        }
+
+        let now = Utc::now().timestamp();
+
+        let remaining = match magic_hat_state.data.go_live_date {
+            Some(date) if date < now => break,
+            Some(date) => {
+                let remaining = date - now;
+                pb.set_message(format!(
+                    "Waiting for go live: {:02}:{:02}:{:02}",
+                    remaining / 3600,
+                    (remaining % 3600) / 60,
+                    remaining % 60
+                ));
+                Some(remaining)
+            }
+            None => {
+                pb.set_message("Waiting for go live (date not set)");
+                None
+            }
+        };
+
+        // refreshes more often close to the go live date
+        let refresh_interval = match remaining {
+            Some(remaining) if remaining <= WAIT_BOUNDARY_SECS => WAIT_REFRESH_NEAR_SECS,
+            _ => WAIT_REFRESH_SECS,
+        };
+
+        if now - last_refresh >= refresh_interval {
+            if let Ok(state) = program.account::<MagicHat>(magic_hat_id) {
+                magic_hat_state = Arc::new(state);
+            }
+            last_refresh = now;
+        }
+
+        thread::sleep(Duration::from_millis(250));
+    }
+
+    pb.finish_with_message("Magic Hat is live");
+
+    Ok(magic_hat_state)
+}
+
+/// Return the delay between mints with a random jitter of up to 20% (either
+/// way) so concurrent mints do not run in lockstep.
+fn delay_with_jitter(delay_ms: u64) -> Duration {
+    let jitter = delay_ms / 5;
+    let delay = if jitter > 0 {
+        rand::thread_rng().gen_range(delay_ms - jitter, delay_ms + jitter + 1)
+    } else {
+        delay_ms
+    };
+
+    Duration::from_millis(delay)
+}
+
+/// Parse the list of keypairs, either a directory containing keypair (JSON) files
+/// or a comma-separated list of keypair files.
+fn parse_keypair_paths(keypairs: &str) -> Result<Vec<String>> {
+    let path = Path::new(keypairs);
+
+    let paths: Vec<String> = if path.is_dir() {
+        let mut paths = Vec::new();
+
+        for entry in std::fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            if entry_path.extension().and_then(|e| e.to_str()) == Some("json") {
+                paths.push(path_to_string(&entry_path)?);
+            }
+        }
+
+        paths.sort();
+        paths
+    } else {
+        keypairs
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect()
+    };
+
+    if paths.is_empty() {
+        return Err(anyhow!("No keypair files found in '{}'", keypairs));
+    }
+
+    Ok(paths)
+}
+
+/// Return the index of the next (round-robin) wallet with enough balance to
+/// mint; wallets with a balance too low are skipped from then on.
+fn next_wallet(
+    wallets: &mut [MintWallet],
+    next: &mut usize,
+    mint_cost: u64,
+    pb: &ProgressBar,
+) -> Option<usize> {
+    for _ in 0..wallets.len() {
+        let index = *next % wallets.len();
+        *next += 1;
+
+        let wallet = &mut wallets[index];
+
+        if wallet.skipped {
+            continue;
+        }
+
+        let balance = wallet
+            .client
+            .program(MAGIC_HAT_ID)
+            .rpc()
+            .get_balance(&wallet.pubkey)
+            .unwrap_or(0);
+
+        if balance < mint_cost {
+            let message = format!(
+                "Skipping wallet {}: balance of {} lamports is too low (minimum {} lamports)",
+                wallet.pubkey, balance, mint_cost
+            );
+            warn!("{}", message);
+            pb.println(format!("{} {}", style("Warning:").yellow().bold(), message));
+            wallet.skipped = true;
+            continue;
+        }
+
+        return Some(index);
+    }
+
+    None
+}
+
+fn print_wallet_summary(wallets: &[MintWallet]) {
+    println!("\n{}", style("Mints per wallet:").bold());
+
+    for wallet in wallets {
+        if wallet.skipped {
+            println!(
+                "  {}: {} minted {}",
+                wallet.pubkey,
+                wallet.minted,
+                style("(skipped: balance too low)").yellow()
+            );
+        } else {
+            println!("  {}: {} minted", wallet.pubkey, wallet.minted);
+        }
+    }
+}
+
+/// Mint one NFT for each of the `jobs` options, running up to `parallel` mints
+/// concurrently. The `on_result` callback is called with the index of the job
+/// as each mint completes; it returns whether the remaining jobs should still
+/// be dispatched (in-flight mints are always waited for).
+///
+/// Mints are only dispatched while items are available, taking into account
+/// the mints in flight. Returns the number of jobs not dispatched because the
+/// magic hat is empty.
+pub async fn mint_parallel<F>(
+    laddu_config: Arc<LadduConfig>,
     magic_hat_id: Pubkey,
     magic_hat_state: Arc<MagicHat>,
     collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
-) -> Result<Signature> {
+    jobs: Vec<MintOptions>,
+    parallel: usize,
+    mut on_result: F,
+) -> Result<usize>
+where
+    F: FnMut(usize, Result<MintRecord>) -> Result<bool>,
+{
+    let parallel = cmp::max(1, cmp::min(parallel, PARALLEL_LIMIT));
+    let client = setup_client(&laddu_config)?;
     let program = client.program(MAGIC_HAT_ID);
+
+    let items_available = magic_hat_state.data.items_available;
+    let mut items_redeemed = magic_hat_state.items_redeemed;
+    // number of mints dispatched but not yet completed
+    let in_flight = AtomicU64::new(0);
+
+    let mut jobs = jobs.into_iter().enumerate().peekable();
+    let mut handles = Vec::new();
+    let mut dispatch = true;
+    let mut empty = false;
+    let mut completed = 0;
+
+    let spawn = |index: usize, options: MintOptions| {
+        let config = Arc::clone(&laddu_config);
+        let magic_hat_state = Arc::clone(&magic_hat_state);
+        let collection_pda_info = Arc::clone(&collection_pda_info);
+
+        tokio::task::spawn_blocking(move || {
+            if options.delay_ms > 0 {
+                thread::sleep(delay_with_jitter(options.delay_ms));
+            }
+
+            let result = setup_client(&config).and_then(|client| {
+                mint_with_retry(
+                    Arc::new(client),
+                    magic_hat_id,
+                    magic_hat_state,
+                    collection_pda_info,
+                    &options,
+                )
+            });
+            (index, result)
+        })
+    };
+
+    loop {
+        // dispatches jobs while there are free slots and available items
+        while dispatch && !empty && handles.len() < parallel {
+            let pending = items_redeemed + in_flight.load(Ordering::SeqCst);
+
+            if items_available <= pending {
+                empty = true;
+                break;
+            }
+
+            match jobs.next() {
+                Some((index, options)) => {
+                    in_flight.fetch_add(1, Ordering::SeqCst);
+                    handles.push(spawn(index, options));
+                }
+                None => break,
+            }
+        }
+
+        if handles.is_empty() {
+            break;
+        }
+
+        let (result, _, remaining) = select_all(handles).await;
+        handles = remaining;
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        let (index, result) = result?;
+
+        if result.is_ok() {
+            items_redeemed += 1;
+        }
+
+        dispatch = on_result(index, result)? && dispatch;
+        completed += 1;
+
+        // refreshes the redeemed count between batches (or when the magic hat
+        // seems empty) to account for mints from other wallets
+        if empty || completed % parallel == 0 {
+            if let Ok(state) = program.account::<MagicHat>(magic_hat_id) {
+                items_redeemed = state.items_redeemed;
+            }
+            empty = false;
+        }
+    }
+
+    if dispatch && jobs.peek().is_some() {
+        Ok(jobs.count())
+    } else {
+        Ok(0)
+    }
+}
+
+/// Mint an NFT, retrying up to `options.retry` times when the mint fails with a
+/// bot tax or a retryable RPC error.
+pub fn mint_with_retry(
+    client: Arc<Client>,
+    magic_hat_id: Pubkey,
+    magic_hat_state: Arc<MagicHat>,
+    collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    options: &MintOptions,
+) -> Result<MintRecord> {
+    let mut magic_hat_state = magic_hat_state;
+    let mut attempt = 0;
+
+    loop {
+        match mint(
+            Arc::clone(&client),
+            magic_hat_id,
+            Arc::clone(&magic_hat_state),
+            Arc::clone(&collection_pda_info),
+            options,
+        ) {
+            Ok(record) => return Ok(record),
+            Err(err) => {
+                if let Some(MintError::BotTax(signature, _)) = err.downcast_ref::<MintError>() {
+                    warn!(
+                        "Mint attempt {} failed with a bot tax: {}",
+                        attempt + 1,
+                        signature
+                    );
+                } else {
+                    warn!("Mint attempt {} failed: {:?}", attempt + 1, err);
+                }
+
+                if attempt >= options.retry || !is_retryable(&err) {
+                    return Err(err);
+                }
+            }
+        }
+
+        attempt += 1;
+        thread::sleep(Duration::from_millis(
+            MINT_RETRY_BACKOFF_MS * attempt as u64,
+        ));
+
+        // refreshes the state since the failure might have been caused by
+        // a stale view of the magic hat
+        let program = client.program(MAGIC_HAT_ID);
+        if let Ok(state) = program.account::<MagicHat>(magic_hat_id) {
+            magic_hat_state = Arc::new(state);
+        }
+
+        info!(
+            "Retrying mint (attempt {} of {})",
+            attempt + 1,
+            options.retry + 1
+        );
+    }
+}
+
+/// Return whether a mint error is worth retrying.
+fn is_retryable(err: &anyhow::Error) -> bool {
+    // only a bot tax is worth retrying, the other mint errors are validation
+    // failures that would fail again
+    if let Some(mint_error) = err.downcast_ref::<MintError>() {
+        return matches!(mint_error, MintError::BotTax(..));
+    }
+
+    let rpc_error = match err.downcast_ref::<ClientError>() {
+        Some(ClientError::SolanaClientError(rpc_error)) => rpc_error,
+        _ => match err.downcast_ref::<RpcClientError>() {
+            Some(rpc_error) => rpc_error,
+            None => return false,
+        },
+    };
+
+    matches!(
+        rpc_error.kind(),
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_)
+    ) || matches!(
+        rpc_error.get_transaction_error(),
+        Some(TransactionError::BlockhashNotFound)
+    )
+}
+
+/// Check that the token account holds a positive balance of the whitelist mint
+/// and that the payer is either its owner or a delegate.
+fn check_whitelist_token_account(
+    program: &Program,
+    token_account: &Pubkey,
+    whitelist_mint: &Pubkey,
+    payer: &Pubkey,
+) -> Result<()> {
+    let data = match program
+        .rpc()
+        .get_account_with_commitment(token_account, CommitmentConfig::confirmed())?
+        .value
+    {
+        Some(account) if !account.data.is_empty() => account.data,
+        _ => {
+            return Err(MintError::WhitelistTokenAccountNotFound(token_account.to_string()).into())
+        }
+    };
+
+    let account = Account::unpack_unchecked(&data)?;
+
+    if account.mint != *whitelist_mint {
+        return Err(MintError::WhitelistTokenWrongMint(
+            token_account.to_string(),
+            whitelist_mint.to_string(),
+            account.mint.to_string(),
+        )
+        .into());
+    }
+
+    if account.amount == 0 {
+        return Err(MintError::WhitelistTokenZeroBalance(token_account.to_string()).into());
+    }
+
+    let is_delegate = account.delegate == COption::Some(*payer) && account.delegated_amount > 0;
+
+    if account.owner != *payer && !is_delegate {
+        return Err(MintError::WhitelistTokenNotAuthorized(
+            token_account.to_string(),
+            payer.to_string(),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Relevant fields of a (Civic) gateway token account.
+struct GatewayTokenInfo {
+    owner_wallet: Pubkey,
+    gatekeeper_network: Pubkey,
+    state: u8,
+    expire_time: Option<i64>,
+}
+
+/// Parse the (borsh serialized) data of a gateway token account.
+fn parse_gateway_token(data: &[u8]) -> Option<GatewayTokenInfo> {
+    let read_pubkey = |offset: &mut usize| -> Option<Pubkey> {
+        let bytes = data.get(*offset..*offset + 32)?;
+        *offset += 32;
+        Some(Pubkey::new(bytes))
+    };
+    let read_u8 = |offset: &mut usize| -> Option<u8> {
+        let value = *data.get(*offset)?;
+        *offset += 1;
+        Some(value)
+    };
+
+    // features
+    let mut offset = 1;
+    // parent gateway token
+    if read_u8(&mut offset)? == 1 {
+        read_pubkey(&mut offset)?;
+    }
+    let owner_wallet = read_pubkey(&mut offset)?;
+    // owner identity
+    if read_u8(&mut offset)? == 1 {
+        read_pubkey(&mut offset)?;
+    }
+    let gatekeeper_network = read_pubkey(&mut offset)?;
+    // issuing gatekeeper
+    read_pubkey(&mut offset)?;
+    let state = read_u8(&mut offset)?;
+    let expire_time = if read_u8(&mut offset)? == 1 {
+        let bytes = data.get(offset..offset + 8)?;
+        Some(i64::from_le_bytes(bytes.try_into().ok()?))
+    } else {
+        None
+    };
+
+    Some(GatewayTokenInfo {
+        owner_wallet,
+        gatekeeper_network,
+        state,
+        expire_time,
+    })
+}
+
+/// Check that the gateway token is active, not expired and issued to the payer
+/// by the expected gatekeeper network.
+fn check_gateway_token(
+    program: &Program,
+    gateway_token: &Pubkey,
+    gatekeeper_network: &Pubkey,
+    payer: &Pubkey,
+) -> Result<()> {
+    let account = match program
+        .rpc()
+        .get_account_with_commitment(gateway_token, CommitmentConfig::confirmed())?
+        .value
+    {
+        Some(account) => account,
+        None => return Err(MintError::GatewayTokenNotFound(gateway_token.to_string()).into()),
+    };
+
+    let invalid = |reason: String| -> anyhow::Error {
+        MintError::InvalidGatewayToken(gateway_token.to_string(), reason).into()
+    };
+
+    if account.owner != Pubkey::from_str(CIVIC)? {
+        return Err(invalid(format!(
+            "account is owned by '{}', not the gateway program",
+            account.owner
+        )));
+    }
+
+    let token = match parse_gateway_token(&account.data) {
+        Some(token) => token,
+        None => return Err(invalid("failed to parse account data".to_string())),
+    };
+
+    if token.owner_wallet != *payer {
+        return Err(invalid(format!(
+            "issued to '{}' instead of the payer '{}'",
+            token.owner_wallet, payer
+        )));
+    }
+
+    if token.gatekeeper_network != *gatekeeper_network {
+        return Err(invalid(format!(
+            "issued by gatekeeper network '{}' (expected '{}')",
+            token.gatekeeper_network, gatekeeper_network
+        )));
+    }
+
+    match token.state {
+        0 => (),
+        1 => return Err(invalid("token is frozen".to_string())),
+        _ => return Err(invalid("token has been revoked".to_string())),
+    }
+
+    if let Some(expire_time) = token.expire_time {
+        if expire_time < Utc::now().timestamp() {
+            return Err(invalid(format!("token expired at {}", expire_time)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the whitelist token balance of the payer (zero if the token account
+/// does not exist).
+fn whitelist_token_balance(
+    program: &Program,
+    payer: &Pubkey,
+    wl_mint_settings: &magic_hat::WhitelistMintSettings,
+    options: &MintOptions,
+) -> u64 {
+    let token_account = options
+        .whitelist_token_account
+        .unwrap_or_else(|| get_associated_token_address(payer, &wl_mint_settings.mint));
+
+    match program.rpc().get_account_data(&token_account) {
+        Ok(data) => match Account::unpack_unchecked(&data) {
+            Ok(account) if account.mint == wl_mint_settings.mint => account.amount,
+            _ => 0,
+        },
+        Err(_) => 0,
+    }
+}
+
+/// Check that the payer's token account can pay for the mint when the magic
+/// hat takes an SPL token as payment.
+fn check_spl_payment(
+    program: &Program,
+    magic_hat_state: &MagicHat,
+    options: &MintOptions,
+) -> Result<()> {
+    let token_mint = match magic_hat_state.token_mint {
+        Some(token_mint) => token_mint,
+        None => return Ok(()),
+    };
+
     let payer = program.payer();
-    let wallet = magic_hat_state.wallet;
+    let token_account = get_associated_token_address(&payer, &token_mint);
+    let mut price = magic_hat_state.data.price;
+
+    // whitelist token holders pay the discount price
+    if let Some(wl_mint_settings) = &magic_hat_state.data.whitelist_mint_settings {
+        if let Some(discount_price) = wl_mint_settings.discount_price {
+            if whitelist_token_balance(program, &payer, wl_mint_settings, options) > 0 {
+                price = discount_price;
+            }
+        }
+    }
 
+    let balance = match program
+        .rpc()
+        .get_account_with_commitment(&token_account, CommitmentConfig::confirmed())?
+        .value
+    {
+        Some(account) if !account.data.is_empty() => {
+            let account = Account::unpack_unchecked(&account.data)?;
+
+            if account.mint != token_mint {
+                return Err(MintError::PaymentTokenWrongMint(
+                    token_account.to_string(),
+                    token_mint.to_string(),
+                    account.mint.to_string(),
+                )
+                .into());
+            }
+
+            account.amount
+        }
+        _ => {
+            if !options.create_ata {
+                return Err(MintError::PaymentTokenAccountNotFound(
+                    token_account.to_string(),
+                    token_mint.to_string(),
+                )
+                .into());
+            }
+            // the account will be created as part of the mint transaction
+            0
+        }
+    };
+
+    if balance < price {
+        let decimals = check_spl_token(program, &token_mint.to_string())
+            .map(|mint| mint.decimals)
+            .unwrap_or(0);
+
+        return Err(MintError::NotEnoughPaymentTokens(
+            token_account.to_string(),
+            format_token_amount(price - balance, decimals),
+            format_token_amount(price, decimals),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn format_token_amount(amount: u64, decimals: u8) -> String {
+    (amount as f64 / 10f64.powi(decimals as i32)).to_string()
+}
+
+/// Check the conditions required to mint from the magic hat, returning the
+/// first one that fails.
+pub fn check_mint_conditions(
+    program: &Program,
+    magic_hat_state: &MagicHat,
+    options: &MintOptions,
+) -> Result<()> {
+    let payer = program.payer();
     let magic_hat_data = &magic_hat_state.data;
 
-    if let Some(_gatekeeper) = &magic_hat_data.gatekeeper {
-        return Err(anyhow!(
-            "Command-line mint disabled (gatekeeper settings in use)"
-        ));
-    } else if magic_hat_state.items_redeemed >= magic_hat_data.items_available {
+    if magic_hat_state.items_redeemed >= magic_hat_data.items_available {
         return Err(anyhow!(MagicHatError::MagicHatEmpty));
     }
 
+    if let Some(gatekeeper) = &magic_hat_data.gatekeeper {
+        let gateway_token = options
+            .gateway_token
+            .unwrap_or_else(|| find_gateway_token_pda(&payer, &gatekeeper.gatekeeper_network));
+        check_gateway_token(
+            program,
+            &gateway_token,
+            &gatekeeper.gatekeeper_network,
+            &payer,
+        )?;
+    }
+
     if magic_hat_state.authority != payer {
         // we are not authority, we need to follow the rules
         // 1. go_live_date
@@ -184,7 +1224,7 @@ pub fn mint(
         if let Some(wl_mint_settings) = &magic_hat_data.whitelist_mint_settings {
             if wl_mint_settings.presale {
                 // we (temporarily) enable the mint - we will validate if the user
-                // has the wl token when creating the transaction
+                // has the wl token below
                 mint_enabled = true;
             } else if !mint_enabled {
                 return Err(anyhow!(MagicHatError::MagicHatNotLive));
@@ -215,6 +1255,49 @@ pub fn mint(
         }
     }
 
+    check_spl_payment(program, magic_hat_state, options)?;
+
+    // Check whitelist mint settings
+    if let Some(wl_mint_settings) = &magic_hat_data.whitelist_mint_settings {
+        if let Some(token_account) = options.whitelist_token_account {
+            check_whitelist_token_account(program, &token_account, &wl_mint_settings.mint, &payer)?;
+        } else if wl_mint_settings.mode == WhitelistMintMode::BurnEveryTime {
+            let whitelist_token_account =
+                get_associated_token_address(&payer, &wl_mint_settings.mint);
+            let mut token_found = false;
+
+            match program.rpc().get_account_data(&whitelist_token_account) {
+                Ok(ata_data) => {
+                    if !ata_data.is_empty() {
+                        let account = Account::unpack_unchecked(&ata_data)?;
+                        token_found = account.amount > 0;
+                    }
+                }
+                Err(err) => return Err(anyhow!(err)),
+            }
+
+            if !token_found {
+                return Err(anyhow!(MagicHatError::NoWhitelistToken));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the instructions of a mint transaction. This assumes that the mint
+/// conditions have been checked.
+pub fn build_mint_transaction(
+    program: &Program,
+    magic_hat_id: Pubkey,
+    magic_hat_state: &MagicHat,
+    collection_pda_info: &Option<PdaInfo<CollectionPDA>>,
+    options: &MintOptions,
+) -> Result<MintTransaction> {
+    let payer = program.payer();
+    let wallet = magic_hat_state.wallet;
+    let magic_hat_data = &magic_hat_state.data;
+
     let nft_mint = Keypair::new();
     let metaplex_program_id = Pubkey::from_str(METAPLEX_PROGRAM_ID)?;
 
@@ -241,12 +1324,15 @@ pub fn mint(
         0,
     )?;
 
+    // the NFT is minted to the payer unless a receiver is specified
+    let receiver = options.receiver.unwrap_or(payer);
+
     // Derive associated token account
-    let assoc = get_associated_token_address(&payer, &nft_mint.pubkey());
+    let assoc = get_associated_token_address(&receiver, &nft_mint.pubkey());
 
     // Create associated account instruction
     let create_assoc_account_ix =
-        create_associated_token_account(&payer, &payer, &nft_mint.pubkey());
+        create_associated_token_account(&payer, &receiver, &nft_mint.pubkey());
 
     // Mint to instruction
     let mint_to_ix = mint_to(
@@ -260,9 +1346,38 @@ pub fn mint(
 
     let mut additional_accounts: Vec<AccountMeta> = Vec::new();
 
-    // Check whitelist mint settings
+    // Gatekeeper settings
+    if let Some(gatekeeper) = &magic_hat_data.gatekeeper {
+        let gateway_token = options
+            .gateway_token
+            .unwrap_or_else(|| find_gateway_token_pda(&payer, &gatekeeper.gatekeeper_network));
+
+        additional_accounts.push(AccountMeta {
+            pubkey: gateway_token,
+            is_signer: false,
+            is_writable: true,
+        });
+
+        if gatekeeper.expire_on_use {
+            additional_accounts.push(AccountMeta {
+                pubkey: Pubkey::from_str(CIVIC)?,
+                is_signer: false,
+                is_writable: false,
+            });
+
+            additional_accounts.push(AccountMeta {
+                pubkey: find_gateway_expire_pda(&gatekeeper.gatekeeper_network),
+                is_signer: false,
+                is_writable: false,
+            });
+        }
+    }
+
+    // Whitelist mint settings
     if let Some(wl_mint_settings) = &magic_hat_data.whitelist_mint_settings {
-        let whitelist_token_account = get_associated_token_address(&payer, &wl_mint_settings.mint);
+        let whitelist_token_account = options
+            .whitelist_token_account
+            .unwrap_or_else(|| get_associated_token_address(&payer, &wl_mint_settings.mint));
 
         additional_accounts.push(AccountMeta {
             pubkey: whitelist_token_account,
@@ -271,36 +1386,17 @@ pub fn mint(
         });
 
         if wl_mint_settings.mode == WhitelistMintMode::BurnEveryTime {
-            let mut token_found = false;
+            additional_accounts.push(AccountMeta {
+                pubkey: wl_mint_settings.mint,
+                is_signer: false,
+                is_writable: true,
+            });
 
-            match program.rpc().get_account_data(&whitelist_token_account) {
-                Ok(ata_data) => {
-                    if !ata_data.is_empty() {
-                        let account = Account::unpack_unchecked(&ata_data)?;
-
-                        if account.amount > 0 {
-                            additional_accounts.push(AccountMeta {
-                                pubkey: wl_mint_settings.mint,
-                                is_signer: false,
-                                is_writable: true,
-                            });
-
-                            additional_accounts.push(AccountMeta {
-                                pubkey: payer,
-                                is_signer: true,
-                                is_writable: false,
-                            });
-
-                            token_found = true;
-                        }
-                    }
-                }
-                Err(err) => return Err(anyhow!(err)),
-            }
-
-            if !token_found {
-                return Err(anyhow!(MagicHatError::NoWhitelistToken));
-            }
+            additional_accounts.push(AccountMeta {
+                pubkey: payer,
+                is_signer: true,
+                is_writable: false,
+            });
         }
     }
 
@@ -324,7 +1420,7 @@ pub fn mint(
     let master_edition_pda = find_master_edition_pda(&nft_mint.pubkey());
     let (magic_hat_creator_pda, creator_bump) = find_magic_hat_creator_pda(&magic_hat_id);
 
-    let mint_ix = program
+    let mut mint_ix = program
         .request()
         .accounts(nft_accounts::MintNFT {
             magic_hat: magic_hat_id,
@@ -347,25 +1443,57 @@ pub fn mint(
         .args(nft_instruction::MintNft { creator_bump })
         .instructions()?;
 
-    let mut builder = program
-        .request()
-        .instruction(create_mint_account_ix)
-        .instruction(init_mint_ix)
-        .instruction(create_assoc_account_ix)
-        .instruction(mint_to_ix)
-        .signer(&nft_mint)
-        .instruction(mint_ix[0].clone());
+    // remaining accounts of the mint instruction
+    mint_ix[0].accounts.extend(additional_accounts);
 
-    if !additional_accounts.is_empty() {
-        for account in additional_accounts {
-            builder = builder.accounts(account);
+    // creates the payment token account if it does not exist
+    let create_payment_ata_ix = match magic_hat_state.token_mint {
+        Some(token_mint) if options.create_ata => {
+            let token_account = get_associated_token_address(&payer, &token_mint);
+
+            match program
+                .rpc()
+                .get_account_with_commitment(&token_account, CommitmentConfig::confirmed())?
+                .value
+            {
+                Some(_) => None,
+                None => Some(create_associated_token_account(&payer, &payer, &token_mint)),
+            }
         }
+        _ => None,
+    };
+
+    let mut instructions = Vec::new();
+
+    // compute budget instructions go first so the mint and set collection
+    // instructions remain adjacent in the transaction
+    if let Some(compute_units) = options.compute_units {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_units,
+        ));
+    }
+
+    if let Some(priority_fee) = options.priority_fee {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+            priority_fee,
+        ));
+    }
+
+    if let Some(create_payment_ata_ix) = create_payment_ata_ix {
+        instructions.push(create_payment_ata_ix);
     }
 
+    instructions.push(create_mint_account_ix);
+    instructions.push(init_mint_ix);
+    instructions.push(create_assoc_account_ix);
+    instructions.push(mint_to_ix);
+    instructions.push(mint_ix.remove(0));
+
     if let Some((collection_pda_pubkey, collection_pda)) = collection_pda_info.as_ref() {
         let collection_authority_record =
             find_collection_authority_account(&collection_pda.mint, collection_pda_pubkey).0;
-        builder = builder
+        let mut set_collection_ix = program
+            .request()
             .accounts(nft_accounts::SetCollectionDuringMint {
                 magic_hat: magic_hat_id,
                 metadata: metadata_pda,
@@ -379,27 +1507,252 @@ pub fn mint(
                 authority: payer,
                 collection_authority_record,
             })
-            .args(nft_instruction::SetCollectionDuringMint {});
+            .args(nft_instruction::SetCollectionDuringMint {})
+            .instructions()?;
+
+        instructions.push(set_collection_ix.remove(0));
     }
 
-    let sig = builder.send()?;
+    Ok(MintTransaction {
+        instructions,
+        nft_mint,
+        token_account: assoc,
+        metadata: metadata_pda,
+    })
+}
 
-    if let Err(_) | Ok(Response { value: None, .. }) = program
-        .rpc()
-        .get_account_with_commitment(&metadata_pda, CommitmentConfig::processed())
-    {
+pub fn mint(
+    client: Arc<Client>,
+    magic_hat_id: Pubkey,
+    magic_hat_state: Arc<MagicHat>,
+    collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    options: &MintOptions,
+) -> Result<MintRecord> {
+    let program = client.program(MAGIC_HAT_ID);
+
+    check_mint_conditions(&program, &magic_hat_state, options)?;
+
+    let mut transaction = build_mint_transaction(
+        &program,
+        magic_hat_id,
+        &magic_hat_state,
+        &collection_pda_info,
+        options,
+    )?;
+
+    // compute unit limit placed in the transaction, used to compute the fee
+    let mut compute_units = options.compute_units;
+
+    if collection_pda_info.is_some() {
+        check_transaction_size(&program, &transaction)?;
+
+        // setting the collection during mint might exceed the default compute
+        // budget, so an explicit limit is requested (unless specified)
+        if options.compute_units.is_none() {
+            if let Some(units) = estimate_compute_units(&program, &transaction)? {
+                debug!("Requesting compute unit limit of {}", units);
+                transaction
+                    .instructions
+                    .insert(0, ComputeBudgetInstruction::set_compute_unit_limit(units));
+                compute_units = Some(units);
+            }
+        }
+    }
+
+    let mut builder = program.request().signer(&transaction.nft_mint);
+
+    for instruction in transaction.instructions.iter().cloned() {
+        builder = builder.instruction(instruction);
+    }
+
+    let sig = builder.send().map_err(decode_program_error)?;
+
+    if !wait_for_metadata(&program, &sig, &transaction.metadata)? {
         let cluster_param = match get_cluster(program.rpc()).unwrap_or(Cluster::Mainnet) {
             Cluster::Devnet => "?devnet",
             Cluster::Mainnet => "",
         };
-        return Err(anyhow!(
-            "Minting most likely failed with a bot tax. Check the transaction link for more details: https://explorer.solana.com/tx/{}{}",
+        return Err(MintError::BotTax(
             sig.to_string(),
-            cluster_param,
-        ));
+            format!(
+                "https://explorer.solana.com/tx/{}{}",
+                sig.to_string(),
+                cluster_param
+            ),
+        )
+        .into());
     }
 
     info!("Minted! TxId: {}", sig);
 
-    Ok(sig)
+    Ok(MintRecord {
+        mint: transaction.nft_mint.pubkey().to_string(),
+        token_account: transaction.token_account.to_string(),
+        signature: sig.to_string(),
+        timestamp: Utc::now().timestamp(),
+        magic_hat: magic_hat_id.to_string(),
+        payer: program.payer().to_string(),
+        priority_fee: options.priority_fee_lamports(compute_units),
+    })
+}
+
+/// Return the compute unit limit (with headroom) for the mint transaction
+/// based on the units consumed in a simulation.
+fn estimate_compute_units(program: &Program, transaction: &MintTransaction) -> Result<Option<u32>> {
+    let tx = Transaction::new_with_payer(&transaction.instructions, Some(&program.payer()));
+    let result = program
+        .rpc()
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+
+    Ok(result.units_consumed.map(|units| {
+        let units = units + units * COMPUTE_UNITS_HEADROOM / 100;
+        cmp::min(units, MAX_COMPUTE_UNITS as u64) as u32
+    }))
+}
+
+/// Check that the mint transaction (including the compute budget instruction
+/// that might be added) fits in a packet.
+fn check_transaction_size(program: &Program, transaction: &MintTransaction) -> Result<()> {
+    let mut instructions = transaction.instructions.clone();
+    instructions.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(0));
+
+    let tx = Transaction::new_with_payer(&instructions, Some(&program.payer()));
+    // signatures (compact array) + message
+    let size =
+        1 + tx.message.header.num_required_signatures as usize * 64 + tx.message.serialize().len();
+
+    if size > PACKET_DATA_SIZE {
+        // the magic hat program requires the set collection instruction to
+        // follow the mint instruction in the same transaction, so it cannot be
+        // sent separately
+        return Err(anyhow!(
+            "Mint transaction size of {} bytes exceeds the limit of {} bytes; try \
+             removing the priority fee or compute unit options (or use --no-collection \
+             to debug without the set collection instruction)",
+            size,
+            PACKET_DATA_SIZE
+        ));
+    }
+
+    Ok(())
+}
+
+/// Confirm the mint transaction and wait (with a bounded backoff) for the
+/// metadata account to be created. Returns `false` if the transaction was
+/// confirmed but the metadata account does not exist.
+fn wait_for_metadata(program: &Program, signature: &Signature, metadata: &Pubkey) -> Result<bool> {
+    let rpc = program.rpc();
+    let commitment = rpc.commitment();
+    let start = Instant::now();
+    let timeout = Duration::from_secs(METADATA_POLL_TIMEOUT_SECS);
+    let mut delay = Duration::from_millis(METADATA_POLL_INITIAL_MS);
+
+    // waits for the transaction to be confirmed
+    loop {
+        match rpc.get_signature_status_with_commitment(signature, commitment)? {
+            Some(Ok(())) => break,
+            Some(Err(err)) => {
+                return Err(anyhow!("Mint transaction {} failed: {}", signature, err));
+            }
+            None => {
+                if start.elapsed() >= timeout {
+                    return Err(anyhow!(
+                        "Mint transaction {} could not be confirmed",
+                        signature
+                    ));
+                }
+            }
+        }
+
+        thread::sleep(delay);
+        delay = cmp::min(delay * 2, Duration::from_secs(METADATA_POLL_MAX_DELAY_SECS));
+    }
+
+    delay = Duration::from_millis(METADATA_POLL_INITIAL_MS);
+
+    // the transaction is confirmed, polls for the metadata account
+    loop {
+        if let Ok(Response { value: Some(_), .. }) =
+            rpc.get_account_with_commitment(metadata, commitment)
+        {
+            return Ok(true);
+        }
+
+        if start.elapsed() >= timeout {
+            return Ok(false);
+        }
+
+        thread::sleep(delay);
+        delay = cmp::min(delay * 2, Duration::from_secs(METADATA_POLL_MAX_DELAY_SECS));
+    }
+}
+
+/// Simulate a mint transaction without sending it, printing the program logs
+/// and any mint condition that would fail.
+pub fn simulate_mint(
+    client: Arc<Client>,
+    magic_hat_id: Pubkey,
+    magic_hat_state: Arc<MagicHat>,
+    collection_pda_info: Arc<Option<PdaInfo<CollectionPDA>>>,
+    options: &MintOptions,
+) -> Result<()> {
+    let program = client.program(MAGIC_HAT_ID);
+
+    let precondition = check_mint_conditions(&program, &magic_hat_state, options);
+
+    let transaction = build_mint_transaction(
+        &program,
+        magic_hat_id,
+        &magic_hat_state,
+        &collection_pda_info,
+        options,
+    )?;
+
+    let tx = Transaction::new_with_payer(&transaction.instructions, Some(&program.payer()));
+    let result = program
+        .rpc()
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+
+    println!("\n{}", style("Program logs:").bold());
+    for log in result.logs.unwrap_or_default() {
+        println!("  {}", log);
+    }
+
+    if let Some(units) = result.units_consumed {
+        println!("\n{} {}", style("Compute units consumed:").bold(), units);
+    }
+
+    if let Err(err) = &precondition {
+        println!(
+            "\n{} {}",
+            style("Mint precondition failed:").red().bold(),
+            err
+        );
+    }
+
+    match result.err {
+        Some(err) => Err(anyhow!("Mint simulation failed: {}", err)),
+        None => {
+            if precondition.is_ok() {
+                println!("\n{}", style("Mint simulation succeeded").green().bold());
+            }
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,303 @@
+use std::{fs, str::FromStr, thread, time::Duration};
+
+use anchor_client::solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+};
+use anyhow::Result;
+use console::style;
+use mpl_token_metadata::deser::meta_deser;
+use serde::Serialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+
+use crate::cache::load_cache;
+use crate::cli::OutputFormat;
+use crate::common::*;
+use crate::magic_hat::MAGIC_HAT_ID;
+use crate::pdas::{find_magic_hat_creator_pda, find_metadata_pda};
+use crate::utils::*;
+
+/// Offset of the mint address in a metadata account (key + update authority).
+const METADATA_MINT_OFFSET: usize = 1 + 32;
+
+/// Offset of the first creator address in a metadata account.
+const METADATA_FIRST_CREATOR_OFFSET: usize = 1 +    // key
+    32 +                                            // update authority
+    32 +                                            // mint
+    4 + MAX_NAME_LENGTH +                           // u32 len + name
+    4 + MAX_SYMBOL_LENGTH +                         // u32 len + symbol
+    4 + MAX_URI_LENGTH +                            // u32 len + uri
+    2 +                                             // seller fee basis points
+    1 +                                             // option for creators
+    4; // u32 len of creators vec
+
+/// Offset of the `verified` flag of the first creator in a metadata account.
+const METADATA_FIRST_CREATOR_VERIFIED_OFFSET: usize = METADATA_FIRST_CREATOR_OFFSET + 32;
+
+/// Maximum number of accounts fetched by a single `getMultipleAccounts` request.
+const MULTIPLE_ACCOUNTS_PAGE_SIZE: usize = 100;
+
+/// Number of retries of a failed RPC request.
+const RPC_RETRIES: u32 = 3;
+
+/// Delay (doubled on each attempt) between retries of a failed RPC request.
+const RPC_RETRY_DELAY_MS: u64 = 500;
+
+pub struct MintListArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub magic_hat: Option<String>,
+    pub metadata: bool,
+    pub out: Option<String>,
+    pub output: OutputFormat,
+}
+
+/// Entry of the mint list; name and URI are only present with `--metadata`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintListItem {
+    pub mint: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+}
+
+pub fn process_mint_list(args: MintListArgs) -> Result<()> {
+    // the list is the only thing printed to stdout when it is not written to a file
+    let to_stdout = args.out.is_none();
+
+    // the magic hat id specified takes precedence over the one from the cache
+
+    let magic_hat_id = match args.magic_hat {
+        Some(magic_hat) => magic_hat,
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.magic_hat
+        }
+    };
+
+    let magic_hat_id = match Pubkey::from_str(&magic_hat_id) {
+        Ok(magic_hat_id) => magic_hat_id,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat_id);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&laddu_config)?;
+    let program = client.program(MAGIC_HAT_ID);
+
+    if !to_stdout {
+        println!(
+            "{} {}Looking up minted NFTs",
+            style(if args.metadata { "[1/2]" } else { "[1/1]" })
+                .bold()
+                .dim(),
+            LOOKING_GLASS_EMOJI
+        );
+    }
+
+    let pb = spinner_with_style();
+    pb.set_message("Querying metadata accounts...");
+
+    // every NFT minted by the magic hat has the creator PDA as its first
+    // verified creator
+    let (creator, _) = find_magic_hat_creator_pda(&magic_hat_id);
+    let metadata_program = Pubkey::from_str(METAPLEX_PROGRAM_ID)?;
+
+    // large collections can exceed what the RPC returns in a single response,
+    // in which case the query is split by the first byte of the mint address
+    let accounts = match with_retry(|| {
+        program
+            .rpc()
+            .get_program_accounts_with_config(&metadata_program, mint_list_config(&creator, None))
+    }) {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            warn!(
+                "Failed to query all metadata accounts at once, querying in batches: {}",
+                err
+            );
+            pb.set_message("Querying metadata accounts in batches...");
+
+            let mut accounts = Vec::new();
+            for first_byte in 0..=u8::MAX {
+                accounts.extend(with_retry(|| {
+                    program.rpc().get_program_accounts_with_config(
+                        &metadata_program,
+                        mint_list_config(&creator, Some(first_byte)),
+                    )
+                })?);
+            }
+            accounts
+        }
+    };
+
+    let mut mints = Vec::with_capacity(accounts.len());
+
+    for (pubkey, account) in accounts {
+        match Pubkey::try_from(account.data.as_slice()) {
+            Ok(mint) => mints.push(mint),
+            Err(_) => warn!("Failed to decode mint of metadata account {}", pubkey),
+        }
+    }
+    // sorted to produce a stable list
+    mints.sort_by_key(|mint| mint.to_string());
+
+    pb.finish_and_clear();
+
+    let mut items: Vec<MintListItem> = mints
+        .iter()
+        .map(|mint| MintListItem {
+            mint: mint.to_string(),
+            name: None,
+            uri: None,
+        })
+        .collect();
+
+    if args.metadata {
+        if !to_stdout {
+            println!(
+                "\n{} {}Fetching metadata",
+                style("[2/2]").bold().dim(),
+                PAPER_EMOJI
+            );
+        }
+
+        let pb = progress_bar_with_style(items.len() as u64);
+
+        for (chunk, page) in mints
+            .chunks(MULTIPLE_ACCOUNTS_PAGE_SIZE)
+            .zip(items.chunks_mut(MULTIPLE_ACCOUNTS_PAGE_SIZE))
+        {
+            let pubkeys: Vec<Pubkey> = chunk.iter().map(find_metadata_pda).collect();
+            let accounts = with_retry(|| program.rpc().get_multiple_accounts(&pubkeys))?;
+
+            for (item, account) in page.iter_mut().zip(accounts) {
+                if let Some(account) = account {
+                    match meta_deser(&mut account.data.as_slice()) {
+                        Ok(metadata) => {
+                            item.name =
+                                Some(metadata.data.name.trim_matches(char::from(0)).to_string());
+                            item.uri =
+                                Some(metadata.data.uri.trim_matches(char::from(0)).to_string());
+                        }
+                        Err(_) => warn!("Failed to deserialize metadata of mint {}", item.mint),
+                    }
+                }
+            }
+
+            pb.inc(chunk.len() as u64);
+        }
+
+        pb.finish_and_clear();
+    }
+
+    let contents = match args.output {
+        OutputFormat::Json => {
+            if args.metadata {
+                serde_json::to_string_pretty(&items)?
+            } else {
+                // the hashlist format expected by marketplaces
+                serde_json::to_string_pretty(
+                    &mints.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+                )?
+            }
+        }
+        OutputFormat::Text => items
+            .iter()
+            .map(|item| match (&item.name, &item.uri) {
+                (Some(name), Some(uri)) => format!("{} {} {}", item.mint, name, uri),
+                _ => item.mint.clone(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+    };
+
+    match args.out {
+        Some(out) => {
+            fs::write(&out, contents + "\n")?;
+            println!(
+                "\n{} NFT(s) found, list written to {}",
+                style(items.len()).bold(),
+                style(&out).bold()
+            );
+        }
+        None => println!("{}", contents),
+    }
+
+    Ok(())
+}
+
+/// Query of the metadata accounts which first verified creator is the magic hat
+/// creator PDA, optionally restricted to mints starting with `first_byte`.
+///
+/// Only the mint address is requested to keep the response small for large
+/// collections; metadata is fetched afterwards in pages.
+fn mint_list_config(creator: &Pubkey, first_byte: Option<u8>) -> RpcProgramAccountsConfig {
+    let mut filters = vec![
+        RpcFilterType::Memcmp(Memcmp {
+            offset: METADATA_FIRST_CREATOR_OFFSET,
+            bytes: MemcmpEncodedBytes::Base58(creator.to_string()),
+            encoding: None,
+        }),
+        // anyone can list the creator PDA as an unverified creator
+        RpcFilterType::Memcmp(Memcmp {
+            offset: METADATA_FIRST_CREATOR_VERIFIED_OFFSET,
+            bytes: MemcmpEncodedBytes::Base58(bs58::encode([1u8]).into_string()),
+            encoding: None,
+        }),
+    ];
+
+    if let Some(first_byte) = first_byte {
+        filters.push(RpcFilterType::Memcmp(Memcmp {
+            offset: METADATA_MINT_OFFSET,
+            bytes: MemcmpEncodedBytes::Base58(bs58::encode([first_byte]).into_string()),
+            encoding: None,
+        }));
+    }
+
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: METADATA_MINT_OFFSET,
+                length: 32,
+            }),
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+        },
+        with_context: None,
+    }
+}
+
+/// Run an RPC request, retrying it with an exponential backoff when it fails.
+fn with_retry<T, F>(request: F) -> Result<T>
+where
+    F: Fn() -> ClientResult<T>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < RPC_RETRIES => {
+                warn!("RPC request failed (attempt {}): {}", attempt + 1, err);
+                thread::sleep(Duration::from_millis(RPC_RETRY_DELAY_MS << attempt));
+                attempt += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
@@ -6,7 +6,9 @@ use mpl_token_metadata::deser::meta_deser;
 use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
 use mpl_token_metadata::state::{Key, MasterEditionV2, Metadata, MAX_MASTER_EDITION_LEN};
 use mpl_token_metadata::utils::try_from_slice_checked;
+use std::str::FromStr;
 
+use crate::constants::CIVIC;
 use crate::magic_hat::MAGIC_HAT_ID;
 
 pub type PdaInfo<T> = (Pubkey, T);
@@ -84,6 +86,27 @@ pub fn find_collection_pda(magic_hat_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(collection_seeds, &MAGIC_HAT_ID)
 }
 
+pub fn find_gateway_token_pda(wallet: &Pubkey, gatekeeper_network: &Pubkey) -> Pubkey {
+    // Derive gateway token address (using the default seed)
+    let gateway_seeds = &[
+        wallet.as_ref(),
+        "gateway".as_bytes(),
+        &[0u8; 8],
+        gatekeeper_network.as_ref(),
+    ];
+    let civic_program_id = Pubkey::from_str(CIVIC).expect("Invalid Civic program id");
+
+    Pubkey::find_program_address(gateway_seeds, &civic_program_id).0
+}
+
+pub fn find_gateway_expire_pda(gatekeeper_network: &Pubkey) -> Pubkey {
+    // Derive network expire feature address
+    let expire_seeds = &[gatekeeper_network.as_ref(), "expire".as_bytes()];
+    let civic_program_id = Pubkey::from_str(CIVIC).expect("Invalid Civic program id");
+
+    Pubkey::find_program_address(expire_seeds, &civic_program_id).0
+}
+
 pub fn get_collection_pda(magic_hat: &Pubkey, program: &Program) -> Result<PdaInfo<CollectionPDA>> {
     let collection_pda_pubkey = find_collection_pda(magic_hat).0;
     program
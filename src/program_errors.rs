@@ -0,0 +1,208 @@
+use anchor_client::{
+    solana_sdk::{instruction::InstructionError, transaction::TransactionError},
+    ClientError,
+};
+use anyhow::anyhow;
+use magic_hat::MagicHatError::{self, *};
+use mpl_token_metadata::error::MetadataError;
+use num_traits::FromPrimitive;
+use solana_client::{
+    client_error::{ClientError as RpcClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use tracing::error;
+
+use crate::constants::METAPLEX_PROGRAM_ID;
+use crate::magic_hat::MAGIC_HAT_ID;
+
+/// Offset of the custom (anchor) error codes.
+const ANCHOR_ERROR_OFFSET: u32 = 6000;
+
+/// Magic Hat program errors; their codes, names and descriptions are taken from
+/// the program crate so they cannot drift from the deployed program.
+const MAGIC_HAT_ERRORS: &[MagicHatError] = &[
+    IncorrectOwner,
+    Uninitialized,
+    MintMismatch,
+    IndexGreaterThanLength,
+    NumericalOverflowError,
+    TooManyCreators,
+    UuidMustBeExactly6Length,
+    NotEnoughTokens,
+    NotEnoughSOL,
+    TokenTransferFailed,
+    MagicHatEmpty,
+    MagicHatNotLive,
+    HiddenSettingsConfigsDoNotHaveConfigLines,
+    CannotChangeNumberOfLines,
+    DerivedKeyInvalid,
+    PublicKeyMismatch,
+    NoWhitelistToken,
+    TokenBurnFailed,
+    GatewayAppMissing,
+    GatewayTokenMissing,
+    GatewayTokenExpireTimeInvalid,
+    NetworkExpireFeatureMissing,
+    CannotFindUsableConfigLine,
+    InvalidString,
+    SuspiciousTransaction,
+    CannotSwitchToHiddenSettings,
+    IncorrectSlotHashesPubkey,
+    IncorrectCollectionAuthority,
+    MismatchedCollectionPDA,
+    MismatchedCollectionMint,
+    SlotHashesEmpty,
+    MetadataAccountMustBeEmpty,
+    MissingSetCollectionDuringMint,
+    NoChangingCollectionDuringMint,
+    MagicHatCollectionRequiresRetainAuthority,
+];
+
+/// Add a human-readable description of a custom program error (if any) to the
+/// client error. The original error can still be retrieved with `downcast_ref`.
+pub fn decode_program_error(err: ClientError) -> anyhow::Error {
+    let description = match &err {
+        ClientError::SolanaClientError(rpc_error) => describe_rpc_error(rpc_error),
+        _ => None,
+    };
+
+    match description {
+        Some(description) => {
+            let message = format!("{}\n{}", err, description);
+            anyhow!(err).context(message)
+        }
+        None => anyhow!(err),
+    }
+}
+
+/// Describe the custom program error of a failed transaction.
+fn describe_rpc_error(rpc_error: &RpcClientError) -> Option<String> {
+    let transaction_error = rpc_error.get_transaction_error()?;
+
+    let logs = match rpc_error.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    describe_transaction_error(&transaction_error, &logs)
+}
+
+/// Describe the custom program error of a failed (or simulated) transaction
+/// given its program logs.
+pub fn describe_transaction_error(
+    transaction_error: &TransactionError,
+    logs: &[String],
+) -> Option<String> {
+    let code = match transaction_error {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => *code,
+        _ => return None,
+    };
+
+    for log in logs {
+        error!("{}", log);
+    }
+
+    let mut description = match lookup_error(failed_program(logs), code) {
+        Some((program, name, message)) => {
+            format!("{} error {} (0x{:x}): {}", program, name, code, message)
+        }
+        None => format!(
+            "Unknown program error 0x{:x}; check the program logs in laddu.log for details",
+            code
+        ),
+    };
+
+    if !logs.is_empty() {
+        description.push_str("\nProgram logs:");
+        for log in logs {
+            description.push_str("\n  ");
+            description.push_str(log);
+        }
+    }
+
+    Some(description)
+}
+
+/// Return the id of the program that failed according to the logs.
+fn failed_program(logs: &[String]) -> Option<String> {
+    logs.iter().rev().find_map(|log| {
+        log.strip_prefix("Program ")
+            .filter(|rest| rest.contains(" failed"))
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(|program| program.to_string())
+    })
+}
+
+/// Look up the name and description of a custom error code, returning the
+/// program name, error name and description.
+fn lookup_error(program: Option<String>, code: u32) -> Option<(String, String, String)> {
+    let magic_hat_id = MAGIC_HAT_ID.to_string();
+
+    let is_magic_hat = match &program {
+        Some(program) => *program == magic_hat_id,
+        // without logs, anchor error codes are assumed to be from magic hat
+        None => code >= ANCHOR_ERROR_OFFSET,
+    };
+
+    if is_magic_hat {
+        return MAGIC_HAT_ERRORS
+            .iter()
+            .find(|err| u32::from(**err) == code)
+            .map(|err| {
+                (
+                    "Magic Hat".to_string(),
+                    format!("{:?}", err),
+                    err.to_string(),
+                )
+            });
+    }
+
+    if program.as_deref() == Some(METAPLEX_PROGRAM_ID) || program.is_none() {
+        return MetadataError::from_u32(code).map(|err| {
+            (
+                "Token Metadata".to_string(),
+                format!("{:?}", err),
+                err.to_string(),
+            )
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_magic_hat_errors() {
+        let code = u32::from(MagicHatEmpty);
+        let (program, name, message) = lookup_error(None, code).unwrap();
+
+        assert_eq!(program, "Magic Hat");
+        assert_eq!(name, "MagicHatEmpty");
+        assert_eq!(message, MagicHatEmpty.to_string());
+
+        let (_, name, _) =
+            lookup_error(Some(MAGIC_HAT_ID.to_string()), u32::from(MagicHatNotLive)).unwrap();
+        assert_eq!(name, "MagicHatNotLive");
+    }
+
+    #[test]
+    fn magic_hat_error_codes_are_unique() {
+        let mut codes: Vec<u32> = MAGIC_HAT_ERRORS.iter().map(|err| u32::from(*err)).collect();
+        codes.sort_unstable();
+        codes.dedup();
+
+        assert_eq!(codes.len(), MAGIC_HAT_ERRORS.len());
+        assert!(codes.iter().all(|code| *code >= ANCHOR_ERROR_OFFSET));
+    }
+
+    #[test]
+    fn unknown_codes_are_not_described() {
+        assert!(lookup_error(Some(MAGIC_HAT_ID.to_string()), u32::MAX).is_none());
+    }
+}
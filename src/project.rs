@@ -0,0 +1,136 @@
+use clap::{ArgMatches, Command, ValueSource};
+use console::style;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::constants::DEFAULT_CONFIG;
+
+/// Arguments which default values are resolved relative to the project root.
+const PROJECT_ARGS: &[&str] = &["assets_dir", "cache", "config", "out"];
+
+/// Project root of the command, used to resolve the default paths (cache, config
+/// and assets) when laddu is run from a subdirectory of the project.
+pub struct ProjectPaths {
+    root: Option<PathBuf>,
+    defaults: Vec<String>,
+}
+
+impl ProjectPaths {
+    /// Discovers the project root from the current directory and records which
+    /// arguments of the (sub)command were left to their default value. With
+    /// `no_discovery`, all paths are relative to the current directory.
+    pub fn discover(command: &Command, matches: &ArgMatches, no_discovery: bool) -> Self {
+        let current_dir = env::current_dir().ok();
+        // the current directory is the default behaviour, there is nothing to resolve
+        let root = match (no_discovery, &current_dir) {
+            (false, Some(current_dir)) => {
+                find_project_root(current_dir).filter(|root| root != current_dir)
+            }
+            _ => None,
+        };
+
+        // default values are only known on the matches of the subcommand being run
+        let (command, matches) = leaf_command(command, matches);
+
+        let defaults = command
+            .get_arguments()
+            .map(|arg| arg.get_id())
+            .filter(|id| PROJECT_ARGS.contains(id))
+            .filter(|id| matches.value_source(id) == Some(ValueSource::DefaultValue))
+            .map(String::from)
+            .collect();
+
+        if let Some(root) = &root {
+            println!(
+                "{} {}",
+                style("Project root:").bold(),
+                style(root.display()).dim()
+            );
+        }
+
+        ProjectPaths { root, defaults }
+    }
+
+    /// Resolves the value of an argument: a default value is made relative to the
+    /// project root, an explicit value is kept as it is.
+    pub fn resolve(&self, arg: &str, value: String) -> String {
+        match &self.root {
+            Some(root) if self.is_default(arg) => root.join(value).to_string_lossy().to_string(),
+            _ => value,
+        }
+    }
+
+    /// Whether the argument was left to its default value.
+    pub fn is_default(&self, arg: &str) -> bool {
+        self.defaults.iter().any(|default| default == arg)
+    }
+
+    /// Resolves an optional config path: when not specified, the config file of the
+    /// project root is used.
+    pub fn resolve_config(&self, value: Option<String>) -> Option<String> {
+        match (value, &self.root) {
+            (None, Some(root)) => Some(root.join(DEFAULT_CONFIG).to_string_lossy().to_string()),
+            (value, _) => value,
+        }
+    }
+}
+
+/// Subcommand being run (e.g. `cache import`) and its matches.
+pub fn leaf_command<'a>(
+    command: &'a Command<'a>,
+    matches: &'a ArgMatches,
+) -> (&'a Command<'a>, &'a ArgMatches) {
+    let mut command = command;
+    let mut matches = matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        match command.find_subcommand(name) {
+            Some(sub_command) => {
+                command = sub_command;
+                matches = sub_matches;
+            }
+            None => break,
+        }
+    }
+    (command, matches)
+}
+
+/// Arguments of the subcommand which values were read from an environment
+/// variable, with the name of the variable.
+pub fn env_arguments<'a>(
+    command: &'a Command<'a>,
+    matches: &'a ArgMatches,
+) -> Vec<(String, String)> {
+    let (command, matches) = leaf_command(command, matches);
+
+    command
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id()) == Some(ValueSource::EnvVariable))
+        .filter_map(|arg| {
+            let env = arg.get_env()?.to_string_lossy().to_string();
+            Some((arg.get_id().to_string(), env))
+        })
+        .collect()
+}
+
+/// Value of an argument of the subcommand, `None` when the subcommand does not
+/// have this argument or it was not specified.
+pub fn argument_value(command: &Command, matches: &ArgMatches, id: &str) -> Option<String> {
+    let (command, matches) = leaf_command(command, matches);
+
+    if command.get_arguments().any(|arg| arg.get_id() == id) {
+        matches.value_of(id).map(String::from)
+    } else {
+        None
+    }
+}
+
+/// Walks up from `start` looking for a directory with a config file, similarly to
+/// how git finds the `.git` directory.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(DEFAULT_CONFIG).is_file())
+        .map(Path::to_path_buf)
+}
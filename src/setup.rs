@@ -6,13 +6,60 @@ use anchor_client::{
     Client, Cluster,
 };
 use anyhow::{anyhow, Result};
-use std::rc::Rc;
+use console::style;
+use std::{
+    path::Path,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
 use tracing::error;
 
-use crate::config::data::LadduConfig;
+use crate::config::{data::LadduConfig, parser::get_config_data};
 use crate::constants::{DEFAULT_KEYPATH, DEFAULT_RPC_DEVNET};
 use crate::parse::*;
 
+/// Cluster of the config file (name and RPC url), used when no RPC url is specified.
+static CONFIG_CLUSTER: Mutex<Option<(String, String)>> = Mutex::new(None);
+
+/// Whether the cluster of the config was printed, so it is printed once.
+static CLUSTER_PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the cluster of the config file of the command. A config that cannot be
+/// loaded is reported by the command itself, but an unknown cluster fails here so
+/// that the commands do not silently fall back to another cluster.
+pub fn configure_cluster(config_path: Option<&str>) -> Result<()> {
+    let config_data = match config_path.filter(|path| Path::new(path).is_file()) {
+        Some(path) => get_config_data(path).ok(),
+        None => None,
+    };
+
+    let cluster = match config_data {
+        Some(config_data) => match config_data.cluster_rpc_url() {
+            Ok(rpc_url) => config_data.cluster.zip(rpc_url),
+            Err(err) => {
+                let error = anyhow!(
+                    "{}: expected mainnet-beta, devnet, localnet or a name of rpcEndpoints",
+                    err
+                );
+                error!("{:?}", error);
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+
+    *CONFIG_CLUSTER.lock().unwrap() = cluster;
+    Ok(())
+}
+
+/// Cluster of the config file (name and RPC url), when it has one.
+pub fn config_cluster() -> Option<(String, String)> {
+    CONFIG_CLUSTER.lock().unwrap().clone()
+}
+
 pub fn setup_client(laddu_config: &LadduConfig) -> Result<Client> {
     let rpc_url = laddu_config.rpc_url.clone();
     let ws_url = rpc_url.replace("http", "ws");
@@ -25,19 +72,37 @@ pub fn setup_client(laddu_config: &LadduConfig) -> Result<Client> {
     Ok(Client::new_with_options(cluster, signer, opts))
 }
 
+/// RPC url of a command: the one specified, the one of the config cluster, or the
+/// one of the Solana config and devnet otherwise.
+pub fn resolve_rpc_url(rpc_url_opt: Option<String>) -> String {
+    if let Some(rpc_url) = rpc_url_opt {
+        return rpc_url;
+    }
+
+    if let Some((cluster, rpc_url)) = config_cluster() {
+        if !CLUSTER_PRINTED.swap(true, Ordering::SeqCst) {
+            println!(
+                "{} {} {}",
+                style("Cluster:").bold(),
+                cluster,
+                style(format!("({})", rpc_url)).dim()
+            );
+        }
+        return rpc_url;
+    }
+
+    match parse_solana_config() {
+        Some(sol_config) => sol_config.json_rpc_url,
+        None => String::from(DEFAULT_RPC_DEVNET),
+    }
+}
+
 pub fn laddu_setup(
     keypair_opt: Option<String>,
     rpc_url_opt: Option<String>,
 ) -> Result<LadduConfig> {
     let sol_config_option = parse_solana_config();
-
-    let rpc_url = match rpc_url_opt {
-        Some(rpc_url) => rpc_url,
-        None => match sol_config_option {
-            Some(ref sol_config) => sol_config.json_rpc_url.clone(),
-            None => String::from(DEFAULT_RPC_DEVNET),
-        },
-    };
+    let rpc_url = resolve_rpc_url(rpc_url_opt);
 
     let keypair = match keypair_opt {
         Some(keypair_path) => match read_keypair_file(&keypair_path) {
@@ -0,0 +1,380 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use magic_hat::{EndSettingType, MagicHat, WhitelistMintMode};
+use serde::Serialize;
+
+use crate::cache::ItemReceipts;
+use crate::config::Price;
+
+/// Default number of config lines shown per page.
+pub const CONFIG_LINES_PAGE_SIZE: u64 = 50;
+
+/// JSON representation of a magic hat printed by `show --output json`.
+///
+/// Optional settings are always present in the output (as `null` when not
+/// set) so the shape of the object does not depend on the magic hat.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowOutput {
+    pub magic_hat: String,
+    pub authority: String,
+    pub wallet: String,
+    pub collection_mint: Option<String>,
+    pub token_mint: Option<String>,
+    pub max_supply: u64,
+    pub items_redeemed: u64,
+    pub items_available: u64,
+    pub uuid: String,
+    pub price: PriceOutput,
+    pub symbol: String,
+    pub seller_fee_basis_points: u16,
+    pub is_mutable: bool,
+    pub retain_authority: bool,
+    pub go_live_date: Option<Timestamp>,
+    pub creators: Vec<CreatorOutput>,
+    pub end_settings: Option<EndSettingsOutput>,
+    pub hidden_settings: Option<HiddenSettingsOutput>,
+    pub whitelist_mint_settings: Option<WhitelistMintSettingsOutput>,
+    pub gatekeeper: Option<GatekeeperOutput>,
+    pub rent: RentOutput,
+}
+
+/// Rent held by the magic hat and collection PDA accounts.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RentOutput {
+    pub magic_hat: AccountRent,
+    pub collection_pda: Option<AccountRent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountRent {
+    pub address: String,
+    pub data_length: usize,
+    pub lamports: Amount,
+    pub rent_exempt_minimum: Amount,
+}
+
+/// Amount in lamports, also formatted in SOL.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Amount {
+    pub lamports: u64,
+    pub sol: String,
+}
+
+/// Price charged by the magic hat, either in SOL or in the SPL token set as the
+/// magic hat `token_mint`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceOutput {
+    pub token_mint: Option<String>,
+    /// Raw amount (lamports or token base units).
+    pub amount: u64,
+    /// Amount in SOL or tokens.
+    pub ui_amount: String,
+}
+
+/// Timestamp as unix time, also formatted as RFC3339.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timestamp {
+    pub unix: i64,
+    pub rfc3339: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorOutput {
+    pub address: String,
+    pub share: u8,
+    pub verified: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndSettingsOutput {
+    /// Either `date` or `amount`.
+    pub end_setting_type: String,
+    pub number: u64,
+    /// Only present for `date` end settings.
+    pub date: Option<Timestamp>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiddenSettingsOutput {
+    pub name: String,
+    pub uri: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WhitelistMintSettingsOutput {
+    /// Either `burnEveryTime` or `neverBurn`.
+    pub mode: String,
+    pub mint: String,
+    pub presale: bool,
+    pub discount_price: Option<PriceOutput>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatekeeperOutput {
+    pub gatekeeper_network: String,
+    pub expire_on_use: bool,
+}
+
+/// Availability summary printed by `show --unminted --output json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmintedOutput {
+    pub magic_hat: String,
+    pub items_available: u64,
+    pub items_redeemed: u64,
+    pub items_remaining: u64,
+    pub percent_minted: f64,
+    pub unminted: Vec<u64>,
+}
+
+/// Balances printed by `show --balances --output json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancesOutput {
+    pub magic_hat: String,
+    pub payer: AccountBalance,
+    pub treasury: TreasuryBalance,
+    /// Not present when the bundlr balance could not be retrieved.
+    pub bundlr: Option<AccountBalance>,
+    pub magic_hat_rent: AccountBalance,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBalance {
+    pub address: String,
+    pub balance: Amount,
+}
+
+/// Balance of the treasury wallet, either in SOL or in the SPL token set as
+/// the magic hat `token_mint`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreasuryBalance {
+    pub address: String,
+    pub token_mint: Option<String>,
+    /// Raw amount (lamports or token base units).
+    pub amount: u64,
+    /// Amount in SOL or tokens.
+    pub ui_amount: String,
+}
+
+/// Summary of the local cache printed by `show --cache-summary`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSummaryOutput {
+    pub cache: String,
+    pub magic_hat: Option<String>,
+    pub total_items: usize,
+    pub image_links: usize,
+    pub metadata_links: usize,
+    pub on_chain: usize,
+    /// Number of items from the config file (if it could be read).
+    pub config_number: Option<u64>,
+    /// First and last indices missing from the cache, without links or not on chain.
+    pub first_missing: Option<u64>,
+    pub last_missing: Option<u64>,
+    /// Whether every item of the config is in the cache, uploaded and on chain.
+    pub ready: bool,
+    /// Storage receipts of the uploaded items.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub receipts: Vec<ItemReceipts>,
+}
+
+/// Raw account information printed by `show --raw --output json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawAccountOutput {
+    pub address: String,
+    pub owner: String,
+    pub lamports: u64,
+    pub data_length: usize,
+    /// Hex encoded anchor discriminator (first 8 bytes).
+    pub discriminator: String,
+    /// Whether the discriminator matches the `MagicHat` account.
+    pub is_magic_hat: bool,
+    /// Hex encoded first bytes of the account data.
+    pub data: String,
+}
+
+/// Config line printed by `show --config-lines --output json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigLineOutput {
+    pub index: u64,
+    /// Whether the line has been written on chain.
+    pub written: bool,
+    pub name: Option<String>,
+    pub uri: Option<String>,
+}
+
+impl Amount {
+    pub fn new(lamports: u64) -> Self {
+        Amount {
+            lamports,
+            sol: Price::from_lamports(lamports).to_string(),
+        }
+    }
+}
+
+impl PriceOutput {
+    pub fn new(amount: u64, token_mint: Option<Pubkey>, token_decimals: Option<u8>) -> Self {
+        let ui_amount = match token_decimals {
+            Some(decimals) => Price::from_base_units(amount, decimals),
+            None => Price::from_lamports(amount),
+        };
+
+        PriceOutput {
+            token_mint: token_mint.map(|mint| mint.to_string()),
+            amount,
+            ui_amount: ui_amount.to_string(),
+        }
+    }
+}
+
+impl AccountBalance {
+    pub fn new(address: &Pubkey, lamports: u64) -> Self {
+        AccountBalance {
+            address: address.to_string(),
+            balance: Amount::new(lamports),
+        }
+    }
+}
+
+impl Timestamp {
+    pub fn new(unix: i64) -> Self {
+        let date = DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(unix, 0), Utc);
+
+        Timestamp {
+            unix,
+            rfc3339: date.to_rfc3339(),
+        }
+    }
+}
+
+/// Collapse a sorted list of indices into ranges (e.g. `120-431, 500`).
+pub fn format_ranges(indices: &[u64]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = indices.iter().peekable();
+
+    while let Some(&start) = iter.next() {
+        let mut end = start;
+
+        while let Some(&&next) = iter.peek() {
+            if next != end + 1 {
+                break;
+            }
+            end = next;
+            iter.next();
+        }
+
+        if start == end {
+            ranges.push(start.to_string());
+        } else {
+            ranges.push(format!("{}-{}", start, end));
+        }
+    }
+
+    ranges.join(", ")
+}
+
+impl ShowOutput {
+    pub fn new(
+        magic_hat_id: &Pubkey,
+        state: &MagicHat,
+        collection_mint: Option<Pubkey>,
+        rent: RentOutput,
+        token_decimals: Option<u8>,
+    ) -> Result<Self> {
+        let data = &state.data;
+
+        let end_settings =
+            data.end_settings
+                .as_ref()
+                .map(|end_settings| match end_settings.end_setting_type {
+                    EndSettingType::Date => EndSettingsOutput {
+                        end_setting_type: "date".to_string(),
+                        number: end_settings.number,
+                        date: Some(Timestamp::new(end_settings.number as i64)),
+                    },
+                    EndSettingType::Amount => EndSettingsOutput {
+                        end_setting_type: "amount".to_string(),
+                        number: end_settings.number,
+                        date: None,
+                    },
+                });
+
+        let hidden_settings = match data.hidden_settings {
+            Some(ref hidden_settings) => Some(HiddenSettingsOutput {
+                name: hidden_settings.name.clone(),
+                uri: hidden_settings.uri.clone(),
+                hash: String::from_utf8(hidden_settings.hash.to_vec())?,
+            }),
+            None => None,
+        };
+
+        let whitelist_mint_settings =
+            data.whitelist_mint_settings
+                .as_ref()
+                .map(|whitelist_settings| WhitelistMintSettingsOutput {
+                    mode: if whitelist_settings.mode == WhitelistMintMode::BurnEveryTime {
+                        "burnEveryTime".to_string()
+                    } else {
+                        "neverBurn".to_string()
+                    },
+                    mint: whitelist_settings.mint.to_string(),
+                    presale: whitelist_settings.presale,
+                    discount_price: whitelist_settings
+                        .discount_price
+                        .map(|price| PriceOutput::new(price, state.token_mint, token_decimals)),
+                });
+
+        Ok(ShowOutput {
+            magic_hat: magic_hat_id.to_string(),
+            authority: state.authority.to_string(),
+            wallet: state.wallet.to_string(),
+            collection_mint: collection_mint.map(|mint| mint.to_string()),
+            token_mint: state.token_mint.map(|mint| mint.to_string()),
+            max_supply: data.max_supply,
+            items_redeemed: state.items_redeemed,
+            items_available: data.items_available,
+            uuid: data.uuid.clone(),
+            price: PriceOutput::new(data.price, state.token_mint, token_decimals),
+            symbol: data.symbol.clone(),
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            is_mutable: data.is_mutable,
+            retain_authority: data.retain_authority,
+            go_live_date: data.go_live_date.map(Timestamp::new),
+            creators: data
+                .creators
+                .iter()
+                .map(|creator| CreatorOutput {
+                    address: creator.address.to_string(),
+                    share: creator.share,
+                    verified: creator.verified,
+                })
+                .collect(),
+            end_settings,
+            hidden_settings,
+            whitelist_mint_settings,
+            gatekeeper: data.gatekeeper.as_ref().map(|gatekeeper| GatekeeperOutput {
+                gatekeeper_network: gatekeeper.gatekeeper_network.to_string(),
+                expire_on_use: gatekeeper.expire_on_use,
+            }),
+            rent,
+        })
+    }
+}
@@ -1,3 +1,5 @@
+pub mod data;
 pub mod process;
 
+pub use data::*;
 pub use process::*;
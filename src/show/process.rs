@@ -1,30 +1,91 @@
-use std::str::FromStr;
+use std::{
+    cmp,
+    collections::VecDeque,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
 
-use anchor_client::solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use anchor_client::solana_sdk::{
+    account::Account, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signer::Signer,
+};
+use anchor_lang::Discriminator;
 use anyhow::Result;
-use chrono::NaiveDateTime;
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
 use console::style;
-use magic_hat::{EndSettingType, WhitelistMintMode};
+use data_encoding::HEXLOWER;
+use magic_hat::{EndSettingType, MagicHatData, WhitelistMintMode};
+use mpl_token_metadata::pda::find_collection_authority_account;
 
 use crate::cache::load_cache;
+use crate::cli::OutputFormat;
 use crate::common::*;
+use crate::config::{get_config_data, Cluster, LadduConfig, Price};
 use crate::magic_hat::*;
-use crate::pdas::get_collection_pda;
+use crate::pdas::{get_collection_pda, get_master_edition_pda, get_metadata_pda};
+use crate::show::{
+    format_ranges, AccountBalance, AccountRent, Amount, BalancesOutput, CacheSummaryOutput,
+    ConfigLineOutput, RawAccountOutput, RentOutput, ShowOutput, TreasuryBalance, UnmintedOutput,
+    CONFIG_LINES_PAGE_SIZE,
+};
+use crate::upload::BundlrHandler;
 use crate::utils::*;
 
+/// Interval (in seconds) between refreshes of the magic hat state in watch mode.
+const WATCH_REFRESH_SECS: i64 = 5;
+
+/// Window (in seconds) sampled to compute the mint rate.
+const STATS_WINDOW_SECS: i64 = 15;
+
+/// Number of refreshes kept to compute the rolling mint rate in watch mode.
+const STATS_SAMPLES: usize = 13;
+
+/// Position of the loaded lines bitmask in the account data.
+pub const LOADED_BITMASK: usize = 0;
+
+/// Position of the minted items bitmask in the account data.
+const MINTED_BITMASK: usize = 1;
+
 pub struct ShowArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
+    pub config: String,
+    pub cache_summary: bool,
     pub magic_hat: Option<String>,
+    pub unminted: bool,
+    pub config_lines: bool,
+    pub balances: bool,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub watch: bool,
+    pub stats: bool,
+    pub raw: bool,
+    pub raw_bytes: usize,
+    pub output: OutputFormat,
+    pub interrupted: Arc<AtomicBool>,
 }
 
-pub fn process_show(args: ShowArgs) -> Result<()> {
-    println!(
-        "{} {}Looking up Magic Hat",
-        style("[1/1]").bold().dim(),
-        LOOKING_GLASS_EMOJI
-    );
+pub async fn process_show(args: ShowArgs) -> Result<()> {
+    // the JSON output is the only thing printed to stdout
+    let json = args.output == OutputFormat::Json;
+
+    // the cache summary is local and does not need a connection
+    if args.cache_summary {
+        return print_cache_summary(&args.cache, &args.config, json);
+    }
+
+    if !json {
+        println!(
+            "{} {}Looking up Magic Hat",
+            style("[1/1]").bold().dim(),
+            LOOKING_GLASS_EMOJI
+        );
+    }
 
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
@@ -51,18 +112,72 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
         }
     };
 
-    let collection_mint =
-        if let Ok((_, collection_pda)) = get_collection_pda(&magic_hat_id, &program) {
-            Some(collection_pda.mint)
-        } else {
-            None
-        };
+    // the raw account is printed before deserializing it, since that is
+    // what fails for unexpected layouts
+    if args.raw {
+        let account = program.rpc().get_account(&magic_hat_id)?;
+        pb.finish_and_clear();
+
+        return print_raw_account(&magic_hat_id, &account, args.raw_bytes, json);
+    }
+
+    let collection_pda = get_collection_pda(&magic_hat_id, &program).ok();
+    let collection_mint = collection_pda
+        .as_ref()
+        .map(|(_, collection_pda)| collection_pda.mint);
 
     let cndy_state = get_magic_hat_state(&laddu_config, &magic_hat_id)?;
-    let cndy_data = cndy_state.data;
+
+    if args.unminted {
+        let data = program.rpc().get_account_data(&magic_hat_id)?;
+        pb.finish_and_clear();
+
+        return print_unminted(&magic_hat_id, &cndy_state, &data, json);
+    }
+
+    if args.balances {
+        let balances = get_balances(&program, &laddu_config, &magic_hat_id, &cndy_state).await?;
+        pb.finish_and_clear();
+
+        return print_balances(balances, json);
+    }
+
+    if args.config_lines {
+        let data = program.rpc().get_account_data(&magic_hat_id)?;
+        pb.finish_and_clear();
+
+        return print_config_lines(&cndy_state, &data, args.from, args.to, json);
+    }
+
+    let rent = RentOutput {
+        magic_hat: get_account_rent(&program, &magic_hat_id)?,
+        collection_pda: match collection_pda {
+            Some((ref collection_pda_pubkey, _)) => {
+                Some(get_account_rent(&program, collection_pda_pubkey)?)
+            }
+            None => None,
+        },
+    };
+
+    // prices are stored in the base units of the payment token
+    let token_decimals = token_decimals(&client, cndy_state.token_mint)?;
 
     pb.finish_and_clear();
 
+    if json {
+        let output = ShowOutput::new(
+            &magic_hat_id,
+            &cndy_state,
+            collection_mint,
+            rent,
+            token_decimals,
+        )?;
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let now = Utc::now().timestamp();
+
     println!(
         "\n{}{} {}",
         MAGICHAT_EMOJI,
@@ -75,9 +190,15 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     println!(" {}", style(":").dim());
     print_with_style("", "authority", cndy_state.authority.to_string());
     print_with_style("", "wallet", cndy_state.wallet.to_string());
-    match collection_mint {
-        Some(collection_mint) => {
-            print_with_style("", "collection mint", collection_mint.to_string())
+    match collection_pda {
+        Some((collection_pda_pubkey, collection_pda)) => {
+            print_with_style("", "collection mint", collection_pda.mint.to_string());
+            print_collection_details(
+                &program,
+                &collection_pda.mint,
+                &collection_pda_pubkey,
+                &cndy_state.authority,
+            );
         }
         None => print_with_style("", "collection mint", "none".to_string()),
     };
@@ -88,49 +209,80 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
         print_with_style("", "spl token", "none".to_string());
     }
 
-    print_with_style("", "max supply", cndy_data.max_supply.to_string());
-    print_with_style("", "items redeemed", cndy_state.items_redeemed.to_string());
-    print_with_style("", "items available", cndy_data.items_available.to_string());
+    print_magic_hat_settings(
+        &cndy_state.data,
+        cndy_state.items_redeemed,
+        now,
+        token_decimals,
+    )?;
 
-    print_with_style("", "uuid", cndy_data.uuid.to_string());
-    print_with_style(
-        "",
-        "price",
-        format!(
-            "◎ {} ({})",
-            cndy_data.price as f64 / LAMPORTS_PER_SOL as f64,
-            cndy_data.price
-        ),
-    );
-    print_with_style("", "symbol", cndy_data.symbol.to_string());
+    // rent
+    print_with_style("", "rent", "".to_string());
+    print_account_rent("magic hat", &rent.magic_hat);
+    if let Some(ref collection_pda) = rent.collection_pda {
+        print_account_rent("collection pda", collection_pda);
+    }
+
+    if args.watch {
+        // ctrl-c stops watching instead of aborting the command
+        args.interrupted.store(false, Ordering::SeqCst);
+        watch_magic_hat(
+            &laddu_config,
+            &magic_hat_id,
+            cndy_state,
+            args.stats,
+            &args.interrupted,
+        );
+        args.interrupted.store(true, Ordering::SeqCst);
+    } else if args.stats {
+        print_mint_stats(&laddu_config, &magic_hat_id, &cndy_state)?;
+    }
+
+    Ok(())
+}
+
+/// Print the magic hat data (settings) in the `show` format. The price is in the
+/// base units of the payment token when `token_decimals` is set, in lamports otherwise.
+pub fn print_magic_hat_settings(
+    data: &MagicHatData,
+    items_redeemed: u64,
+    now: i64,
+    token_decimals: Option<u8>,
+) -> Result<()> {
+    let data = data.clone();
+
+    print_with_style("", "max supply", data.max_supply.to_string());
+    print_with_style("", "items redeemed", items_redeemed.to_string());
+    print_with_style("", "items available", data.items_available.to_string());
+
+    print_with_style("", "uuid", data.uuid.to_string());
+    print_with_style("", "price", format_price(data.price, token_decimals));
+    print_with_style("", "symbol", data.symbol.to_string());
     print_with_style(
         "",
         "seller fee basis points",
         format!(
             "{}% ({})",
-            cndy_data.seller_fee_basis_points / 100,
-            cndy_data.seller_fee_basis_points
+            data.seller_fee_basis_points / 100,
+            data.seller_fee_basis_points
         ),
     );
-    print_with_style("", "is mutable", cndy_data.is_mutable.to_string());
-    print_with_style(
-        "",
-        "retain authority",
-        cndy_data.retain_authority.to_string(),
-    );
-    if let Some(date) = cndy_data.go_live_date {
-        let date = NaiveDateTime::from_timestamp(date, 0);
+    print_with_style("", "is mutable", data.is_mutable.to_string());
+    print_with_style("", "retain authority", data.retain_authority.to_string());
+    if let Some(date) = data.go_live_date {
+        print_with_style("", "go live date", format_utc(date));
+        print_with_style(":   ", "local time", format_local(date));
         print_with_style(
-            "",
-            "go live date",
-            date.format("%a %B %e %Y %H:%M:%S UTC").to_string(),
+            ":   ",
+            "countdown",
+            format_countdown(date, now, "starts", "started"),
         );
     } else {
         print_with_style("", "go live date", "none".to_string());
     }
     print_with_style("", "creators", "".to_string());
 
-    for (index, creator) in cndy_data.creators.into_iter().enumerate() {
+    for (index, creator) in data.creators.into_iter().enumerate() {
         let info = format!(
             "{} ({}%{})",
             creator.address,
@@ -141,16 +293,18 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     }
 
     // end settings
-    if let Some(end_settings) = cndy_data.end_settings {
+    if let Some(end_settings) = data.end_settings {
         print_with_style("", "end settings", "".to_string());
         match end_settings.end_setting_type {
             EndSettingType::Date => {
                 print_with_style(":   ", "end setting type", "date".to_string());
-                let date = NaiveDateTime::from_timestamp(end_settings.number as i64, 0);
+                let date = end_settings.number as i64;
+                print_with_style(":   ", "number", format_utc(date));
+                print_with_style(":   ", "local time", format_local(date));
                 print_with_style(
                     ":   ",
-                    "number",
-                    date.format("%a %B %e %Y %H:%M:%S UTC").to_string(),
+                    "countdown",
+                    format_countdown(date, now, "ends", "ended"),
                 );
             }
             EndSettingType::Amount => {
@@ -163,7 +317,7 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     }
 
     // hidden settings
-    if let Some(hidden_settings) = cndy_data.hidden_settings {
+    if let Some(hidden_settings) = data.hidden_settings {
         print_with_style("", "hidden settings", "".to_string());
         print_with_style(":   ", "name", hidden_settings.name);
         print_with_style(":   ", "uri", hidden_settings.uri);
@@ -177,7 +331,7 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     }
 
     // whitelist mint settings
-    if let Some(whitelist_settings) = cndy_data.whitelist_mint_settings {
+    if let Some(whitelist_settings) = data.whitelist_mint_settings {
         print_with_style("", "whitelist mint settings", "".to_string());
         print_with_style(
             ":   ",
@@ -193,8 +347,9 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
         print_with_style(
             ":   ",
             "discount price",
+            // the discount is charged in the payment token, like the price
             if let Some(value) = whitelist_settings.discount_price {
-                format!("◎ {} ({})", value as f64 / LAMPORTS_PER_SOL as f64, value)
+                format_price(value, token_decimals)
             } else {
                 "none".to_string()
             },
@@ -204,7 +359,7 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     }
 
     // gatekeeper settings
-    if let Some(gatekeeper) = cndy_data.gatekeeper {
+    if let Some(gatekeeper) = data.gatekeeper {
         print_with_style("", "gatekeeper", "".to_string());
         print_with_style(
             "    ",
@@ -223,6 +378,852 @@ pub fn process_show(args: ShowArgs) -> Result<()> {
     Ok(())
 }
 
+/// Formats a price stored on chain as a decimal amount of SOL or of the payment
+/// token, followed by the raw amount.
+pub fn format_price(price: u64, token_decimals: Option<u8>) -> String {
+    match token_decimals {
+        Some(decimals) => format!(
+            "{} ({} base units)",
+            Price::from_base_units(price, decimals),
+            price
+        ),
+        None => format!("◎ {} ({})", Price::from_lamports(price), price),
+    }
+}
+
+/// Print the details of the collection NFT, flagging (in yellow) the settings
+/// that prevent the collection from being set during mint.
+fn print_collection_details(
+    program: &Program,
+    collection_mint: &Pubkey,
+    collection_pda: &Pubkey,
+    authority: &Pubkey,
+) {
+    match get_metadata_pda(collection_mint, program) {
+        Ok((_, metadata)) => {
+            print_with_style(
+                ":   ",
+                "name",
+                metadata.data.name.trim_matches(char::from(0)).to_string(),
+            );
+            print_with_style(
+                ":   ",
+                "symbol",
+                metadata.data.symbol.trim_matches(char::from(0)).to_string(),
+            );
+            print_with_style(
+                ":   ",
+                "uri",
+                metadata.data.uri.trim_matches(char::from(0)).to_string(),
+            );
+
+            let update_authority = metadata.update_authority.to_string();
+            print_with_style(
+                ":   ",
+                "update authority",
+                if metadata.update_authority == *authority {
+                    update_authority
+                } else {
+                    format!(
+                        "{} {}",
+                        update_authority,
+                        style("(differs from the magic hat authority)").yellow()
+                    )
+                },
+            );
+            print_with_style(
+                ":   ",
+                "verified",
+                match metadata.collection {
+                    Some(collection) if collection.verified => {
+                        format!("true (collection {})", collection.key)
+                    }
+                    _ => "false".to_string(),
+                },
+            );
+        }
+        Err(err) => print_with_style(
+            ":   ",
+            "metadata",
+            style(format!("not found ({})", err)).yellow().to_string(),
+        ),
+    }
+
+    print_with_style(
+        ":   ",
+        "master edition",
+        match get_master_edition_pda(collection_mint, program) {
+            Ok((pubkey, master_edition)) => format!(
+                "{} (supply {}{})",
+                pubkey,
+                master_edition.supply,
+                match master_edition.max_supply {
+                    Some(max_supply) => format!(", max supply {}", max_supply),
+                    None => String::new(),
+                }
+            ),
+            Err(_) => style("not found").yellow().to_string(),
+        },
+    );
+
+    let (authority_record, _) = find_collection_authority_account(collection_mint, collection_pda);
+    print_with_style(
+        ":   ",
+        "authority record",
+        if program.rpc().get_account(&authority_record).is_ok() {
+            authority_record.to_string()
+        } else {
+            format!(
+                "{} {}",
+                authority_record,
+                style("(not found, re-run `collection set`)").yellow()
+            )
+        },
+    );
+}
+
+/// Refresh the countdowns and the number of items redeemed until interrupted,
+/// optionally including the mint rate of the last refreshes.
+fn watch_magic_hat(
+    laddu_config: &LadduConfig,
+    magic_hat_id: &Pubkey,
+    mut state: MagicHat,
+    stats: bool,
+    interrupted: &AtomicBool,
+) {
+    println!("\nWatching Magic Hat (Ctrl+C to stop)");
+
+    let pb = spinner_with_style();
+    let mut last_refresh = Utc::now().timestamp();
+    let mut samples = VecDeque::from(vec![(last_refresh, state.items_redeemed)]);
+
+    while !interrupted.load(Ordering::SeqCst) {
+        let now = Utc::now().timestamp();
+
+        if now - last_refresh >= WATCH_REFRESH_SECS {
+            match get_magic_hat_state(laddu_config, magic_hat_id) {
+                Ok(refreshed) => state = refreshed,
+                Err(err) => warn!("Failed to refresh Magic Hat state: {:?}", err),
+            }
+            last_refresh = now;
+
+            samples.push_back((now, state.items_redeemed));
+            if samples.len() > STATS_SAMPLES {
+                samples.pop_front();
+            }
+        }
+
+        let mut status = vec![format!(
+            "items redeemed: {}/{}",
+            state.items_redeemed, state.data.items_available
+        )];
+
+        if stats {
+            let (first_time, first_redeemed) = samples[0];
+            let (last_time, last_redeemed) = samples[samples.len() - 1];
+            let deltas: Vec<u64> = samples
+                .iter()
+                .zip(samples.iter().skip(1))
+                .map(|((_, previous), (_, current))| current.saturating_sub(*previous))
+                .collect();
+
+            status.push(format!(
+                "{} {}",
+                sparkline(&deltas),
+                format_mint_rate(
+                    mint_rate(first_redeemed, last_redeemed, last_time - first_time),
+                    state
+                        .data
+                        .items_available
+                        .saturating_sub(state.items_redeemed),
+                )
+            ));
+        }
+
+        if let Some(date) = state.data.go_live_date {
+            status.push(format!(
+                "go live: {}",
+                format_countdown(date, now, "starts", "started")
+            ));
+        }
+
+        if let Some(ref end_settings) = state.data.end_settings {
+            if end_settings.end_setting_type == EndSettingType::Date {
+                status.push(format!(
+                    "end: {}",
+                    format_countdown(end_settings.number as i64, now, "ends", "ended")
+                ));
+            }
+        }
+
+        pb.set_message(status.join(" | "));
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    pb.finish_with_message("Stopped watching");
+}
+
+/// Sample the number of items redeemed over a short window and print the mint
+/// rate and the estimated time to sell out.
+fn print_mint_stats(
+    laddu_config: &LadduConfig,
+    magic_hat_id: &Pubkey,
+    state: &MagicHat,
+) -> Result<()> {
+    println!();
+
+    let pb = spinner_with_style();
+    pb.set_message(format!(
+        "Sampling mints for {} seconds...",
+        STATS_WINDOW_SECS
+    ));
+
+    let start = Utc::now().timestamp();
+    thread::sleep(Duration::from_secs(STATS_WINDOW_SECS as u64));
+    let refreshed = get_magic_hat_state(laddu_config, magic_hat_id)?;
+    let elapsed = Utc::now().timestamp() - start;
+
+    pb.finish_and_clear();
+
+    let rate = mint_rate(state.items_redeemed, refreshed.items_redeemed, elapsed);
+    let remaining = refreshed
+        .data
+        .items_available
+        .saturating_sub(refreshed.items_redeemed);
+
+    print_with_style(
+        "",
+        "items redeemed",
+        format!(
+            "{}/{}",
+            refreshed.items_redeemed, refreshed.data.items_available
+        ),
+    );
+    print_with_style("", "mint rate", format_mint_rate(rate, remaining));
+
+    Ok(())
+}
+
+/// Return the number of mints per minute between two samples.
+fn mint_rate(first_redeemed: u64, last_redeemed: u64, elapsed_secs: i64) -> Option<f64> {
+    if elapsed_secs > 0 {
+        Some(last_redeemed.saturating_sub(first_redeemed) as f64 * 60.0 / elapsed_secs as f64)
+    } else {
+        None
+    }
+}
+
+fn format_mint_rate(rate: Option<f64>, remaining: u64) -> String {
+    match rate {
+        _ if remaining == 0 => "sold out".to_string(),
+        Some(rate) if rate > 0.0 => format!(
+            "{:.1} mints/min, sells out in {}",
+            rate,
+            format_duration((remaining as f64 / rate * 60.0).ceil() as i64)
+        ),
+        Some(_) => "0 mints/min".to_string(),
+        None => "measuring...".to_string(),
+    }
+}
+
+/// Render the values as a sparkline, scaled to the maximum value.
+fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    values
+        .iter()
+        .map(|value| {
+            if max == 0 {
+                BARS[0]
+            } else {
+                BARS[(value * (BARS.len() as u64 - 1) / max) as usize]
+            }
+        })
+        .collect()
+}
+
+fn format_utc(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0)
+        .format("%a %B %e %Y %H:%M:%S UTC")
+        .to_string()
+}
+
+fn format_local(timestamp: i64) -> String {
+    Local
+        .timestamp(timestamp, 0)
+        .format("%a %B %e %Y %H:%M:%S %:z")
+        .to_string()
+}
+
+/// Describe a date relative to `now` (e.g. "starts in 2h 13m" or "ended 3d ago").
+fn format_countdown(timestamp: i64, now: i64, upcoming: &str, elapsed: &str) -> String {
+    if timestamp > now {
+        format!("{} in {}", upcoming, format_duration(timestamp - now))
+    } else {
+        format!("{} {} ago", elapsed, format_duration(now - timestamp))
+    }
+}
+
+/// Format a number of seconds using its two most significant units.
+fn format_duration(seconds: i64) -> String {
+    let units = [
+        (seconds / 86_400, "d"),
+        (seconds % 86_400 / 3_600, "h"),
+        (seconds % 3_600 / 60, "m"),
+        (seconds % 60, "s"),
+    ];
+
+    match units.iter().position(|(value, _)| *value > 0) {
+        Some(first) => units[first..]
+            .iter()
+            .take(2)
+            .map(|(value, unit)| format!("{}{}", value, unit))
+            .collect::<Vec<String>>()
+            .join(" "),
+        None => "0s".to_string(),
+    }
+}
+
+fn print_unminted(magic_hat_id: &Pubkey, state: &MagicHat, data: &[u8], json: bool) -> Result<()> {
+    let unminted = unminted_indices(data, state)?;
+    let items_available = state.data.items_available;
+    let items_remaining = unminted.len() as u64;
+    let percent_minted = if items_available > 0 {
+        (items_available - items_remaining) as f64 * 100.0 / items_available as f64
+    } else {
+        0.0
+    };
+
+    if json {
+        let output = UnmintedOutput {
+            magic_hat: magic_hat_id.to_string(),
+            items_available,
+            items_redeemed: state.items_redeemed,
+            items_remaining,
+            percent_minted,
+            unminted,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!(
+        "\n{}{} {}",
+        MAGICHAT_EMOJI,
+        style("Magic Hat ID:").dim(),
+        magic_hat_id
+    );
+    println!(" {}", style(":").dim());
+    print_with_style("", "items available", items_available.to_string());
+    print_with_style("", "items redeemed", state.items_redeemed.to_string());
+    print_with_style("", "items remaining", items_remaining.to_string());
+    print_with_style("", "minted", format!("{:.2}%", percent_minted));
+    print_with_style(
+        "",
+        "unminted indices",
+        if unminted.is_empty() {
+            "none".to_string()
+        } else {
+            format_ranges(&unminted)
+        },
+    );
+
+    Ok(())
+}
+
+fn get_account_rent(program: &Program, address: &Pubkey) -> Result<AccountRent> {
+    let account = program.rpc().get_account(address)?;
+    let minimum = program
+        .rpc()
+        .get_minimum_balance_for_rent_exemption(account.data.len())?;
+
+    Ok(AccountRent {
+        address: address.to_string(),
+        data_length: account.data.len(),
+        lamports: Amount::new(account.lamports),
+        rent_exempt_minimum: Amount::new(minimum),
+    })
+}
+
+fn print_account_rent(name: &str, rent: &AccountRent) {
+    print_with_style(":   ", name, rent.address.clone());
+    print_with_style(":       ", "data length", rent.data_length.to_string());
+    print_with_style(
+        ":       ",
+        "lamports",
+        format!("◎ {} ({})", rent.lamports.sol, rent.lamports.lamports),
+    );
+    print_with_style(
+        ":       ",
+        "rent-exempt minimum",
+        format!(
+            "◎ {} ({})",
+            rent.rent_exempt_minimum.sol, rent.rent_exempt_minimum.lamports
+        ),
+    );
+}
+
+fn print_raw_account(address: &Pubkey, account: &Account, bytes: usize, json: bool) -> Result<()> {
+    let discriminator = &account.data[..cmp::min(8, account.data.len())];
+    let is_magic_hat = discriminator == MagicHat::discriminator();
+    let data = &account.data[..cmp::min(bytes, account.data.len())];
+
+    if json {
+        let output = RawAccountOutput {
+            address: address.to_string(),
+            owner: account.owner.to_string(),
+            lamports: account.lamports,
+            data_length: account.data.len(),
+            discriminator: HEXLOWER.encode(discriminator),
+            is_magic_hat,
+            data: HEXLOWER.encode(data),
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{} {}", style("Account:").dim(), address);
+    println!(" {}", style(":").dim());
+    print_with_style(
+        "",
+        "owner",
+        if account.owner == MAGIC_HAT_ID {
+            account.owner.to_string()
+        } else {
+            format!(
+                "{} {}",
+                account.owner,
+                style(format!("(expected {})", MAGIC_HAT_ID)).yellow()
+            )
+        },
+    );
+    print_with_style(
+        "",
+        "lamports",
+        format!(
+            "{} (◎ {})",
+            account.lamports,
+            account.lamports as f64 / LAMPORTS_PER_SOL as f64
+        ),
+    );
+    print_with_style("", "data length", account.data.len().to_string());
+    print_with_style(
+        "",
+        "discriminator",
+        format!(
+            "{} {}",
+            HEXLOWER.encode(discriminator),
+            if is_magic_hat {
+                style("(MagicHat)").green()
+            } else {
+                style("(not a MagicHat account)").yellow()
+            }
+        ),
+    );
+    print_with_style("", "data", format!("first {} byte(s)", data.len()));
+
+    for (index, line) in data.chunks(16).enumerate() {
+        let ascii: String = line
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        println!(
+            "   {}  {:<47}  {}",
+            style(format!("{:08x}", index * 16)).dim(),
+            line.iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<String>>()
+                .join(" "),
+            style(ascii).dim()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_cache_summary(cache_path: &str, config_path: &str, json: bool) -> Result<()> {
+    let cache = load_cache(cache_path, false)?;
+    let config_number = if Path::new(config_path).exists() {
+        get_config_data(config_path)?.number
+    } else {
+        None
+    };
+
+    let items = &cache.items.0;
+    let count = |predicate: fn(&CacheItem) -> bool| items.values().filter(|i| predicate(i)).count();
+
+    // the expected number of items comes from the config, falling back to the
+    // cache when the config is not available
+    let expected = config_number
+        .or(cache.program.number)
+        .unwrap_or(items.len() as u64);
+    let missing: Vec<u64> = (0..expected)
+        .filter(|index| match items.get(&index.to_string()) {
+            Some(item) => {
+                item.image_link.is_empty() || item.metadata_link.is_empty() || !item.on_chain
+            }
+            None => true,
+        })
+        .collect();
+
+    let summary = CacheSummaryOutput {
+        cache: cache_path.to_string(),
+        magic_hat: if cache.program.magic_hat.is_empty() {
+            None
+        } else {
+            Some(cache.program.magic_hat.clone())
+        },
+        total_items: items.len(),
+        image_links: count(|item| !item.image_link.is_empty()),
+        metadata_links: count(|item| !item.metadata_link.is_empty()),
+        on_chain: count(|item| item.on_chain),
+        config_number,
+        first_missing: missing.first().copied(),
+        last_missing: missing.last().copied(),
+        ready: missing.is_empty() && items.len() as u64 == expected,
+        receipts: items
+            .iter()
+            .filter_map(|(index, item)| item.receipts(index))
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    let format_count = |value: usize| {
+        let text = format!("{}/{}", value, expected);
+        if value as u64 == expected {
+            text
+        } else {
+            style(text).yellow().to_string()
+        }
+    };
+
+    println!("\n{} {}", style("Cache:").dim(), summary.cache);
+    println!(" {}", style(":").dim());
+    print_with_style(
+        "",
+        "magic hat",
+        summary.magic_hat.unwrap_or_else(|| "none".to_string()),
+    );
+    print_with_style(
+        "",
+        "config number",
+        match summary.config_number {
+            Some(number) => number.to_string(),
+            None => style(format!("unknown ({} not found)", config_path))
+                .yellow()
+                .to_string(),
+        },
+    );
+    print_with_style("", "total items", format_count(summary.total_items));
+    print_with_style("", "image links", format_count(summary.image_links));
+    print_with_style("", "metadata links", format_count(summary.metadata_links));
+    print_with_style("", "on chain", format_count(summary.on_chain));
+    print_with_style("", "receipts", format_count(summary.receipts.len()));
+    print_with_style(
+        "",
+        "missing",
+        match (summary.first_missing, summary.last_missing) {
+            (Some(first), Some(last)) => {
+                format!("{} item(s), first {} last {}", missing.len(), first, last)
+            }
+            _ => "none".to_string(),
+        },
+    );
+    print_with_style(
+        "",
+        "ready",
+        if summary.ready {
+            style("yes").green().to_string()
+        } else {
+            style("no").red().to_string()
+        },
+    );
+
+    Ok(())
+}
+
+async fn get_balances(
+    program: &Program,
+    laddu_config: &LadduConfig,
+    magic_hat_id: &Pubkey,
+    state: &MagicHat,
+) -> Result<BalancesOutput> {
+    let rpc = program.rpc();
+    let payer = laddu_config.keypair.pubkey();
+
+    let treasury = match state.token_mint {
+        Some(token_mint) => {
+            let balance = rpc.get_token_account_balance(&state.wallet)?;
+            TreasuryBalance {
+                address: state.wallet.to_string(),
+                token_mint: Some(token_mint.to_string()),
+                amount: balance.amount.parse::<u64>()?,
+                ui_amount: balance.ui_amount_string,
+            }
+        }
+        None => {
+            let lamports = rpc.get_balance(&state.wallet)?;
+            TreasuryBalance {
+                address: state.wallet.to_string(),
+                token_mint: None,
+                amount: lamports,
+                ui_amount: Amount::new(lamports).sol,
+            }
+        }
+    };
+
+    // the bundlr balance is informative, failing to retrieve it is not an error
+    let bundlr = match get_cluster(program.rpc()) {
+        Ok(cluster) => {
+            let node = match cluster {
+                Cluster::Devnet => BUNDLR_DEVNET,
+                Cluster::Mainnet => BUNDLR_MAINNET,
+            };
+            let http_client = HttpClient::new();
+
+            match BundlrHandler::get_bundlr_balance(&http_client, &payer.to_string(), node).await {
+                Ok(lamports) => Some(AccountBalance::new(&payer, lamports)),
+                Err(err) => {
+                    warn!("Failed to retrieve bundlr balance: {:?}", err);
+                    None
+                }
+            }
+        }
+        Err(err) => {
+            warn!("Failed to retrieve bundlr balance: {:?}", err);
+            None
+        }
+    };
+
+    Ok(BalancesOutput {
+        magic_hat: magic_hat_id.to_string(),
+        payer: AccountBalance::new(&payer, rpc.get_balance(&payer)?),
+        treasury,
+        bundlr,
+        magic_hat_rent: AccountBalance::new(magic_hat_id, rpc.get_balance(magic_hat_id)?),
+    })
+}
+
+fn print_balances(balances: BalancesOutput, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(&balances)?);
+        return Ok(());
+    }
+
+    let format_balance = |balance: &AccountBalance| {
+        format!(
+            "◎ {} ({}) {}",
+            balance.balance.sol,
+            balance.balance.lamports,
+            style(&balance.address).dim()
+        )
+    };
+
+    println!(
+        "\n{}{} {}",
+        MAGICHAT_EMOJI,
+        style("Magic Hat ID:").dim(),
+        balances.magic_hat
+    );
+    println!(" {}", style(":").dim());
+    print_with_style("", "payer", format_balance(&balances.payer));
+    print_with_style(
+        "",
+        "treasury",
+        match balances.treasury.token_mint {
+            Some(ref token_mint) => format!(
+                "{} tokens of {} ({}) {}",
+                balances.treasury.ui_amount,
+                token_mint,
+                balances.treasury.amount,
+                style(&balances.treasury.address).dim()
+            ),
+            None => format!(
+                "◎ {} ({}) {}",
+                balances.treasury.ui_amount,
+                balances.treasury.amount,
+                style(&balances.treasury.address).dim()
+            ),
+        },
+    );
+    print_with_style(
+        "",
+        "bundlr",
+        match balances.bundlr {
+            Some(ref bundlr) => format_balance(bundlr),
+            None => "unavailable".to_string(),
+        },
+    );
+    print_with_style(
+        "",
+        "magic hat rent",
+        format_balance(&balances.magic_hat_rent),
+    );
+
+    Ok(())
+}
+
+fn print_config_lines(
+    state: &MagicHat,
+    data: &[u8],
+    from: Option<u64>,
+    to: Option<u64>,
+    json: bool,
+) -> Result<()> {
+    if state.data.hidden_settings.is_some() {
+        let error = anyhow!("Magic Hat uses hidden settings and has no config lines");
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let items_available = state.data.items_available;
+    let from = from.unwrap_or(0);
+    let to = cmp::min(
+        to.unwrap_or(from + CONFIG_LINES_PAGE_SIZE - 1),
+        items_available.saturating_sub(1),
+    );
+
+    if from > to {
+        let error = anyhow!(
+            "Invalid range {}-{}, the Magic Hat has {} config line(s)",
+            from,
+            to,
+            items_available
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let loaded = get_bitmask(data, items_available, LOADED_BITMASK)?;
+    let mut lines = Vec::new();
+
+    for index in from..=to {
+        let line = if is_bit_set(loaded, index) {
+            let line_start =
+                CONFIG_ARRAY_START + STRING_LEN_SIZE + CONFIG_LINE_SIZE * index as usize;
+            let name_start = line_start + CONFIG_NAME_OFFSET;
+            let uri_start = line_start + CONFIG_URI_OFFSET;
+
+            ConfigLineOutput {
+                index,
+                written: true,
+                name: Some(decode_string(
+                    &data[name_start..name_start + MAX_NAME_LENGTH],
+                )),
+                uri: Some(decode_string(&data[uri_start..uri_start + MAX_URI_LENGTH])),
+            }
+        } else {
+            ConfigLineOutput {
+                index,
+                written: false,
+                name: None,
+                uri: None,
+            }
+        };
+
+        lines.push(line);
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&lines)?);
+        return Ok(());
+    }
+
+    println!(
+        "\n{} config line(s) {}-{} of {}",
+        style("Showing").bold(),
+        from,
+        to,
+        items_available
+    );
+    println!(" {}", style(":").dim());
+
+    for line in lines {
+        match (line.name, line.uri) {
+            (Some(name), Some(uri)) => {
+                print_with_style("", &line.index.to_string(), format!("{} {}", name, uri))
+            }
+            _ => print_with_style(
+                "",
+                &line.index.to_string(),
+                style("not written").yellow().to_string(),
+            ),
+        }
+    }
+
+    if to + 1 < items_available {
+        println!(
+            "\nUse --from {} to show the next page.",
+            style(to + 1).bold()
+        );
+    }
+
+    Ok(())
+}
+
+/// Decode a fixed-size string slot, removing the null padding.
+fn decode_string(slot: &[u8]) -> String {
+    String::from_utf8_lossy(slot)
+        .trim_matches(char::from(0))
+        .to_string()
+}
+
+/// Return the indices of the items not minted yet. Magic hats with hidden
+/// settings have no config lines, so their items are minted in sequence.
+fn unminted_indices(data: &[u8], state: &MagicHat) -> Result<Vec<u64>> {
+    let items_available = state.data.items_available;
+
+    if state.data.hidden_settings.is_some() {
+        return Ok((state.items_redeemed..items_available).collect());
+    }
+
+    let minted = get_bitmask(data, items_available, MINTED_BITMASK)?;
+
+    Ok((0..items_available)
+        .filter(|index| !is_bit_set(minted, *index))
+        .collect())
+}
+
+/// Return one of the bitmasks stored after the config lines.
+///
+/// The account data stores the bitmask of loaded lines followed by the bitmask
+/// of minted items, each of them preceded by a u32 counter. As laid out by the
+/// program, the minted bitmask starts `items_available / 8 + 4` bytes after the
+/// loaded one (not after its last byte).
+pub fn get_bitmask(data: &[u8], items_available: u64, position: usize) -> Result<&[u8]> {
+    let bitmask_size = items_available as usize / 8 + 1;
+    let bitmask_start = CONFIG_ARRAY_START
+        + STRING_LEN_SIZE
+        + items_available as usize * CONFIG_LINE_SIZE
+        + 4
+        + position * (items_available as usize / 8 + 4);
+
+    match data.get(bitmask_start..bitmask_start + bitmask_size) {
+        Some(bitmask) => Ok(bitmask),
+        None => {
+            let error = anyhow!("Magic Hat account data does not contain the expected bitmask");
+            error!("{:?}", error);
+            Err(error)
+        }
+    }
+}
+
+pub fn is_bit_set(bitmask: &[u8], index: u64) -> bool {
+    bitmask[(index / 8) as usize] & (1u8 << (7 - index % 8)) != 0
+}
+
 fn print_with_style(indent: &str, key: &str, value: String) {
     println!(
         " {} {}",
@@ -230,3 +1231,56 @@ fn print_with_style(indent: &str, key: &str, value: String) {
         value
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the account data of a magic hat with the given bitmasks.
+    fn account_data(items_available: u64, loaded: &[u8], minted: &[u8]) -> Vec<u8> {
+        let n = items_available as usize;
+        let loaded_start = CONFIG_ARRAY_START + STRING_LEN_SIZE + n * CONFIG_LINE_SIZE + 4;
+        let minted_start = loaded_start + n / 8 + 4;
+
+        let mut data = vec![0u8; minted_start + n / 8 + 1];
+        data[loaded_start..loaded_start + loaded.len()].copy_from_slice(loaded);
+        data[minted_start..minted_start + minted.len()].copy_from_slice(minted);
+        data
+    }
+
+    #[test]
+    fn read_minted_bitmask() {
+        // items 0, 2 and 9 are minted
+        let data = account_data(10, &[0xff, 0xc0], &[0b1010_0000, 0b0100_0000]);
+
+        let minted = get_bitmask(&data, 10, MINTED_BITMASK).unwrap();
+        let minted: Vec<u64> = (0..10).filter(|index| is_bit_set(minted, *index)).collect();
+        assert_eq!(minted, [0, 2, 9]);
+
+        let loaded = get_bitmask(&data, 10, LOADED_BITMASK).unwrap();
+        assert!((0..10).all(|index| is_bit_set(loaded, index)));
+    }
+
+    #[test]
+    fn read_bitmasks_of_a_multiple_of_eight_items() {
+        // item 7 is not loaded, items 1 and 15 are minted
+        let data = account_data(16, &[0xfe, 0xff], &[0b0100_0000, 0b0000_0001]);
+
+        let loaded = get_bitmask(&data, 16, LOADED_BITMASK).unwrap();
+        assert!(!is_bit_set(loaded, 7));
+        assert!(is_bit_set(loaded, 8));
+
+        let minted = get_bitmask(&data, 16, MINTED_BITMASK).unwrap();
+        let unminted: Vec<u64> = (0..16)
+            .filter(|index| !is_bit_set(minted, *index))
+            .collect();
+        assert_eq!(unminted.len(), 14);
+        assert!(!unminted.contains(&1) && !unminted.contains(&15));
+    }
+
+    #[test]
+    fn reject_short_account_data() {
+        let data = account_data(10, &[], &[]);
+        assert!(get_bitmask(&data[..data.len() - 1], 10, MINTED_BITMASK).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use anchor_client::solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use anyhow::{anyhow, Result};
+use console::style;
+use solana_client::rpc_client::RpcClient;
+use solana_program::hash::hashv;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::setup::resolve_rpc_url;
+
+/// SPL Name Service program.
+const NAME_PROGRAM_ID: &str = "namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX";
+
+/// Name account of the `.sol` top level domain, parent of the `.sol` domains.
+const SOL_TLD_AUTHORITY: &str = "58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx";
+
+/// Prefix of the hashed names used to derive the name accounts.
+const HASH_PREFIX: &str = "SPL Name Service";
+
+/// Size of the name record header (parent name, owner and class).
+const NAME_RECORD_HEADER_LEN: usize = 96;
+
+/// Whether `.sol` names are resolved, cleared by `--no-sns`.
+static SNS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// RPC of the command, used to look up the name accounts.
+static SNS_RPC_URL: Mutex<Option<String>> = Mutex::new(None);
+
+/// Names resolved so far, so each name is looked up (and printed) once.
+static RESOLVED_NAMES: Mutex<Vec<(String, Pubkey)>> = Mutex::new(Vec::new());
+
+/// Sets how `.sol` names are resolved: through the RPC of the command (the Solana
+/// config one when not specified), or not at all.
+pub fn configure_sns(enabled: bool, rpc_url: Option<String>) {
+    SNS_ENABLED.store(enabled, Ordering::SeqCst);
+    *SNS_RPC_URL.lock().unwrap() = rpc_url;
+}
+
+/// Whether the input is a Solana Name Service domain (e.g. `artist.sol`).
+pub fn is_sns_name(input: &str) -> bool {
+    input.trim().to_lowercase().ends_with(".sol")
+}
+
+/// Parses an address given either as a base58 pubkey or as a `.sol` domain, which
+/// is resolved to the owner of the domain.
+pub fn parse_address(input: &str) -> Result<Pubkey> {
+    if is_sns_name(input) {
+        resolve_sns_name(input)
+    } else {
+        Pubkey::from_str(input.trim()).map_err(|_| anyhow!("Invalid address: {}", input))
+    }
+}
+
+/// Resolves a `.sol` domain (or subdomain) to the owner of its name account.
+pub fn resolve_sns_name(name: &str) -> Result<Pubkey> {
+    let name = name.trim().to_lowercase();
+
+    if !SNS_ENABLED.load(Ordering::SeqCst) {
+        return Err(anyhow!(
+            "Cannot resolve {}: .sol domains are not resolved with --no-sns, use the address instead",
+            name
+        ));
+    }
+
+    if let Some((_, owner)) = RESOLVED_NAMES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(resolved, _)| *resolved == name)
+    {
+        return Ok(*owner);
+    }
+
+    let rpc_url = resolve_rpc_url(SNS_RPC_URL.lock().unwrap().clone());
+    let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+
+    let name_account = sns_name_account(&name)?;
+    let account = rpc_client
+        .get_account_with_commitment(&name_account, CommitmentConfig::confirmed())
+        .map_err(|err| anyhow!("Failed to resolve {}: {}", name, err))?
+        .value
+        .ok_or_else(|| anyhow!("Failed to resolve {}: the domain is not registered", name))?;
+
+    if account.data.len() < NAME_RECORD_HEADER_LEN {
+        return Err(anyhow!(
+            "Failed to resolve {}: invalid name account {}",
+            name,
+            name_account
+        ));
+    }
+    let owner = Pubkey::new(&account.data[32..64]);
+
+    println!(
+        "{} {} {}",
+        style("Resolved").bold(),
+        name,
+        style(format!("to {}", owner)).dim()
+    );
+    RESOLVED_NAMES.lock().unwrap().push((name, owner));
+
+    Ok(owner)
+}
+
+/// Derives the name account of a domain: each label is a child of the previous one,
+/// starting from the `.sol` top level domain. Subdomains are prefixed with `\0`.
+fn sns_name_account(name: &str) -> Result<Pubkey> {
+    let program_id = Pubkey::from_str(NAME_PROGRAM_ID)?;
+    let domain = name.strip_suffix(".sol").unwrap_or(name);
+
+    let labels: Vec<&str> = domain.split('.').rev().collect();
+    if labels.iter().any(|label| label.is_empty()) {
+        return Err(anyhow!("Invalid .sol domain: {}", name));
+    }
+
+    let mut parent = Pubkey::from_str(SOL_TLD_AUTHORITY)?;
+    for (depth, label) in labels.iter().enumerate() {
+        let label = if depth == 0 {
+            label.to_string()
+        } else {
+            format!("\0{}", label)
+        };
+        let hashed_name = hashv(&[HASH_PREFIX.as_bytes(), label.as_bytes()]);
+        let class = Pubkey::default();
+
+        parent = Pubkey::find_program_address(
+            &[hashed_name.as_ref(), class.as_ref(), parent.as_ref()],
+            &program_id,
+        )
+        .0;
+    }
+
+    Ok(parent)
+}
@@ -0,0 +1,162 @@
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anyhow::Result;
+use console::style;
+use dialoguer::{theme::ColorfulTheme, Input};
+use std::str::FromStr;
+
+use magic_hat::accounts as nft_accounts;
+use magic_hat::instruction as nft_instruction;
+
+use crate::cache::load_cache;
+use crate::common::*;
+use crate::magic_hat::{get_magic_hat_state, MAGIC_HAT_ID};
+use crate::program_errors::decode_program_error;
+use crate::sns::parse_address;
+use crate::utils::spinner_with_style;
+
+pub struct UpdateAuthorityArgs {
+    pub keypair: Option<String>,
+    pub rpc_url: Option<String>,
+    pub cache: String,
+    pub magic_hat: Option<String>,
+    pub new_authority: String,
+    pub allow_pda: bool,
+    pub yes: bool,
+}
+
+pub fn process_update_authority(args: UpdateAuthorityArgs) -> Result<()> {
+    let new_authority = match parse_address(&args.new_authority) {
+        Ok(new_authority) => new_authority,
+        Err(err) => {
+            let error = anyhow!(
+                "Failed to parse new authority: {} ({})",
+                args.new_authority,
+                err
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    // an address off the curve has no private key, so it can only sign
+    // through a program
+    if !new_authority.is_on_curve() && !args.allow_pda {
+        let error = anyhow!(
+            "New authority {} is not on the ed25519 curve (PDA), use --allow-pda if this is intended",
+            new_authority
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+    let client = setup_client(&laddu_config)?;
+    let program = client.program(MAGIC_HAT_ID);
+
+    // the magic hat id specified takes precedence over the one from the cache
+
+    let magic_hat_id = match args.magic_hat {
+        Some(magic_hat_id) => magic_hat_id,
+        None => {
+            let cache = load_cache(&args.cache, false)?;
+            cache.program.magic_hat
+        }
+    };
+
+    let magichat_pubkey = match Pubkey::from_str(&magic_hat_id) {
+        Ok(magichat_pubkey) => magichat_pubkey,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat_id);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    println!(
+        "{} {}Loading Magic Hat",
+        style("[1/2]").bold().dim(),
+        LOOKING_GLASS_EMOJI
+    );
+    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let magic_hat_state = get_magic_hat_state(&laddu_config, &magichat_pubkey)?;
+
+    pb.finish_with_message("Done");
+
+    if magic_hat_state.authority == new_authority {
+        println!(
+            "\n{} is already the authority, nothing to update.",
+            new_authority
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\n{} {}Updating authority",
+        style("[2/2]").bold().dim(),
+        COMPUTER_EMOJI
+    );
+    println!(
+        "\n{}",
+        style("This operation is irreversible: only the new authority will be able to update or withdraw the Magic Hat.")
+            .yellow()
+    );
+    println!(
+        "  {} {}",
+        style("current:").dim(),
+        magic_hat_state.authority
+    );
+    println!("  {} {}\n", style("new:").dim(), new_authority);
+
+    if !args.yes {
+        let confirmation: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Re-type the new authority to confirm")
+            .interact_text()?;
+
+        if confirmation.trim() != new_authority.to_string() {
+            let error = anyhow!("The address entered does not match the new authority");
+            error!("{:?}", error);
+            return Err(error);
+        }
+    }
+
+    let pb = spinner_with_style();
+    pb.set_message("Sending update authority transaction...");
+
+    let signature = program
+        .request()
+        .accounts(nft_accounts::UpdateMagicHat {
+            magic_hat: magichat_pubkey,
+            authority: program.payer(),
+            wallet: magic_hat_state.wallet,
+        })
+        .args(nft_instruction::UpdateAuthority {
+            new_authority: Some(new_authority),
+        })
+        .send()
+        .map_err(decode_program_error)?;
+
+    pb.finish_with_message(format!(
+        "{} {}",
+        style("Authority signature:").bold(),
+        signature
+    ));
+
+    let updated_state = get_magic_hat_state(&laddu_config, &magichat_pubkey)?;
+
+    println!(
+        "\n{} {}",
+        style("Old authority:").bold(),
+        magic_hat_state.authority
+    );
+    println!(
+        "{} {}",
+        style("New authority:").bold(),
+        updated_state.authority
+    );
+
+    Ok(())
+}
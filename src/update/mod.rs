@@ -1,3 +1,5 @@
+pub mod authority;
 pub mod process;
 
+pub use authority::*;
 pub use process::*;
@@ -1,7 +1,11 @@
-use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use anchor_client::RequestBuilder;
 use anchor_lang::prelude::AccountMeta;
 use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
 use console::style;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
 use spl_associated_token_account::get_associated_token_address;
 use std::str::FromStr;
 
@@ -9,30 +13,93 @@ use magic_hat::instruction as nft_instruction;
 use magic_hat::{accounts as nft_accounts, MagicHatData};
 
 use crate::common::*;
-use crate::config::{data::*, parser::get_config_data};
+use crate::config::{data::*, lint::*, parser::get_config_data, price::Price};
 use crate::magic_hat::MAGIC_HAT_ID;
-use crate::magic_hat::{get_magic_hat_state, parse_config_price};
-use crate::utils::{check_spl_token, check_spl_token_account, spinner_with_style};
+use crate::magic_hat::{get_magic_hat_state, parse_config_price, parse_price, token_decimals};
+use crate::program_errors::{decode_program_error, describe_transaction_error};
+use crate::show::print_magic_hat_settings;
+use crate::sns::parse_address;
+use crate::utils::{check_spl_token, check_spl_token_account, cluster_name, spinner_with_style};
 use crate::{cache::load_cache, config::data::ConfigData};
 
 pub struct UpdateArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
-    pub new_authority: Option<String>,
     pub config: String,
+    /// Whether the config file was specified with `--config`
+    pub explicit_config: bool,
     pub magic_hat: Option<String>,
+    pub from_config: bool,
+    pub treasury: Option<String>,
+    pub update_treasury: bool,
+    pub price: Option<Price>,
+    pub go_live_date: Option<String>,
+    pub timezone: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+    pub is_mutable: Option<bool>,
+    pub retain_authority: Option<bool>,
+    pub symbol: Option<String>,
+    pub hidden_name: Option<String>,
+    pub hidden_uri: Option<String>,
+    pub hidden_hash: Option<String>,
+    pub force: bool,
+    pub simulate: bool,
+    pub yes: bool,
+}
+
+impl UpdateArgs {
+    /// Whether any of the per-field flags was specified.
+    fn has_field_flags(&self) -> bool {
+        self.price.is_some()
+            || self.go_live_date.is_some()
+            || self.seller_fee_basis_points.is_some()
+            || self.is_mutable.is_some()
+            || self.retain_authority.is_some()
+            || self.symbol.is_some()
+            || self.has_hidden_settings_flags()
+    }
+
+    /// Whether any of the hidden settings flags was specified.
+    fn has_hidden_settings_flags(&self) -> bool {
+        self.hidden_name.is_some() || self.hidden_uri.is_some() || self.hidden_hash.is_some()
+    }
 }
 
 pub fn process_update(args: UpdateArgs) -> Result<()> {
-    let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+    let laddu_config = laddu_setup(args.keypair.clone(), args.rpc_url.clone())?;
     let client = setup_client(&laddu_config)?;
-    let config_data = get_config_data(&args.config)?;
+
+    // when only field flags are specified, the update starts from the on-chain
+    // data and the config file is not used
+    let mut config_data = if args.has_field_flags() && !args.from_config && !args.update_treasury {
+        if args.explicit_config {
+            let error = anyhow!(
+                "The config file {} would be ignored with field flags: pass --from-config to \
+                 start the update from it, or drop --config",
+                args.config
+            );
+            error!("{:?}", error);
+            return Err(error);
+        }
+        None
+    } else {
+        Some(get_config_data(&args.config)?)
+    };
+
+    // the timezone flag takes precedence over the timezone of the config file
+    let timezone = args
+        .timezone
+        .clone()
+        .or_else(|| config_data.as_ref().and_then(|c| c.timezone.clone()));
+    if let Some(ref mut config_data) = config_data {
+        config_data.timezone = timezone.clone();
+    }
 
     // the magic hat id specified takes precedence over the one from the cache
 
     let magic_hat_id = match args.magic_hat {
-        Some(magic_hat_id) => magic_hat_id,
+        Some(ref magic_hat_id) => magic_hat_id.clone(),
         None => {
             let cache = load_cache(&args.cache, false)?;
             cache.program.magic_hat
@@ -59,10 +126,29 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
     pb.set_message("Connecting...");
 
     let magic_hat_state = get_magic_hat_state(&laddu_config, &magichat_pubkey)?;
-    let magic_hat_data = create_magic_hat_data(&client, &config_data, magic_hat_state.data)?;
+    let mut magic_hat_data = match config_data {
+        Some(ref config_data) => {
+            create_magic_hat_data(&client, config_data, magic_hat_state.data.clone())?
+        }
+        None => magic_hat_state.data.clone(),
+    };
+
+    let spl_token = match config_data {
+        Some(ref config_data) => config_data.spl_token,
+        None => magic_hat_state.token_mint,
+    };
+    apply_field_flags(
+        &client,
+        &args,
+        &mut magic_hat_data,
+        spl_token,
+        timezone.as_deref(),
+    )?;
 
     pb.finish_with_message("Done");
 
+    check_items_available(&args, &magic_hat_state, &magic_hat_data)?;
+
     println!(
         "\n{} {}Updating configuration",
         style("[2/2]").bold().dim(),
@@ -71,49 +157,242 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
 
     let mut remaining_accounts: Vec<AccountMeta> = Vec::new();
 
-    if config_data.spl_token.is_some() {
-        if let Some(token) = config_data.spl_token {
-            remaining_accounts.push(AccountMeta {
-                pubkey: token,
-                is_signer: false,
-                is_writable: false,
-            })
-        }
+    if let Some(token) = spl_token {
+        remaining_accounts.push(AccountMeta {
+            pubkey: token,
+            is_signer: false,
+            is_writable: false,
+        })
     }
 
     let program = client.program(MAGIC_HAT_ID);
 
-    let treasury_account = match config_data.spl_token {
-        Some(spl_token) => {
-            let spl_token_account_figured = if config_data.spl_token_account.is_some() {
-                config_data.spl_token_account
-            } else {
-                Some(get_associated_token_address(&program.payer(), &spl_token))
-            };
+    let treasury_account = resolve_treasury_account(
+        &program,
+        &args,
+        config_data.as_ref(),
+        &magic_hat_state,
+        spl_token,
+    )?;
 
-            if config_data.sol_treasury_account.is_some() {
-                return Err(anyhow!("If spl-token-account or spl-token is set then sol-treasury-account cannot be set"));
-            }
+    // prices are stored in the base units of their payment token, so each side
+    // of the diff is formatted with its own token
+    let current_decimals = token_decimals(&client, magic_hat_state.token_mint)?;
+    let new_decimals = token_decimals(&client, spl_token)?;
+    let format_current_price =
+        |price| format_token_price(price, magic_hat_state.token_mint, current_decimals);
+    let format_new_price = |price| format_token_price(price, spl_token, new_decimals);
 
-            // validates the mint address of the token accepted as payment
-            check_spl_token(&program, &spl_token.to_string())?;
+    let mut changes = diff_magic_hat_data(
+        &normalize_magic_hat_data(&magic_hat_state.data),
+        &normalize_magic_hat_data(&magic_hat_data),
+        &format_current_price,
+        &format_new_price,
+    );
 
-            if let Some(token_account) = spl_token_account_figured {
-                // validates the spl token wallet to receive proceedings from SPL token payments
-                check_spl_token_account(&program, &token_account.to_string())?;
-                token_account
-            } else {
-                return Err(anyhow!(
-                    "If spl-token is set, spl-token-account must also be set"
-                ));
-            }
+    if magic_hat_state.token_mint != spl_token {
+        changes.push((
+            "token mint",
+            format_token(magic_hat_state.token_mint),
+            format_token(spl_token),
+        ));
+    }
+
+    if magic_hat_state.wallet != treasury_account {
+        changes.push((
+            "wallet",
+            magic_hat_state.wallet.to_string(),
+            treasury_account.to_string(),
+        ));
+    }
+
+    if changes.is_empty() {
+        if !args.force {
+            println!("\nMagic Hat is already up to date, nothing to update.");
+            return Ok(());
         }
-        None => match config_data.sol_treasury_account {
-            Some(sol_treasury_account) => sol_treasury_account,
-            None => laddu_config.keypair.pubkey(),
-        },
-    };
+        println!("\nMagic Hat is already up to date, forcing the update.");
+    } else {
+        println!("\n{}", style("Changes:").bold());
+    }
+    for (field, old, new) in &changes {
+        println!(
+            "  {} {} {} {}",
+            style(format!("{}:", field)).dim(),
+            style(old).red(),
+            style("→").dim(),
+            style(new).green()
+        );
+    }
+    println!();
+
+    if magic_hat_state.token_mint != spl_token {
+        // prices are stored in the base units of the payment token, so the
+        // same number means a different price after the switch
+        println!(
+            "{}",
+            style("WARNING: the payment token is changing, review the price carefully.")
+                .yellow()
+                .bold()
+        );
+        println!(
+            "  {} {}",
+            style("current price:").dim(),
+            format_current_price(magic_hat_state.data.price)
+        );
+        println!(
+            "  {} {}\n",
+            style("new price:").dim(),
+            format_new_price(magic_hat_data.price)
+        );
 
+        if !args.yes
+            && !args.simulate
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Change the payment token?")
+                .default(false)
+                .interact()?
+        {
+            println!("Update cancelled.");
+            return Ok(());
+        }
+    }
+
+    if magic_hat_state.wallet != treasury_account {
+        println!(
+            "{}\n",
+            style(format!(
+                "WARNING: mint proceeds will be sent to {} instead of {}.",
+                treasury_account, magic_hat_state.wallet
+            ))
+            .yellow()
+            .bold()
+        );
+
+        if !args.yes
+            && !args.simulate
+            && !Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Change the treasury wallet?")
+                .default(false)
+                .interact()?
+        {
+            println!("Update cancelled.");
+            return Ok(());
+        }
+    }
+
+    // only the risky values this update introduces are reported
+    if !confirm_risky_settings(
+        &RiskySettings::from_magic_hat_data(&magic_hat_data),
+        Some(&RiskySettings::from_magic_hat_data(&magic_hat_state.data)),
+        args.yes || args.simulate,
+    )? {
+        println!("Update cancelled.");
+        return Ok(());
+    }
+
+    if args.simulate {
+        return simulate_update(
+            &program,
+            magichat_pubkey,
+            treasury_account,
+            magic_hat_data,
+            remaining_accounts,
+            magic_hat_state.items_redeemed,
+            new_decimals,
+        );
+    }
+
+    if !args.yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Send the update transaction to {} ({})?",
+                cluster_name(&laddu_config.rpc_url),
+                laddu_config.rpc_url
+            ))
+            .default(false)
+            .interact()?
+    {
+        println!("Update cancelled.");
+        return Ok(());
+    }
+
+    update_magic_hat_data(
+        &program,
+        magichat_pubkey,
+        treasury_account,
+        magic_hat_data,
+        remaining_accounts,
+    )
+}
+
+/// Checks that the number of items is not changed on a deployed magic hat.
+///
+/// The account size is fixed at initialize time based on the number of items,
+/// so the supply can only be changed when the magic hat uses hidden settings
+/// (no config lines are stored).
+fn check_items_available(
+    args: &UpdateArgs,
+    magic_hat_state: &MagicHat,
+    magic_hat_data: &MagicHatData,
+) -> Result<()> {
+    let current = magic_hat_state.data.items_available;
+    let new = magic_hat_data.items_available;
+
+    if current == new {
+        return Ok(());
+    }
+
+    if magic_hat_state.data.hidden_settings.is_none() {
+        let error = anyhow!(
+            "Number of items cannot be changed from {} to {}: the account size is fixed at \
+             deploy time, a new deploy is required to change the supply",
+            current,
+            new
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    if new < magic_hat_state.items_redeemed {
+        let error = anyhow!(
+            "Number of items cannot be lower than the {} item(s) already minted",
+            magic_hat_state.items_redeemed
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    println!(
+        "\n{}",
+        style(format!(
+            "The number of items will change from {} to {} (hidden settings).",
+            current, new
+        ))
+        .yellow()
+    );
+
+    if !args.yes
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt("Change the number of items?")
+            .default(false)
+            .interact()?
+    {
+        let error = anyhow!("Update cancelled: number of items not confirmed");
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+fn update_request<'a>(
+    program: &'a Program,
+    magichat_pubkey: Pubkey,
+    treasury_account: Pubkey,
+    magic_hat_data: MagicHatData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> RequestBuilder<'a> {
     let mut builder = program
         .request()
         .accounts(nft_accounts::UpdateMagicHat {
@@ -131,10 +410,28 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
         }
     }
 
+    builder
+}
+
+fn update_magic_hat_data(
+    program: &Program,
+    magichat_pubkey: Pubkey,
+    treasury_account: Pubkey,
+    magic_hat_data: MagicHatData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Result<()> {
+    let builder = update_request(
+        program,
+        magichat_pubkey,
+        treasury_account,
+        magic_hat_data,
+        remaining_accounts,
+    );
+
     let pb = spinner_with_style();
     pb.set_message("Sending update transaction...");
 
-    let update_signature = builder.send()?;
+    let update_signature = builder.send().map_err(decode_program_error)?;
 
     pb.finish_with_message(format!(
         "{} {}",
@@ -142,30 +439,479 @@ pub fn process_update(args: UpdateArgs) -> Result<()> {
         update_signature
     ));
 
-    if let Some(new_authority) = args.new_authority {
-        let pb = spinner_with_style();
-        pb.set_message("Sending update authority transaction...");
-
-        let new_authority_pubkey = Pubkey::from_str(&new_authority)?;
-        let builder = program
-            .request()
-            .accounts(nft_accounts::UpdateMagicHat {
-                magic_hat: magichat_pubkey,
-                authority: program.payer(),
-                wallet: treasury_account,
-            })
-            .args(nft_instruction::UpdateAuthority {
-                new_authority: Some(new_authority_pubkey),
-            });
-
-        let authority_signature = builder.send()?;
-        pb.finish_with_message(format!(
-            "{} {}",
-            style("Authority signature:").bold(),
-            authority_signature
+    Ok(())
+}
+
+/// Simulate the update transaction without sending it, printing the program
+/// logs and the resulting magic hat settings.
+fn simulate_update(
+    program: &Program,
+    magichat_pubkey: Pubkey,
+    treasury_account: Pubkey,
+    magic_hat_data: MagicHatData,
+    remaining_accounts: Vec<AccountMeta>,
+    items_redeemed: u64,
+    token_decimals: Option<u8>,
+) -> Result<()> {
+    let instructions = update_request(
+        program,
+        magichat_pubkey,
+        treasury_account,
+        magic_hat_data.clone(),
+        remaining_accounts,
+    )
+    .instructions()?;
+
+    let pb = spinner_with_style();
+    pb.set_message("Simulating update transaction...");
+
+    let tx = Transaction::new_with_payer(&instructions, Some(&program.payer()));
+    let result = program
+        .rpc()
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+
+    pb.finish_and_clear();
+
+    let logs = result.logs.unwrap_or_default();
+
+    println!("{}", style("Program logs:").bold());
+    for log in &logs {
+        println!("  {}", log);
+    }
+
+    if let Some(units) = result.units_consumed {
+        println!("\n{} {}", style("Compute units consumed:").bold(), units);
+    }
+
+    if let Some(err) = result.err {
+        let error = match describe_transaction_error(&err, &logs) {
+            Some(description) => anyhow!("Update simulation failed: {}\n{}", err, description),
+            None => anyhow!("Update simulation failed: {}", err),
+        };
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    println!("\n{}", style("Update simulation succeeded").green().bold());
+    println!("\n{}", style("Resulting Magic Hat settings:").bold());
+    println!(" {}", style(":").dim());
+    print_magic_hat_settings(
+        &magic_hat_data,
+        items_redeemed,
+        Utc::now().timestamp(),
+        token_decimals,
+    )?;
+
+    Ok(())
+}
+
+/// Return the fields that differ between the on-chain data and the new data,
+/// formatted as `(field, old, new)`.
+fn diff_magic_hat_data(
+    current: &MagicHatData,
+    new: &MagicHatData,
+    format_current_price: &dyn Fn(u64) -> String,
+    format_new_price: &dyn Fn(u64) -> String,
+) -> Vec<(&'static str, String, String)> {
+    let fields = vec![
+        ("uuid", current.uuid.clone(), new.uuid.clone()),
+        (
+            "price",
+            format_current_price(current.price),
+            format_new_price(new.price),
+        ),
+        ("symbol", current.symbol.clone(), new.symbol.clone()),
+        (
+            "seller fee basis points",
+            current.seller_fee_basis_points.to_string(),
+            new.seller_fee_basis_points.to_string(),
+        ),
+        (
+            "max supply",
+            current.max_supply.to_string(),
+            new.max_supply.to_string(),
+        ),
+        (
+            "is mutable",
+            current.is_mutable.to_string(),
+            new.is_mutable.to_string(),
+        ),
+        (
+            "retain authority",
+            current.retain_authority.to_string(),
+            new.retain_authority.to_string(),
+        ),
+        (
+            "go live date",
+            format_date(current.go_live_date),
+            format_date(new.go_live_date),
+        ),
+        (
+            "end settings",
+            format_end_settings(&current.end_settings),
+            format_end_settings(&new.end_settings),
+        ),
+        (
+            "creators",
+            format_creators(&current.creators),
+            format_creators(&new.creators),
+        ),
+        (
+            "whitelist mint settings",
+            format_whitelist_mint_settings(&current.whitelist_mint_settings, format_current_price),
+            format_whitelist_mint_settings(&new.whitelist_mint_settings, format_new_price),
+        ),
+        (
+            "hidden settings",
+            format_hidden_settings(&current.hidden_settings),
+            format_hidden_settings(&new.hidden_settings),
+        ),
+        (
+            "items available",
+            current.items_available.to_string(),
+            new.items_available.to_string(),
+        ),
+        (
+            "gatekeeper",
+            format_gatekeeper(&current.gatekeeper),
+            format_gatekeeper(&new.gatekeeper),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .collect()
+}
+
+/// Returns a copy of the magic hat data in a canonical form, so that values
+/// differing only in creators order or trailing null padding compare equal.
+fn normalize_magic_hat_data(data: &MagicHatData) -> MagicHatData {
+    let trim = |value: &str| value.trim_end_matches(char::from(0)).to_string();
+
+    let mut data = data.clone();
+    data.symbol = trim(&data.symbol);
+    data.creators
+        .sort_by_key(|creator| creator.address.to_string());
+
+    if let Some(ref mut hidden_settings) = data.hidden_settings {
+        hidden_settings.name = trim(&hidden_settings.name);
+        hidden_settings.uri = trim(&hidden_settings.uri);
+    }
+
+    data
+}
+
+fn format_date(date: Option<i64>) -> String {
+    match date {
+        Some(date) => NaiveDateTime::from_timestamp(date, 0)
+            .format("%a %B %e %Y %H:%M:%S UTC")
+            .to_string(),
+        None => "none".to_string(),
+    }
+}
+
+fn format_end_settings(end_settings: &Option<magic_hat::EndSettings>) -> String {
+    match end_settings {
+        Some(end_settings) => match end_settings.end_setting_type {
+            magic_hat::EndSettingType::Date => {
+                format!("date {}", format_date(Some(end_settings.number as i64)))
+            }
+            magic_hat::EndSettingType::Amount => format!("amount {}", end_settings.number),
+        },
+        None => "none".to_string(),
+    }
+}
+
+fn format_creators(creators: &[magic_hat::Creator]) -> String {
+    creators
+        .iter()
+        .map(|creator| format!("{} ({}%)", creator.address, creator.share))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn format_whitelist_mint_settings(
+    settings: &Option<magic_hat::WhitelistMintSettings>,
+    format_price: &dyn Fn(u64) -> String,
+) -> String {
+    match settings {
+        Some(settings) => format!(
+            "{}, mint {}, presale {}, discount price {}",
+            if settings.mode == magic_hat::WhitelistMintMode::BurnEveryTime {
+                "burn every time"
+            } else {
+                "never burn"
+            },
+            settings.mint,
+            settings.presale,
+            match settings.discount_price {
+                Some(price) => format_price(price),
+                None => "none".to_string(),
+            }
+        ),
+        None => "none".to_string(),
+    }
+}
+
+fn format_hidden_settings(settings: &Option<magic_hat::HiddenSettings>) -> String {
+    match settings {
+        Some(settings) => format!(
+            "name {}, uri {}, hash {}",
+            settings.name,
+            settings.uri,
+            String::from_utf8_lossy(&settings.hash)
+        ),
+        None => "none".to_string(),
+    }
+}
+
+fn format_gatekeeper(gatekeeper: &Option<magic_hat::GatekeeperConfig>) -> String {
+    match gatekeeper {
+        Some(gatekeeper) => format!(
+            "network {}, expire on use {}",
+            gatekeeper.gatekeeper_network, gatekeeper.expire_on_use
+        ),
+        None => "none".to_string(),
+    }
+}
+
+/// Determines the treasury wallet of the update.
+///
+/// The current on-chain wallet is kept unless a new one is explicitly requested
+/// with `--treasury` or recomputed from the config file with `--update-treasury`.
+fn resolve_treasury_account(
+    program: &Program,
+    args: &UpdateArgs,
+    config_data: Option<&ConfigData>,
+    magic_hat_state: &MagicHat,
+    spl_token: Option<Pubkey>,
+) -> Result<Pubkey> {
+    let treasury = match args.treasury {
+        Some(ref treasury) => match parse_address(treasury) {
+            Ok(treasury_account) => {
+                if spl_token.is_some() {
+                    // validates the spl token wallet to receive proceedings from SPL token payments
+                    check_spl_token_account(program, &treasury_account.to_string())?;
+                }
+                Some(treasury_account)
+            }
+            Err(err) => {
+                let error = anyhow!("Failed to parse treasury: {} ({})", treasury, err);
+                error!("{:?}", error);
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+
+    let config_treasury = if treasury.is_none() && args.update_treasury {
+        let config_data = match config_data {
+            Some(config_data) => config_data,
+            None => return Err(anyhow!("--update-treasury requires the config file")),
+        };
+
+        let treasury_account = config_treasury_account(
+            config_data.spl_token,
+            config_data.spl_token_account,
+            config_data.sol_treasury_account,
+            program.payer(),
+        )?;
+
+        if let Some(spl_token) = config_data.spl_token {
+            // validates the mint address of the token accepted as payment
+            check_spl_token(program, &spl_token.to_string())?;
+            // validates the spl token wallet to receive proceedings from SPL token payments
+            check_spl_token_account(program, &treasury_account.to_string())?;
+        }
+
+        Some(treasury_account)
+    } else {
+        None
+    };
+
+    select_treasury_account(
+        treasury,
+        config_treasury,
+        magic_hat_state.wallet,
+        magic_hat_state.token_mint,
+        spl_token,
+    )
+    .map_err(|error| {
+        error!("{:?}", error);
+        error
+    })
+}
+
+/// Selects the treasury wallet among the explicit treasury, the treasury computed
+/// from the config file and the current wallet, which is only kept when the
+/// payment token does not change.
+fn select_treasury_account(
+    treasury: Option<Pubkey>,
+    config_treasury: Option<Pubkey>,
+    current_wallet: Pubkey,
+    current_token: Option<Pubkey>,
+    spl_token: Option<Pubkey>,
+) -> Result<Pubkey> {
+    if let Some(treasury) = treasury.or(config_treasury) {
+        return Ok(treasury);
+    }
+
+    // the current wallet only receives payments in the current token
+    if spl_token != current_token {
+        return Err(anyhow!(
+            "Payment token changes from {} to {}, specify the new treasury with --treasury \
+             or --update-treasury",
+            format_token(current_token),
+            format_token(spl_token)
         ));
     }
 
+    Ok(current_wallet)
+}
+
+/// Treasury wallet from the payment settings of the config file: the SPL token
+/// account (the associated token account of the payer by default) for SPL token
+/// payments, the SOL treasury account (the payer by default) otherwise.
+fn config_treasury_account(
+    spl_token: Option<Pubkey>,
+    spl_token_account: Option<Pubkey>,
+    sol_treasury_account: Option<Pubkey>,
+    payer: Pubkey,
+) -> Result<Pubkey> {
+    match spl_token {
+        Some(spl_token) => {
+            if sol_treasury_account.is_some() {
+                return Err(anyhow!("If spl-token-account or spl-token is set then sol-treasury-account cannot be set"));
+            }
+
+            Ok(spl_token_account
+                .unwrap_or_else(|| get_associated_token_address(&payer, &spl_token)))
+        }
+        None => Ok(sol_treasury_account.unwrap_or(payer)),
+    }
+}
+
+/// Formats a price stored on chain in SOL or in whole tokens of the payment mint
+/// (`decimals` are the decimals of the mint).
+fn format_token_price(price: u64, token_mint: Option<Pubkey>, decimals: Option<u8>) -> String {
+    match (token_mint, decimals) {
+        (Some(token_mint), Some(decimals)) => format!(
+            "{} {} ({} base units)",
+            Price::from_base_units(price, decimals),
+            token_mint,
+            price
+        ),
+        (Some(token_mint), None) => format!("{} base units of {}", price, token_mint),
+        (None, _) => format!("◎ {}", Price::from_lamports(price)),
+    }
+}
+
+fn format_token(token_mint: Option<Pubkey>) -> String {
+    match token_mint {
+        Some(token_mint) => token_mint.to_string(),
+        None => "SOL".to_string(),
+    }
+}
+
+/// Override the fields specified by flags.
+fn apply_field_flags(
+    client: &Client,
+    args: &UpdateArgs,
+    data: &mut MagicHatData,
+    spl_token: Option<Pubkey>,
+    timezone: Option<&str>,
+) -> Result<()> {
+    if let Some(ref price) = args.price {
+        data.price = parse_price(client, price, spl_token)?;
+    }
+
+    if let Some(ref go_live_date) = args.go_live_date {
+        data.go_live_date = Some(go_live_date_as_timestamp(go_live_date, timezone)?);
+    }
+
+    if let Some(seller_fee_basis_points) = args.seller_fee_basis_points {
+        if seller_fee_basis_points > 10_000 {
+            return Err(anyhow!(
+                "Invalid seller fee basis points: {} (maximum is 10000)",
+                seller_fee_basis_points
+            ));
+        }
+        data.seller_fee_basis_points = seller_fee_basis_points;
+    }
+
+    if let Some(is_mutable) = args.is_mutable {
+        data.is_mutable = is_mutable;
+    }
+
+    if let Some(retain_authority) = args.retain_authority {
+        data.retain_authority = retain_authority;
+    }
+
+    if let Some(ref symbol) = args.symbol {
+        if symbol.len() > MAX_SYMBOL_LENGTH {
+            return Err(anyhow!(
+                "Symbol too long: {} (maximum is {} characters)",
+                symbol,
+                MAX_SYMBOL_LENGTH
+            ));
+        }
+        data.symbol = symbol.clone();
+    }
+
+    if args.has_hidden_settings_flags() {
+        // the account of a magic hat without hidden settings is sized for the
+        // config lines, so hidden settings can only be changed, not added
+        let hidden_settings = match data.hidden_settings {
+            Some(ref mut hidden_settings) => hidden_settings,
+            None => {
+                return Err(anyhow!(
+                    "Magic Hat does not use hidden settings, they cannot be added after deploy"
+                ))
+            }
+        };
+
+        if let Some(ref name) = args.hidden_name {
+            if name.len() > MAX_NAME_LENGTH {
+                return Err(anyhow!(
+                    "Hidden settings name too long: {} (maximum is {} characters)",
+                    name,
+                    MAX_NAME_LENGTH
+                ));
+            }
+            hidden_settings.name = name.clone();
+        }
+
+        if let Some(ref uri) = args.hidden_uri {
+            if uri.len() > MAX_URI_LENGTH {
+                return Err(anyhow!(
+                    "Hidden settings uri too long: {} (maximum is {} characters)",
+                    uri,
+                    MAX_URI_LENGTH
+                ));
+            }
+            hidden_settings.uri = uri.clone();
+        }
+
+        if let Some(ref hash) = args.hidden_hash {
+            if hash.len() != 32 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!(
+                    "Invalid hidden settings hash: {} (must be 32 hexadecimal characters)",
+                    hash
+                ));
+            }
+            hidden_settings.hash.copy_from_slice(hash.as_bytes());
+        }
+    }
+
     Ok(())
 }
 
@@ -175,17 +921,20 @@ fn create_magic_hat_data(
     magic_hat: MagicHatData,
 ) -> Result<MagicHatData> {
     info!("{:?}", config.go_live_date);
-    let go_live_date = Some(go_live_date_as_timestamp(&config.go_live_date)?);
+    let go_live_date = config.go_live_timestamp()?;
 
     let end_settings = config
         .end_settings
         .as_ref()
         .map(|s| s.into_magichat_format());
 
-    let whitelist_mint_settings = config
-        .whitelist_mint_settings
-        .as_ref()
-        .map(|s| s.into_magichat_format());
+    // the discount price is charged in the payment token
+    let whitelist_mint_settings = match config.whitelist_mint_settings {
+        Some(ref settings) => {
+            Some(settings.into_magichat_format(token_decimals(client, config.spl_token)?)?)
+        }
+        None => None,
+    };
 
     let hidden_settings = config
         .hidden_settings
@@ -216,8 +965,125 @@ fn create_magic_hat_data(
         creators,
         whitelist_mint_settings,
         hidden_settings,
-        items_available: config.number,
+        // an auto number keeps the items of the deployed magic hat
+        items_available: config.number.unwrap_or(magic_hat.items_available),
         gatekeeper,
     };
     Ok(data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_treasury_for_spl_token_payments() {
+        let payer = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+
+        // the token account of the config is used
+        assert_eq!(
+            config_treasury_account(Some(token), Some(token_account), None, payer).unwrap(),
+            token_account
+        );
+        // the associated token account of the payer by default
+        assert_eq!(
+            config_treasury_account(Some(token), None, None, payer).unwrap(),
+            get_associated_token_address(&payer, &token)
+        );
+        // a SOL treasury cannot receive token payments
+        assert!(
+            config_treasury_account(Some(token), None, Some(Pubkey::new_unique()), payer).is_err()
+        );
+    }
+
+    #[test]
+    fn config_treasury_for_sol_payments() {
+        let payer = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+
+        assert_eq!(
+            config_treasury_account(None, None, Some(treasury), payer).unwrap(),
+            treasury
+        );
+        assert_eq!(
+            config_treasury_account(None, None, None, payer).unwrap(),
+            payer
+        );
+    }
+
+    #[test]
+    fn current_wallet_is_kept_by_default() {
+        let wallet = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+
+        // native SOL payments
+        assert_eq!(
+            select_treasury_account(None, None, wallet, None, None).unwrap(),
+            wallet
+        );
+        // SPL token payments
+        assert_eq!(
+            select_treasury_account(None, None, wallet, Some(token), Some(token)).unwrap(),
+            wallet
+        );
+    }
+
+    #[test]
+    fn explicit_treasury_takes_precedence() {
+        let wallet = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        let config_treasury = Pubkey::new_unique();
+
+        assert_eq!(
+            select_treasury_account(Some(treasury), Some(config_treasury), wallet, None, None)
+                .unwrap(),
+            treasury
+        );
+        assert_eq!(
+            select_treasury_account(None, Some(config_treasury), wallet, None, None).unwrap(),
+            config_treasury
+        );
+    }
+
+    #[test]
+    fn payment_token_change_requires_a_treasury() {
+        let wallet = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+
+        // from native SOL to SPL token payments
+        assert!(select_treasury_account(None, None, wallet, None, Some(token)).is_err());
+        // from SPL token to native SOL payments
+        assert!(select_treasury_account(None, None, wallet, Some(token), None).is_err());
+
+        assert_eq!(
+            select_treasury_account(Some(token_account), None, wallet, None, Some(token)).unwrap(),
+            token_account
+        );
+    }
+
+    #[test]
+    fn prices_are_formatted_with_their_own_token() {
+        let token = Pubkey::new_unique();
+
+        assert_eq!(format_token_price(1_500_000_000, None, None), "◎ 1.5");
+        assert_eq!(
+            format_token_price(1_500_000, Some(token), Some(6)),
+            format!("1.5 {} (1500000 base units)", token)
+        );
+
+        // the same base units are a different price after a payment token switch
+        let format_current_price = |price| format_token_price(price, None, None);
+        let format_new_price = |price| format_token_price(price, Some(token), Some(6));
+        assert_ne!(
+            format_current_price(1_000_000_000),
+            format_new_price(1_000_000_000)
+        );
+        assert_eq!(
+            format_new_price(1_000_000_000),
+            format!("1000 {} (1000000000 base units)", token)
+        );
+    }
+}
@@ -51,6 +51,9 @@ impl AssetPair {
             on_chain: false,
             animation_hash: self.animation_hash,
             animation_link: self.animation,
+            extra_files: None,
+            image_receipt: None,
+            metadata_receipt: None,
         }
     }
 }
@@ -253,6 +256,7 @@ pub fn get_updated_metadata(
     metadata_file: &str,
     image_link: &str,
     animation_link: Option<String>,
+    extra_files: Option<&IndexMap<String, String>>,
 ) -> Result<String> {
     let mut metadata: Metadata = {
         let m = OpenOptions::new()
@@ -275,6 +279,10 @@ pub fn get_updated_metadata(
                 }
             }
         }
+        // additional files already uploaded are referenced by their link
+        if let Some(link) = extra_files.and_then(|extra_files| extra_files.get(&file.uri)) {
+            file.uri = link.to_string();
+        }
     }
 
     metadata.image = image_link.to_string();
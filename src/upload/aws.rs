@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use aws_sdk_s3::{types::ByteStream, Client};
+use aws_sdk_s3::{types::ByteStream, Client, Region};
 use bs58;
 use console::style;
 use futures::future::select_all;
@@ -25,6 +25,7 @@ struct ObjectInfo {
     content_type: String,
     bucket: String,
     animation_link: Option<String>,
+    extra_files: Option<IndexMap<String, String>>,
 }
 
 pub struct AWSHandler {
@@ -33,37 +34,51 @@ pub struct AWSHandler {
 }
 
 impl AWSHandler {
-    /// Initialize a new AWSHandler.
-    pub async fn initialize(config_data: &ConfigData) -> Result<AWSHandler> {
-        let shared_config = aws_config::load_from_env().await;
+    /// Initialize a new AWSHandler. The region of the settings takes precedence over
+    /// the one of the AWS environment.
+    pub async fn initialize(settings: &AwsConfig) -> Result<AWSHandler> {
+        let shared_config = match &settings.region {
+            Some(region) => {
+                aws_config::from_env()
+                    .region(Region::new(region.clone()))
+                    .load()
+                    .await
+            }
+            None => aws_config::load_from_env().await,
+        };
         let client = Client::new(&shared_config);
 
-        if let Some(aws_s3_bucket) = &config_data.aws_s3_bucket {
-            Ok(AWSHandler {
-                client: Arc::new(client),
-                bucket: aws_s3_bucket.to_string(),
-            })
-        } else {
-            Err(anyhow!("Missing 'awsS3Bucket' value in config file."))
-        }
+        Ok(AWSHandler {
+            client: Arc::new(client),
+            bucket: settings.bucket.clone(),
+        })
     }
 
-    /// Send an object to AWS and wait for a response.
-    async fn send_to_aws(aws_client: Arc<Client>, info: ObjectInfo) -> Result<(String, String)> {
+    /// Send an object to AWS and wait for a response, returning the key and ETag of
+    /// the object.
+    async fn send_to_aws(
+        aws_client: Arc<Client>,
+        info: ObjectInfo,
+    ) -> Result<(String, String, String)> {
         let data = match info.data_type {
             DataType::Image => fs::read(&info.file_path)?,
             DataType::Metadata => {
                 // replaces the image link without modifying the original file to avoid
                 // changing the hash of the metadata file
-                get_updated_metadata(&info.file_path, &info.image_link, info.animation_link)?
-                    .into_bytes()
+                get_updated_metadata(
+                    &info.file_path,
+                    &info.image_link,
+                    info.animation_link,
+                    info.extra_files.as_ref(),
+                )?
+                .into_bytes()
             }
             DataType::Animation => fs::read(&info.file_path)?,
         };
 
         let key = bs58::encode(&info.file_path).into_string();
 
-        aws_client
+        let output = aws_client
             .put_object()
             .bucket(info.bucket)
             .key(&key)
@@ -71,8 +86,13 @@ impl AWSHandler {
             .content_type(info.content_type)
             .send()
             .await?;
+        let etag = output
+            .e_tag()
+            .unwrap_or_default()
+            .trim_matches('"')
+            .to_string();
 
-        Ok((info.asset_id, key))
+        Ok((info.asset_id, key, etag))
     }
 }
 
@@ -172,6 +192,7 @@ impl UploadHandler for AWSHandler {
                 content_type: content_type.clone(),
                 bucket: self.bucket.clone(),
                 animation_link: cache_item.animation_link.clone(),
+                extra_files: cache_item.extra_files.clone(),
             });
         }
 
@@ -199,9 +220,16 @@ impl UploadHandler for AWSHandler {
                         // cache item to update
                         let item = cache.items.0.get_mut(&val.0).unwrap();
 
+                        // the receipt of a re-upload replaces the previous one
                         match data_type {
-                            DataType::Image => item.image_link = link,
-                            DataType::Metadata => item.metadata_link = link,
+                            DataType::Image => {
+                                item.image_link = link;
+                                item.image_receipt = Some(val.2);
+                            }
+                            DataType::Metadata => {
+                                item.metadata_link = link;
+                                item.metadata_receipt = Some(val.2);
+                            }
                             DataType::Animation => item.animation_link = Some(link),
                         }
                         // updates the progress bar
@@ -41,6 +41,7 @@ struct TxInfo {
     file_path: String,
     image_link: String,
     animation_link: Option<String>,
+    extra_files: Option<IndexMap<String, String>>,
     data_type: DataType,
     tag: Vec<Tag>,
 }
@@ -52,25 +53,21 @@ pub struct BundlrHandler {
 }
 
 impl BundlrHandler {
-    /// Initialize a new BundlrHandler.
+    /// Initialize a new BundlrHandler, using the node of the settings or the node of
+    /// the cluster.
     pub async fn initialize(
-        config_data: &ConfigData,
+        settings: &BundlrConfig,
         laddu_config: &LadduConfig,
     ) -> Result<BundlrHandler> {
-        let client = setup_client(laddu_config)?;
-        let program = client.program(MAGIC_HAT_ID);
-        let solana_cluster: Cluster = get_cluster(program.rpc())?;
-
-        let bundlr_node = match config_data.upload_method {
-            UploadMethod::Bundlr => match solana_cluster {
-                Cluster::Devnet => BUNDLR_DEVNET,
-                Cluster::Mainnet => BUNDLR_MAINNET,
-            },
-            _ => {
-                return Err(anyhow!(format!(
-                    "Upload method '{}' currently unsupported!",
-                    &config_data.upload_method.to_string()
-                )))
+        let bundlr_node = match &settings.node {
+            Some(node) => node.trim_end_matches('/'),
+            None => {
+                let client = setup_client(laddu_config)?;
+                let program = client.program(MAGIC_HAT_ID);
+                match get_cluster(program.rpc())? {
+                    Cluster::Devnet => BUNDLR_DEVNET,
+                    Cluster::Mainnet => BUNDLR_MAINNET,
+                }
             }
         };
 
@@ -207,6 +204,7 @@ impl BundlrHandler {
                     &tx_info.file_path,
                     &tx_info.image_link,
                     tx_info.animation_link,
+                    tx_info.extra_files.as_ref(),
                 )?
                 .into_bytes()
             }
@@ -264,11 +262,15 @@ impl UploadHandler for BundlrHandler {
                 None
             };
 
-            let updated_metadata =
-                match get_updated_metadata(&item.metadata, &mock_uri, mock_animation_uri.clone()) {
-                    Ok(metadata) => metadata.into_bytes().len() as u64,
-                    Err(err) => return Err(err),
-                };
+            let updated_metadata = match get_updated_metadata(
+                &item.metadata,
+                &mock_uri,
+                mock_animation_uri.clone(),
+                None,
+            ) {
+                Ok(metadata) => metadata.into_bytes().len() as u64,
+                Err(err) => return Err(err),
+            };
 
             total_size += HEADER_SIZE + cmp::max(MINIMUM_SIZE, updated_metadata);
         }
@@ -431,6 +433,7 @@ impl UploadHandler for BundlrHandler {
                 data_type: data_type.clone(),
                 tag: vec![laddu_tag.clone(), image_tag.clone()],
                 animation_link: cache_item.animation_link.clone(),
+                extra_files: cache_item.extra_files.clone(),
             });
         }
 
@@ -454,13 +457,20 @@ impl UploadHandler for BundlrHandler {
 
                     if res.is_ok() {
                         let val = res?;
-                        let link = format!("https://arweave.net/{}", val.clone().1);
+                        let link = format!("https://arweave.net/{}", val.1);
                         // cache item to update
                         let item = cache.items.0.get_mut(&val.0).unwrap();
 
+                        // the receipt of a re-upload replaces the previous one
                         match data_type {
-                            DataType::Image => item.image_link = link,
-                            DataType::Metadata => item.metadata_link = link,
+                            DataType::Image => {
+                                item.image_link = link;
+                                item.image_receipt = Some(val.1);
+                            }
+                            DataType::Metadata => {
+                                item.metadata_link = link;
+                                item.metadata_receipt = Some(val.1);
+                            }
                             DataType::Animation => item.animation_link = Some(link),
                         }
                         // updates the progress bar
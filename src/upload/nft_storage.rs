@@ -53,6 +53,7 @@ struct UploadInfo {
     image_link: String,
     data_type: DataType,
     animation_link: Option<String>,
+    extra_files: Option<IndexMap<String, String>>,
 }
 
 pub struct NftStorageHandler {
@@ -61,37 +62,29 @@ pub struct NftStorageHandler {
 
 impl NftStorageHandler {
     /// Initialize a new NftStorageHandler.
-    pub async fn initialize(config_data: &ConfigData) -> Result<NftStorageHandler> {
-        if let Some(auth_token) = &config_data.nft_storage_auth_token {
-            let client_builder = Client::builder();
-
-            let mut headers = header::HeaderMap::new();
-            let bearer_value = format!("Bearer {}", auth_token);
-            let mut auth_value = header::HeaderValue::from_str(&bearer_value)?;
-            auth_value.set_sensitive(true);
-            headers.insert(header::AUTHORIZATION, auth_value);
-
-            let client = client_builder
-                .default_headers(headers)
-                .timeout(Duration::from_secs(TIMEOUT))
-                .build()?;
-
-            let url = format!("{}/", NFT_STORAGE_API_URL);
-            let response = client.get(url).send().await?;
-
-            match response.status() {
-                StatusCode::OK => Ok(NftStorageHandler {
-                    client: Arc::new(client),
-                }),
-                StatusCode::UNAUTHORIZED => {
-                    Err(anyhow!("Invalid nft.storage authentication token."))
-                }
-                code => Err(anyhow!("Could not initialize nft.storage client: {code}")),
-            }
-        } else {
-            Err(anyhow!(
-                "Missing 'nftStorageAuthToken' value in config file."
-            ))
+    pub async fn initialize(settings: &NftStorageConfig) -> Result<NftStorageHandler> {
+        let client_builder = Client::builder();
+
+        let mut headers = header::HeaderMap::new();
+        let bearer_value = format!("Bearer {}", settings.auth_token);
+        let mut auth_value = header::HeaderValue::from_str(&bearer_value)?;
+        auth_value.set_sensitive(true);
+        headers.insert(header::AUTHORIZATION, auth_value);
+
+        let client = client_builder
+            .default_headers(headers)
+            .timeout(Duration::from_secs(TIMEOUT))
+            .build()?;
+
+        let url = format!("{}/", NFT_STORAGE_API_URL);
+        let response = client.get(url).send().await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(NftStorageHandler {
+                client: Arc::new(client),
+            }),
+            StatusCode::UNAUTHORIZED => Err(anyhow!("Invalid nft.storage authentication token.")),
+            code => Err(anyhow!("Could not initialize nft.storage client: {code}")),
         }
     }
 
@@ -105,8 +98,13 @@ impl NftStorageHandler {
             DataType::Metadata => {
                 // replaces the image link without modifying the original file to avoid
                 // changing the hash of the metadata file
-                get_updated_metadata(&info.file_path, &info.image_link, info.animation_link)?
-                    .into_bytes()
+                get_updated_metadata(
+                    &info.file_path,
+                    &info.image_link,
+                    info.animation_link,
+                    info.extra_files.as_ref(),
+                )?
+                .into_bytes()
             }
             DataType::Animation => fs::read(&info.file_path)?,
         };
@@ -215,6 +213,7 @@ impl UploadHandler for NftStorageHandler {
                 image_link: cache_item.image_link.clone(),
                 data_type: data_type.clone(),
                 animation_link: cache_item.animation_link.clone(),
+                extra_files: cache_item.extra_files.clone(),
             });
         }
 
@@ -242,9 +241,16 @@ impl UploadHandler for NftStorageHandler {
                         // cache item to update
                         let item = cache.items.0.get_mut(&val.0).unwrap();
 
+                        // the receipt of a re-upload replaces the previous one
                         match data_type {
-                            DataType::Image => item.image_link = link,
-                            DataType::Metadata => item.metadata_link = link,
+                            DataType::Image => {
+                                item.image_link = link;
+                                item.image_receipt = Some(val.1);
+                            }
+                            DataType::Metadata => {
+                                item.metadata_link = link;
+                                item.metadata_receipt = Some(val.1);
+                            }
                             DataType::Animation => item.animation_link = Some(link),
                         }
                         // updates the progress bar
@@ -10,7 +10,7 @@ use std::{
 
 use crate::cache::{load_cache, Cache};
 use crate::common::*;
-use crate::config::{data::LadduConfig, get_config_data, UploadMethod};
+use crate::config::{data::LadduConfig, get_config_data, UploadConfig, AUTO_NUMBER};
 use crate::upload::bundlr::BundlrHandler;
 use crate::upload::*;
 use crate::utils::*;
@@ -75,6 +75,11 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
     // creates/loads the cache
     let mut cache = load_cache(&args.cache, true)?;
 
+    // an auto number is resolved from the assets and kept in the cache for deploy
+    if config_data.number.is_none() {
+        cache.program.number = Some(asset_pairs.len() as u64);
+    }
+
     // list of indices to upload
     // 0: image
     // 1: metadata
@@ -100,10 +105,10 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                 if !&item.image_hash.eq(&pair.image_hash) || item.image_link.is_empty() {
                     // we replace the entire item to trigger the image and metadata upload
                     let item_clone = item.clone();
-                    cache
-                        .items
-                        .0
-                        .insert(index.to_string(), pair.clone().into_cache_item());
+                    // additional files already uploaded are kept, they are not re-uploaded
+                    let mut new_item = pair.clone().into_cache_item();
+                    new_item.extra_files = item_clone.extra_files.clone();
+                    cache.items.0.insert(index.to_string(), new_item);
                     // we need to upload both image/metadata
                     indices.image.push(*index);
                     indices.metadata.push(*index);
@@ -113,10 +118,9 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                     }
                 } else if animation_conditon {
                     // we replace the entire item to trigger the image and metadata upload
-                    cache
-                        .items
-                        .0
-                        .insert(index.to_string(), pair.clone().into_cache_item());
+                    let mut new_item = pair.clone().into_cache_item();
+                    new_item.extra_files = item.extra_files.clone();
+                    cache.items.0.insert(index.to_string(), new_item);
                     // we need to upload both image/metadata
                     indices.animation.push(*index);
                     indices.image.push(*index);
@@ -187,6 +191,14 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         "Found {} image/metadata pair(s), uploading files:",
         asset_pairs.len()
     );
+    if config_data.number.is_none() {
+        println!(
+            "Number of items is \"{}\", using {} detected in '{}'",
+            AUTO_NUMBER,
+            asset_pairs.len(),
+            args.assets_dir
+        );
+    }
     println!("+--------------------+");
     println!("| images    | {:>6} |", indices.image.len());
     println!("| metadata  | {:>6} |", indices.metadata.len());
@@ -226,17 +238,16 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         let pb = spinner_with_style();
         pb.set_message("Connecting...");
 
-        let handler = match config_data.upload_method {
-            UploadMethod::Bundlr => Box::new(
-                BundlrHandler::initialize(&get_config_data(&args.config)?, &laddu_config).await?,
-            ) as Box<dyn UploadHandler>,
-            UploadMethod::AWS => {
-                Box::new(AWSHandler::initialize(&get_config_data(&args.config)?).await?)
+        let handler = match &config_data.upload {
+            UploadConfig::Bundlr(settings) => {
+                Box::new(BundlrHandler::initialize(settings, &laddu_config).await?)
                     as Box<dyn UploadHandler>
             }
-            UploadMethod::NftStorage => {
-                Box::new(NftStorageHandler::initialize(&get_config_data(&args.config)?).await?)
-                    as Box<dyn UploadHandler>
+            UploadConfig::Aws(settings) => {
+                Box::new(AWSHandler::initialize(settings).await?) as Box<dyn UploadHandler>
+            }
+            UploadConfig::NftStorage(settings) => {
+                Box::new(NftStorageHandler::initialize(settings).await?) as Box<dyn UploadHandler>
             }
         };
 
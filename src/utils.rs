@@ -10,9 +10,21 @@ pub use anyhow::{anyhow, Result};
 pub use indicatif::{ProgressBar, ProgressStyle};
 use solana_client::rpc_client::RpcClient;
 use spl_token::state::{Account, Mint};
-use std::str::FromStr;
+use std::{
+    env, fs,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
 
+use crate::cache::with_suffix;
 use crate::config::data::Cluster;
+use crate::constants::CLUSTER_PRESETS;
+use crate::setup::config_cluster;
+
+/// Environment variable that, when set, skips the interactive confirmations
+/// (same as `--yes`)
+pub const NON_INTERACTIVE_ENV: &str = "LADDU_NON_INTERACTIVE";
 
 /// Hash for devnet cluster
 pub const DEVNET_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
@@ -38,6 +50,30 @@ pub fn get_cluster(rpc_client: RpcClient) -> Result<Cluster> {
     }
 }
 
+/// Name of the cluster of an RPC url, shown by the commands sending transactions:
+/// the cluster of the config, a preset, or the cluster of the genesis hash.
+pub fn cluster_name(rpc_url: &str) -> String {
+    if let Some((cluster, cluster_rpc_url)) = config_cluster() {
+        if cluster_rpc_url == rpc_url {
+            return cluster;
+        }
+    }
+
+    let trimmed = rpc_url.trim_end_matches('/');
+    if let Some((name, _)) = CLUSTER_PRESETS
+        .iter()
+        .find(|(_, preset)| preset.trim_end_matches('/') == trimmed)
+    {
+        return name.to_string();
+    }
+
+    match get_cluster(RpcClient::new(rpc_url.to_string())) {
+        Ok(Cluster::Mainnet) => "mainnet-beta".to_string(),
+        Ok(Cluster::Devnet) => "devnet".to_string(),
+        Err(_) => "unknown cluster".to_string(),
+    }
+}
+
 /// Check that the mint token is a valid address.
 pub fn check_spl_token(program: &Program, input: &str) -> Result<Mint> {
     let pubkey = Pubkey::from_str(input)?;
@@ -102,3 +138,27 @@ pub fn progress_bar_with_style(len: u64) -> ProgressBar {
     );
     pb
 }
+
+/// Whether the global non-interactive mode is enabled through the environment.
+pub fn is_non_interactive() -> bool {
+    match env::var(NON_INTERACTIVE_ENV) {
+        Ok(value) => !matches!(value.to_lowercase().as_str(), "" | "0" | "false"),
+        Err(_) => false,
+    }
+}
+
+/// Write the contents to a temporary file next to `path` and rename it over
+/// the destination, so an interrupted write never leaves a truncated file.
+pub fn write_atomic<P: AsRef<Path>>(path: P, contents: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let tmp_path = with_suffix(path, ".tmp");
+
+    let mut writer = BufWriter::new(fs::File::create(&tmp_path)?);
+    writer.write_all(contents)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+
+    fs::rename(&tmp_path, path)?;
+
+    Ok(())
+}
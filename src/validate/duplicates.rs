@@ -0,0 +1,95 @@
+use anyhow::Result;
+use rayon::prelude::*;
+use ring::digest::{Context, SHA256};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::utils::progress_bar_with_style;
+use crate::validate::errors::ValidateError;
+
+/// Reports the names used by more than one item, with the indices using them.
+pub fn find_duplicate_names(names: &[(String, String)]) -> Vec<ValidateError> {
+    let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for (index, name) in names {
+        groups.entry(name).or_default().push(index);
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(name, indices)| ValidateError::DuplicateName(name.to_string(), indices.join(", ")))
+        .collect()
+}
+
+/// Hashes the images of the items in parallel and reports the ones with identical
+/// content under different indices.
+pub fn find_duplicate_images(images: &[(String, PathBuf)]) -> Vec<ValidateError> {
+    let pb = progress_bar_with_style(images.len() as u64);
+    pb.set_message(format!("Hashing {} image file(s)...", images.len()));
+
+    let hashes: Vec<(String, Result<String>)> = images
+        .par_iter()
+        .map(|(index, path)| {
+            let hash = hash_file(path);
+            pb.inc(1);
+            (index.clone(), hash)
+        })
+        .collect();
+
+    pb.finish_and_clear();
+
+    let mut errors = Vec::new();
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for ((index, hash), (_, path)) in hashes.into_iter().zip(images) {
+        match hash {
+            Ok(hash) => groups.entry(hash).or_default().push(index),
+            Err(error) => errors.push(ValidateError::UnreadableImage(
+                path.display().to_string(),
+                error.to_string(),
+            )),
+        }
+    }
+
+    errors.extend(
+        groups
+            .into_values()
+            .filter(|indices| indices.len() > 1)
+            .map(|indices| ValidateError::DuplicateImage(indices.join(", "))),
+    );
+
+    errors
+}
+
+/// SHA-256 of a file, read in chunks to avoid loading it into memory.
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut context = Context::new(&SHA256);
+    update_with_file(&mut context, path)?;
+
+    Ok(to_hex(context.finish().as_ref()))
+}
+
+/// Feeds the content of a file to a digest, in chunks.
+pub fn update_with_file(context: &mut Context, path: &Path) -> Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buffer = [0u8; 8192];
+
+    loop {
+        let count = reader.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        context.update(&buffer[..count]);
+    }
+
+    Ok(())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -34,4 +34,183 @@ pub enum ValidateError {
 
     #[error("Missing collection field")]
     MissingCollection,
+
+    #[error("Asset file '{0}' does not exist.")]
+    MissingAssetFile(String),
+
+    #[error("Asset file '{0}' has an unsupported extension (expected one of: {1}).")]
+    UnsupportedAssetExtension(String, String),
+
+    #[error("Asset file '{0}' does not match the metadata file index '{1}'.")]
+    AssetIndexMismatch(String, String),
+
+    #[error("Missing properties.files entry for '{0}'.")]
+    MissingPropertiesFile(String),
+
+    #[error("Invalid type '{1}' in properties.files entry for '{0}' (expected: {2}).")]
+    InvalidPropertiesFileType(String, String, String),
+
+    #[error("Attribute {0} is not an object: {1}")]
+    InvalidAttribute(usize, String),
+
+    #[error("Attribute {0} is missing a string trait_type: {1}")]
+    MissingTraitType(usize, String),
+
+    #[error("Attribute {0} value must be a string or a number: {1}")]
+    InvalidAttributeValue(usize, String),
+
+    #[error("Attribute {0} has an unknown display_type (expected one of: {2}): {1}")]
+    InvalidDisplayType(usize, String, String),
+
+    #[error("Attribute {0} is a duplicate trait_type/value pair: {1}")]
+    DuplicateAttribute(usize, String),
+
+    #[error("Empty attributes array")]
+    EmptyAttributes,
+
+    #[error("Missing properties.creators field")]
+    MissingCreators,
+
+    #[error("Creators [{0}] do not match the config creators [{1}]")]
+    CreatorsMismatch(String, String),
+
+    #[error("File name '{0}' is not a valid index")]
+    InvalidAssetIndex(String),
+
+    #[error("Missing metadata file(s) for indices: {0}")]
+    MissingIndices(String),
+
+    #[error("Index {0} is used by multiple files: {1}")]
+    DuplicateIndex(usize, String),
+
+    #[error("Image file '{0}' has no matching metadata file")]
+    MissingMetadataFile(String),
+
+    #[error("Metadata file '{0}' has no matching image file")]
+    MissingImageFile(String),
+
+    #[error("Found {0} metadata file(s) but the config number is {1}")]
+    ItemCountMismatch(usize, u64),
+
+    #[error("Unknown field '{0}'{1}")]
+    UnknownField(String, String),
+
+    #[error("Invalid MIME type '{1}' in properties.files entry for '{0}'.")]
+    InvalidFileType(String, String),
+
+    #[error("Invalid category '{0}' (expected one of: {1}).")]
+    InvalidCategory(String, String),
+
+    #[error("Category '{0}' does not match the file types: {1}")]
+    CategoryMismatch(String, String),
+
+    #[error("File '{0}' in properties.files does not exist in the assets directory.")]
+    MissingPropertiesFileAsset(String),
+
+    #[error("Symbol '{0}' does not match the expected symbol '{1}'")]
+    SymbolMismatch(String, String),
+
+    #[error("Seller fee basis points {0} do not match the expected value {1}")]
+    SellerFeeMismatch(u16, u16),
+
+    #[error("Image '{0}' has {1} bytes (maximum is {2})")]
+    ImageTooLarge(String, u64, u64),
+
+    #[error("Image '{0}' is {1}x{2} pixels (maximum dimension is {3})")]
+    ImageDimensionTooLarge(String, u32, u32, u32),
+
+    #[error("Could not read image '{0}': {1}")]
+    UnreadableImage(String, String),
+
+    #[error("Animation file '{0}' has no animation_url in the metadata")]
+    UnreferencedAnimationFile(String),
+
+    #[error("Animation '{0}' has {1} bytes (maximum is {2})")]
+    AnimationTooLarge(String, u64, u64),
+
+    #[error("Category '{0}' does not match animation '{1}' (expected: {2})")]
+    AnimationCategoryMismatch(String, String, String),
+
+    #[error("Missing collection file '{0}'")]
+    MissingCollectionFile(String),
+
+    #[error("Name '{0}' is used by multiple items: {1}")]
+    DuplicateName(String, String),
+
+    #[error("Identical image content for items: {0}")]
+    DuplicateImage(String),
+
+    #[error("Seller fee basis points are 0 (no royalties)")]
+    NoRoyalties,
+
+    #[error("Seller fee basis points {0} are above the threshold of {1}")]
+    HighSellerFee(u16, u16),
+
+    #[error("Hidden settings name '{0}' exceeds {1} chars (the item number is appended on mint)")]
+    HiddenNameTooLong(String, usize),
+
+    #[error("Hidden settings hash has {0} chars (expected 32)")]
+    InvalidHiddenHash(usize),
+
+    #[error("Missing hidden settings in the config file")]
+    MissingHiddenSettings,
+}
+
+impl ValidateError {
+    /// Name of the error variant, used to group errors in the validation report.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ValidateError::MissingOrEmptyAssetsDirectory => "MissingOrEmptyAssetsDirectory",
+            ValidateError::InvalidAssetsDirectory => "InvalidAssetsDirectory",
+            ValidateError::NameTooLong => "NameTooLong",
+            ValidateError::SymbolTooLong => "SymbolTooLong",
+            ValidateError::UrlTooLong => "UrlTooLong",
+            ValidateError::InvalidCreatorAddress(..) => "InvalidCreatorAddress",
+            ValidateError::InvalidCreatorShare => "InvalidCreatorShare",
+            ValidateError::InvalidSellerFeeBasisPoints => "InvalidSellerFeeBasisPoints",
+            ValidateError::MissingAnimationUrl => "MissingAnimationUrl",
+            ValidateError::MissingExternalUrl => "MissingExternalUrl",
+            ValidateError::MissingCollection => "MissingCollection",
+            ValidateError::MissingAssetFile(..) => "MissingAssetFile",
+            ValidateError::UnsupportedAssetExtension(..) => "UnsupportedAssetExtension",
+            ValidateError::AssetIndexMismatch(..) => "AssetIndexMismatch",
+            ValidateError::MissingPropertiesFile(..) => "MissingPropertiesFile",
+            ValidateError::InvalidPropertiesFileType(..) => "InvalidPropertiesFileType",
+            ValidateError::InvalidAttribute(..) => "InvalidAttribute",
+            ValidateError::MissingTraitType(..) => "MissingTraitType",
+            ValidateError::InvalidAttributeValue(..) => "InvalidAttributeValue",
+            ValidateError::InvalidDisplayType(..) => "InvalidDisplayType",
+            ValidateError::DuplicateAttribute(..) => "DuplicateAttribute",
+            ValidateError::EmptyAttributes => "EmptyAttributes",
+            ValidateError::MissingCreators => "MissingCreators",
+            ValidateError::CreatorsMismatch(..) => "CreatorsMismatch",
+            ValidateError::InvalidAssetIndex(..) => "InvalidAssetIndex",
+            ValidateError::MissingIndices(..) => "MissingIndices",
+            ValidateError::DuplicateIndex(..) => "DuplicateIndex",
+            ValidateError::MissingMetadataFile(..) => "MissingMetadataFile",
+            ValidateError::MissingImageFile(..) => "MissingImageFile",
+            ValidateError::ItemCountMismatch(..) => "ItemCountMismatch",
+            ValidateError::UnknownField(..) => "UnknownField",
+            ValidateError::InvalidFileType(..) => "InvalidFileType",
+            ValidateError::InvalidCategory(..) => "InvalidCategory",
+            ValidateError::CategoryMismatch(..) => "CategoryMismatch",
+            ValidateError::MissingPropertiesFileAsset(..) => "MissingPropertiesFileAsset",
+            ValidateError::SymbolMismatch(..) => "SymbolMismatch",
+            ValidateError::SellerFeeMismatch(..) => "SellerFeeMismatch",
+            ValidateError::ImageTooLarge(..) => "ImageTooLarge",
+            ValidateError::ImageDimensionTooLarge(..) => "ImageDimensionTooLarge",
+            ValidateError::UnreadableImage(..) => "UnreadableImage",
+            ValidateError::UnreferencedAnimationFile(..) => "UnreferencedAnimationFile",
+            ValidateError::AnimationTooLarge(..) => "AnimationTooLarge",
+            ValidateError::AnimationCategoryMismatch(..) => "AnimationCategoryMismatch",
+            ValidateError::MissingCollectionFile(..) => "MissingCollectionFile",
+            ValidateError::DuplicateName(..) => "DuplicateName",
+            ValidateError::DuplicateImage(..) => "DuplicateImage",
+            ValidateError::NoRoyalties => "NoRoyalties",
+            ValidateError::HighSellerFee(..) => "HighSellerFee",
+            ValidateError::HiddenNameTooLong(..) => "HiddenNameTooLong",
+            ValidateError::InvalidHiddenHash(..) => "InvalidHiddenHash",
+            ValidateError::MissingHiddenSettings => "MissingHiddenSettings",
+        }
+    }
 }
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::{
+    fs::{self, File},
+    path::Path,
+};
+
+use crate::validate::parser::IMAGE_EXTENSIONS;
+
+/// Applies the safe, mechanical fixes to a metadata file:
+///
+/// - missing `symbol` is set to the config symbol (when a config is provided);
+/// - `seller_fee_basis_points` given as a string is converted to a number;
+/// - leading/trailing whitespace is removed from `name` and `symbol`;
+/// - `image` pointing at a missing file is replaced by the only image with the
+///   same index in the assets directory (also in `properties.files`).
+///
+/// Returns a description of each change made. Anything ambiguous is left as is
+/// and reported by the validation.
+pub fn fix_metadata(
+    metadata: &mut Value,
+    index: &str,
+    file_names: &[String],
+    symbol: Option<&str>,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let object = match metadata.as_object_mut() {
+        Some(object) => object,
+        None => return changes,
+    };
+
+    if let Some(symbol) = symbol {
+        if !object.contains_key("symbol") {
+            object.insert("symbol".to_string(), Value::from(symbol));
+            changes.push(format!("set missing symbol to '{symbol}'"));
+        }
+    }
+
+    let seller_fee_basis_points = object
+        .get("seller_fee_basis_points")
+        .and_then(Value::as_str)
+        .and_then(|value| value.trim().parse::<u16>().ok());
+
+    if let Some(seller_fee_basis_points) = seller_fee_basis_points {
+        object.insert(
+            "seller_fee_basis_points".to_string(),
+            Value::from(seller_fee_basis_points),
+        );
+        changes.push(format!(
+            "converted seller_fee_basis_points \"{seller_fee_basis_points}\" to a number"
+        ));
+    }
+
+    for field in ["name", "symbol"] {
+        if let Some(Value::String(value)) = object.get_mut(field) {
+            let trimmed = value.trim().to_string();
+            if trimmed.len() != value.len() {
+                changes.push(format!("trimmed whitespace in {field} '{trimmed}'"));
+                *value = trimmed;
+            }
+        }
+    }
+
+    let image = object
+        .get("image")
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    if let Some(image) = image {
+        if !image.contains("://") && !file_names.contains(&image) {
+            let candidates: Vec<&String> = file_names
+                .iter()
+                .filter(|file_name| {
+                    let file = Path::new(file_name);
+                    file.file_stem().and_then(|s| s.to_str()) == Some(index)
+                        && file
+                            .extension()
+                            .and_then(|e| e.to_str())
+                            .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                            .unwrap_or(false)
+                })
+                .collect();
+
+            if let [replacement] = candidates.as_slice() {
+                object.insert("image".to_string(), Value::from(replacement.as_str()));
+
+                let files = object
+                    .get_mut("properties")
+                    .and_then(|p| p.get_mut("files"))
+                    .and_then(Value::as_array_mut);
+
+                for file in files.into_iter().flatten() {
+                    if file.get("uri").and_then(Value::as_str) == Some(image.as_str()) {
+                        file["uri"] = Value::from(replacement.as_str());
+                    }
+                }
+
+                changes.push(format!("changed image '{image}' to '{replacement}'"));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Reads a metadata file, applies the fixes and, unless it is a dry run, writes
+/// it back (keeping a `.bak` copy when `backup` is set).
+pub fn fix_file(
+    path: &Path,
+    file_names: &[String],
+    symbol: Option<&str>,
+    dry_run: bool,
+    backup: bool,
+) -> Result<Vec<String>> {
+    let mut metadata: Value = serde_json::from_reader(File::open(path)?)?;
+
+    let index = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let changes = fix_metadata(&mut metadata, index, file_names, symbol);
+
+    if !changes.is_empty() && !dry_run {
+        if backup {
+            fs::copy(path, path.with_extension("json.bak"))?;
+        }
+        serde_json::to_writer_pretty(File::create(path)?, &metadata)?;
+    }
+
+    Ok(changes)
+}
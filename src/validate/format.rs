@@ -1,7 +1,8 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
-use crate::validate::{errors, parser};
+use crate::config::data::Creator;
+use crate::validate::{errors::ValidateError, parser};
 
 #[derive(Debug, Clone, Deserialize, Default, Serialize)]
 pub struct Metadata {
@@ -12,44 +13,129 @@ pub struct Metadata {
     pub image: String,
     pub animation_url: Option<String>,
     pub external_url: Option<String>,
-    pub attributes: Vec<Attribute>,
+    pub attributes: Vec<serde_json::Value>,
     pub collection: Option<Collection>,
     pub properties: Property,
 }
 
 impl Metadata {
-    pub fn validate(self) -> Result<()> {
-        parser::check_name(&self.name)?;
-        parser::check_symbol(&self.symbol)?;
-        parser::check_url(&self.image)?;
-        parser::check_seller_fee_basis_points(self.seller_fee_basis_points)?;
+    /// Runs every check on the metadata, returning all the errors found instead of
+    /// stopping at the first one.
+    pub fn validate(&self, strict: bool) -> Vec<ValidateError> {
+        let mut errors = Vec::new();
 
-        Ok(())
-    }
+        if strict {
+            match &self.animation_url {
+                Some(animation_url) => push_error(&mut errors, parser::check_url(animation_url)),
+                None => errors.push(ValidateError::MissingAnimationUrl),
+            }
+
+            if self.collection.is_none() {
+                errors.push(ValidateError::MissingCollection);
+            }
+
+            match &self.external_url {
+                Some(external_url) => push_error(&mut errors, parser::check_url(external_url)),
+                None => errors.push(ValidateError::MissingExternalUrl),
+            }
+        }
+
+        push_error(&mut errors, parser::check_name(&self.name));
+        push_error(&mut errors, parser::check_symbol(&self.symbol));
+        push_error(&mut errors, parser::check_url(&self.image));
+        push_error(
+            &mut errors,
+            parser::check_seller_fee_basis_points(self.seller_fee_basis_points),
+        );
+        push_error(&mut errors, parser::check_attributes(&self.attributes));
 
-    pub fn validate_strict(self) -> Result<()> {
-        if self.animation_url.is_none() {
-            return Err(errors::ValidateError::MissingAnimationUrl.into());
-        } else {
-            parser::check_url(&self.animation_url.unwrap())?;
+        if strict && self.attributes.is_empty() {
+            errors.push(ValidateError::EmptyAttributes);
         }
 
-        if self.collection.is_none() {
-            return Err(errors::ValidateError::MissingCollection.into());
+        errors
+    }
+
+    /// Checks the image (and animation) files referenced by the metadata file at
+    /// `metadata_path` against its sibling assets. In strict mode, the assets must
+    /// also be listed in `properties.files`.
+    pub fn validate_assets(&self, metadata_path: &Path, strict: bool) -> Vec<ValidateError> {
+        let mut errors = Vec::new();
+
+        let assets_dir = metadata_path.parent().unwrap_or_else(|| Path::new("."));
+        let index = metadata_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+
+        push_error(
+            &mut errors,
+            parser::check_asset_file(assets_dir, index, &self.image, parser::IMAGE_EXTENSIONS),
+        );
+
+        if let Some(animation_url) = &self.animation_url {
+            push_error(
+                &mut errors,
+                parser::check_asset_file(
+                    assets_dir,
+                    index,
+                    animation_url,
+                    parser::ANIMATION_EXTENSIONS,
+                ),
+            );
+
+            if strict {
+                push_error(
+                    &mut errors,
+                    parser::check_animation_category(
+                        animation_url,
+                        self.properties.category.as_deref(),
+                    ),
+                );
+            }
         }
 
-        if self.external_url.is_none() {
-            return Err(errors::ValidateError::MissingExternalUrl.into());
-        } else {
-            parser::check_url(&self.external_url.unwrap())?;
+        errors.extend(parser::check_properties(&self.properties, assets_dir));
+
+        if strict {
+            push_error(
+                &mut errors,
+                parser::check_properties_file(&self.properties.files, &self.image),
+            );
+
+            if let Some(animation_url) = &self.animation_url {
+                push_error(
+                    &mut errors,
+                    parser::check_properties_file(&self.properties.files, animation_url),
+                );
+            }
         }
 
-        parser::check_name(&self.name)?;
-        parser::check_symbol(&self.symbol)?;
-        parser::check_url(&self.image)?;
-        parser::check_seller_fee_basis_points(self.seller_fee_basis_points)?;
+        errors
+    }
+
+    /// Compares `properties.creators` against the creators of the config file,
+    /// ignoring their order. Returns `false` when the metadata has no creators
+    /// (only an error in strict mode).
+    pub fn validate_creators(
+        &self,
+        expected: &[Creator],
+        strict: bool,
+    ) -> Result<bool, ValidateError> {
+        match &self.properties.creators {
+            Some(creators) => {
+                parser::check_creators(creators, expected)?;
+                Ok(true)
+            }
+            None if strict => Err(ValidateError::MissingCreators),
+            None => Ok(false),
+        }
+    }
+}
 
-        Ok(())
+fn push_error(errors: &mut Vec<ValidateError>, result: Result<(), ValidateError>) {
+    if let Err(error) = result {
+        errors.push(error);
     }
 }
 
@@ -62,13 +148,10 @@ pub struct Collection {
 #[derive(Debug, Clone, Deserialize, Default, Serialize)]
 pub struct Property {
     pub files: Vec<FileAttr>,
-    pub category: String,
-}
-
-#[derive(Debug, Clone, Deserialize, Default, Serialize)]
-pub struct Attribute {
-    pub trait_type: String,
-    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creators: Option<Vec<CreatorAttr>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default, Serialize)]
@@ -77,3 +160,9 @@ pub struct FileAttr {
     #[serde(rename = "type")]
     pub file_type: String,
 }
+
+#[derive(Debug, Clone, Deserialize, Default, Serialize)]
+pub struct CreatorAttr {
+    pub address: String,
+    pub share: u8,
+}
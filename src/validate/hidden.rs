@@ -0,0 +1,95 @@
+use anyhow::Result;
+use ring::digest::{Context, SHA256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::data::HiddenSettings;
+use crate::constants::COLLECTION_FILENAME;
+use crate::validate::{
+    duplicates::{hash_file, to_hex},
+    errors::ValidateError,
+    parser::{check_url, MAX_NAME_LENGTH},
+};
+
+/// Length of the hidden settings hash.
+pub const HIDDEN_HASH_LENGTH: usize = 32;
+
+/// Checks the hidden settings placeholder: the name must leave room for the item
+/// number appended on mint, the URI must fit on-chain and the hash must have
+/// exactly 32 characters.
+pub fn check_hidden_settings(settings: &HiddenSettings, number: u64) -> Vec<ValidateError> {
+    let mut errors = Vec::new();
+
+    let digits = number.max(1).to_string().len();
+    if settings.name().len() + digits > MAX_NAME_LENGTH {
+        errors.push(ValidateError::HiddenNameTooLong(
+            settings.name().to_string(),
+            MAX_NAME_LENGTH - digits.min(MAX_NAME_LENGTH),
+        ));
+    }
+
+    if let Err(error) = check_url(settings.uri()) {
+        errors.push(error);
+    }
+
+    if settings.hash().len() != HIDDEN_HASH_LENGTH {
+        errors.push(ValidateError::InvalidHiddenHash(settings.hash().len()));
+    }
+
+    errors
+}
+
+/// Hash of the reveal set: SHA-256 over the hex SHA-256 of each metadata file in
+/// index order, truncated to the length of the hidden settings hash. The per-file
+/// hashes are the same as the `metadataHash` of the cache items, so the hash can
+/// be computed from either the assets or the cache.
+pub fn reveal_hash(paths: &[PathBuf]) -> Result<String> {
+    let mut paths: Vec<&PathBuf> = paths.iter().collect();
+    paths.sort_by_key(|path| {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(usize::MAX)
+    });
+
+    let hashes = paths
+        .into_iter()
+        .map(|path| hash_file(path))
+        .collect::<Result<Vec<String>>>()?;
+
+    Ok(reveal_hash_from_hashes(&hashes))
+}
+
+/// Hash of the reveal set from the metadata files of an assets directory (the
+/// collection metadata is not part of the reveal set).
+pub fn reveal_hash_from_dir(assets_dir: &Path) -> Result<String> {
+    let paths: Vec<PathBuf> = fs::read_dir(assets_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|e| e.to_str()) == Some("json")
+                && path.file_stem().and_then(|s| s.to_str()) != Some(COLLECTION_FILENAME)
+        })
+        .collect();
+
+    if paths.is_empty() {
+        return Err(ValidateError::MissingOrEmptyAssetsDirectory.into());
+    }
+
+    reveal_hash(&paths)
+}
+
+/// Hash of the reveal set from the (ordered) hex hashes of its metadata files.
+pub fn reveal_hash_from_hashes<S: AsRef<str>>(hashes: &[S]) -> String {
+    let mut context = Context::new(&SHA256);
+    for hash in hashes {
+        context.update(hash.as_ref().as_bytes());
+    }
+
+    let mut hash = to_hex(context.finish().as_ref());
+    hash.truncate(HIDDEN_HASH_LENGTH);
+
+    hash
+}
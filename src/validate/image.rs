@@ -0,0 +1,144 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::validate::errors::ValidateError;
+
+/// Size and dimension limits for the images of the assets directory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageLimits {
+    pub max_bytes: Option<u64>,
+    pub max_dimension: Option<u32>,
+}
+
+impl ImageLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_bytes.is_none() && self.max_dimension.is_none()
+    }
+}
+
+/// Checks the file size and the dimensions of an image against the limits. Only
+/// the image header is read to find its dimensions.
+pub fn check_image(path: &Path, limits: &ImageLimits) -> Vec<ValidateError> {
+    let mut errors = Vec::new();
+    let file = path.display().to_string();
+
+    if let Some(max_bytes) = limits.max_bytes {
+        match path.metadata() {
+            Ok(metadata) if metadata.len() > max_bytes => {
+                errors.push(ValidateError::ImageTooLarge(
+                    file.clone(),
+                    metadata.len(),
+                    max_bytes,
+                ));
+            }
+            Ok(_) => {}
+            Err(error) => {
+                errors.push(ValidateError::UnreadableImage(file, error.to_string()));
+                return errors;
+            }
+        }
+    }
+
+    if let Some(max_dimension) = limits.max_dimension {
+        match image_dimensions(path) {
+            Ok((width, height)) if width > max_dimension || height > max_dimension => {
+                errors.push(ValidateError::ImageDimensionTooLarge(
+                    file,
+                    width,
+                    height,
+                    max_dimension,
+                ));
+            }
+            Ok(_) => {}
+            Err(error) => errors.push(ValidateError::UnreadableImage(file, error.to_string())),
+        }
+    }
+
+    errors
+}
+
+/// Reads the width and height of a png, jpeg or gif image from its header.
+pub fn image_dimensions(path: &Path) -> Result<(u32, u32)> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut signature = [0u8; 8];
+    reader.read_exact(&mut signature)?;
+
+    if signature == [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A] {
+        // IHDR chunk: length (4) + type (4) + width (4) + height (4)
+        let mut ihdr = [0u8; 16];
+        reader.read_exact(&mut ihdr)?;
+
+        if &ihdr[4..8] != b"IHDR" {
+            return Err(anyhow!("Missing PNG IHDR chunk"));
+        }
+
+        let width = u32::from_be_bytes([ihdr[8], ihdr[9], ihdr[10], ihdr[11]]);
+        let height = u32::from_be_bytes([ihdr[12], ihdr[13], ihdr[14], ihdr[15]]);
+
+        Ok((width, height))
+    } else if &signature[..6] == b"GIF87a" || &signature[..6] == b"GIF89a" {
+        let width = u16::from_le_bytes([signature[6], signature[7]]);
+        let mut height = [0u8; 2];
+        reader.read_exact(&mut height)?;
+
+        Ok((width as u32, u16::from_le_bytes(height) as u32))
+    } else if signature[..2] == [0xFF, 0xD8] {
+        reader.seek(SeekFrom::Start(2))?;
+        jpeg_dimensions(&mut reader)
+    } else {
+        Err(anyhow!("Unsupported image format"))
+    }
+}
+
+/// Walks the jpeg segments until the start of frame, skipping the segment data.
+fn jpeg_dimensions<R: Read + Seek>(reader: &mut R) -> Result<(u32, u32)> {
+    let mut byte = [0u8; 1];
+
+    loop {
+        reader.read_exact(&mut byte)?;
+        if byte[0] != 0xFF {
+            return Err(anyhow!("Invalid JPEG marker"));
+        }
+
+        // markers can be padded with any number of 0xFF bytes
+        let mut marker = 0xFF;
+        while marker == 0xFF {
+            reader.read_exact(&mut byte)?;
+            marker = byte[0];
+        }
+
+        // standalone markers without a length
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            continue;
+        }
+
+        if marker == 0xD9 || marker == 0xDA {
+            return Err(anyhow!("Missing JPEG start of frame"));
+        }
+
+        let mut length = [0u8; 2];
+        reader.read_exact(&mut length)?;
+        let length = u16::from_be_bytes(length) as i64;
+
+        let is_start_of_frame =
+            (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker);
+
+        if is_start_of_frame {
+            // precision (1) + height (2) + width (2)
+            let mut frame = [0u8; 5];
+            reader.read_exact(&mut frame)?;
+
+            let height = u16::from_be_bytes([frame[1], frame[2]]);
+            let width = u16::from_be_bytes([frame[3], frame[4]]);
+
+            return Ok((width as u32, height as u32));
+        }
+
+        reader.seek(SeekFrom::Current(length - 2))?;
+    }
+}
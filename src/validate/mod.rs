@@ -1,9 +1,17 @@
+pub mod duplicates;
 pub mod errors;
+pub mod fix;
 pub mod format;
+pub mod hidden;
+pub mod image;
 pub mod parser;
 pub mod process;
 
+pub use duplicates::*;
 pub use errors::*;
+pub use fix::*;
 pub use format::*;
+pub use hidden::*;
+pub use image::*;
 pub use parser::*;
 pub use process::*;
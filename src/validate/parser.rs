@@ -1,6 +1,46 @@
 pub use mpl_token_metadata::state::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
 
-use crate::validate::errors::ValidateError;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+use crate::config::data::Creator;
+use crate::constants::COLLECTION_FILENAME;
+use crate::validate::{errors::ValidateError, CreatorAttr, FileAttr, Property};
+
+/// Supported image file extensions.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "gif"];
+
+/// Supported animation file extensions.
+pub const ANIMATION_EXTENSIONS: &[&str] = &["mp4", "mov", "webm", "glb", "gltf", "mp3", "html"];
+
+/// Known top-level metadata fields.
+pub const METADATA_FIELDS: &[&str] = &[
+    "name",
+    "symbol",
+    "description",
+    "seller_fee_basis_points",
+    "image",
+    "animation_url",
+    "external_url",
+    "attributes",
+    "collection",
+    "properties",
+];
+
+/// Known fields of the metadata `properties`.
+pub const PROPERTIES_FIELDS: &[&str] = &["files", "category", "creators"];
+
+/// Standard values of the metadata `properties.category`.
+pub const CATEGORIES: &[&str] = &["image", "video", "audio", "vr", "html"];
+
+/// Top-level MIME types accepted in `properties.files`.
+pub const MIME_TYPES: &[&str] = &["image", "video", "audio", "model", "text", "application"];
+
+/// Known attribute display types.
+pub const DISPLAY_TYPES: &[&str] = &["number", "boost_number", "boost_percentage", "date"];
 
 pub fn check_name(name: &str) -> Result<(), ValidateError> {
     if name.len() > MAX_NAME_LENGTH {
@@ -25,7 +65,495 @@ pub fn check_url(url: &str) -> Result<(), ValidateError> {
 
 pub fn check_seller_fee_basis_points(seller_fee_basis_points: u16) -> Result<(), ValidateError> {
     if seller_fee_basis_points > 10000 {
+        return Err(ValidateError::InvalidSellerFeeBasisPoints);
+    }
+    Ok(())
+}
+
+/// Flags seller fees that are valid but probably a mistake: no royalties or a fee
+/// above the sanity threshold. These are reported as warnings.
+pub fn check_seller_fee_sanity(
+    seller_fee_basis_points: u16,
+    threshold: u16,
+) -> Result<(), ValidateError> {
+    if seller_fee_basis_points == 0 {
+        return Err(ValidateError::NoRoyalties);
+    }
+    if seller_fee_basis_points > threshold {
+        return Err(ValidateError::HighSellerFee(
+            seller_fee_basis_points,
+            threshold,
+        ));
+    }
+    Ok(())
+}
+
+/// Checks the top-level and `properties` keys of a metadata file against the known
+/// fields, suggesting the closest known field for typos.
+pub fn check_unknown_fields(metadata: &Value) -> Vec<ValidateError> {
+    let mut errors = Vec::new();
+
+    if let Some(object) = metadata.as_object() {
+        for key in object.keys() {
+            if !METADATA_FIELDS.contains(&key.as_str()) {
+                errors.push(unknown_field(key, METADATA_FIELDS));
+            }
+        }
+    }
+
+    if let Some(properties) = metadata.get("properties").and_then(Value::as_object) {
+        for key in properties.keys() {
+            if !PROPERTIES_FIELDS.contains(&key.as_str()) {
+                errors.push(unknown_field(
+                    &format!("properties.{key}"),
+                    PROPERTIES_FIELDS,
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+fn unknown_field(key: &str, known: &[&str]) -> ValidateError {
+    let name = key.rsplit('.').next().unwrap_or(key);
+
+    // only suggests fields within 2 edits of the unknown key
+    let hint = known
+        .iter()
+        .map(|field| (edit_distance(name, field), field))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, field)| format!(" (did you mean '{field}'?)"))
+        .unwrap_or_default();
+
+    ValidateError::UnknownField(key.to_string(), hint)
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Checks that each attribute is an object with a string `trait_type`, a string or
+/// number `value` and an optional known `display_type`, and that there are no
+/// duplicate trait_type/value pairs.
+pub fn check_attributes(attributes: &[Value]) -> Result<(), ValidateError> {
+    let mut seen = HashSet::with_capacity(attributes.len());
+
+    for (index, attribute) in attributes.iter().enumerate() {
+        let object = attribute
+            .as_object()
+            .ok_or_else(|| ValidateError::InvalidAttribute(index, attribute.to_string()))?;
+
+        let trait_type = object
+            .get("trait_type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ValidateError::MissingTraitType(index, attribute.to_string()))?;
+
+        let value = match object.get("value") {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Number(value)) => value.to_string(),
+            _ => {
+                return Err(ValidateError::InvalidAttributeValue(
+                    index,
+                    attribute.to_string(),
+                ))
+            }
+        };
+
+        if let Some(display_type) = object.get("display_type") {
+            if !display_type
+                .as_str()
+                .map(|d| DISPLAY_TYPES.contains(&d))
+                .unwrap_or(false)
+            {
+                return Err(ValidateError::InvalidDisplayType(
+                    index,
+                    attribute.to_string(),
+                    DISPLAY_TYPES.join(", "),
+                ));
+            }
+        }
+
+        if !seen.insert((trait_type.to_string(), value)) {
+            return Err(ValidateError::DuplicateAttribute(
+                index,
+                attribute.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the `properties.files` MIME types, that the category is a standard value
+/// consistent with them, and that local files exist in the assets directory.
+pub fn check_properties(properties: &Property, assets_dir: &Path) -> Vec<ValidateError> {
+    let mut errors = Vec::new();
+
+    for file in &properties.files {
+        if !is_mime_type(&file.file_type) {
+            errors.push(ValidateError::InvalidFileType(
+                file.uri.clone(),
+                file.file_type.clone(),
+            ));
+        }
+
+        if !file.uri.contains("://") && !assets_dir.join(&file.uri).is_file() {
+            errors.push(ValidateError::MissingPropertiesFileAsset(file.uri.clone()));
+        }
+    }
+
+    if let Some(category) = &properties.category {
+        let expected = match category.as_str() {
+            "image" => "image/",
+            "video" => "video/",
+            "audio" => "audio/",
+            "vr" => "model/",
+            "html" => "text/html",
+            _ => {
+                errors.push(ValidateError::InvalidCategory(
+                    category.clone(),
+                    CATEGORIES.join(", "),
+                ));
+                return errors;
+            }
+        };
+
+        if !properties.files.is_empty()
+            && !properties
+                .files
+                .iter()
+                .any(|f| f.file_type.to_lowercase().starts_with(expected))
+        {
+            errors.push(ValidateError::CategoryMismatch(
+                category.clone(),
+                properties
+                    .files
+                    .iter()
+                    .map(|f| f.file_type.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Checks that a file type looks like `<type>/<subtype>` with a known top-level type.
+fn is_mime_type(file_type: &str) -> bool {
+    match file_type.to_lowercase().split_once('/') {
+        Some((kind, subtype)) => {
+            MIME_TYPES.contains(&kind)
+                && !subtype.is_empty()
+                && subtype
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        }
+        None => false,
+    }
+}
+
+/// Checks that the creators of a metadata file have the same addresses and shares
+/// as the expected creators, in any order.
+pub fn check_creators(creators: &[CreatorAttr], expected: &[Creator]) -> Result<(), ValidateError> {
+    let mut found: Vec<(String, u8)> = creators
+        .iter()
+        .map(|c| (c.address.clone(), c.share))
+        .collect();
+    let mut wanted: Vec<(String, u8)> = expected
+        .iter()
+        .map(|c| (c.address.to_string(), c.share))
+        .collect();
+
+    found.sort();
+    wanted.sort();
+
+    if found != wanted {
+        let describe = |creators: &[(String, u8)]| {
+            creators
+                .iter()
+                .map(|(address, share)| format!("{address} ({share}%)"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        return Err(ValidateError::CreatorsMismatch(
+            describe(&found),
+            describe(&wanted),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the shares of the creators add up to 100%.
+pub fn check_creator_shares(creators: &[CreatorAttr]) -> Result<(), ValidateError> {
+    let total: u32 = creators.iter().map(|c| c.share as u32).sum();
+
+    if !creators.is_empty() && total != 100 {
         return Err(ValidateError::InvalidCreatorShare);
     }
+
+    Ok(())
+}
+
+/// Checks that an asset referenced by a metadata file exists in the assets directory,
+/// has a supported extension and follows the `<index>.<ext>` naming convention.
+///
+/// Absolute URIs (e.g. already uploaded assets) are not checked.
+pub fn check_asset_file(
+    assets_dir: &Path,
+    index: &str,
+    file: &str,
+    extensions: &[&str],
+) -> Result<(), ValidateError> {
+    if file.contains("://") {
+        return Ok(());
+    }
+
+    let asset = Path::new(file);
+    let extension = asset
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    if !extensions.contains(&extension.as_str()) {
+        return Err(ValidateError::UnsupportedAssetExtension(
+            file.to_string(),
+            extensions.join(", "),
+        ));
+    }
+
+    if asset.file_stem().and_then(|s| s.to_str()) != Some(index) {
+        return Err(ValidateError::AssetIndexMismatch(
+            file.to_string(),
+            index.to_string(),
+        ));
+    }
+
+    if !assets_dir.join(asset).is_file() {
+        return Err(ValidateError::MissingAssetFile(file.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Checks that `properties.files` has an entry for the asset with a content type
+/// matching its extension.
+pub fn check_properties_file(files: &[FileAttr], file: &str) -> Result<(), ValidateError> {
+    let entry = files
+        .iter()
+        .find(|f| f.uri == file)
+        .ok_or_else(|| ValidateError::MissingPropertiesFile(file.to_string()))?;
+
+    let extension = Path::new(file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let expected = content_types(&extension);
+
+    if !expected.contains(&entry.file_type.to_lowercase().as_str()) {
+        return Err(ValidateError::InvalidPropertiesFileType(
+            file.to_string(),
+            entry.file_type.clone(),
+            expected.join(", "),
+        ));
+    }
+
     Ok(())
 }
+
+fn content_types(extension: &str) -> &'static [&'static str] {
+    match extension {
+        "png" => &["image/png"],
+        "jpg" => &["image/jpeg", "image/jpg"],
+        "gif" => &["image/gif"],
+        "mp4" => &["video/mp4"],
+        "mov" => &["video/quicktime", "video/mov"],
+        "webm" => &["video/webm"],
+        "glb" => &["model/gltf-binary"],
+        "gltf" => &["model/gltf+json"],
+        "mp3" => &["audio/mpeg", "audio/mp3"],
+        "html" => &["text/html"],
+        _ => &[],
+    }
+}
+
+/// Category expected for an animation file, based on its extension.
+fn animation_category(extension: &str) -> Option<&'static str> {
+    match extension {
+        "mp4" | "mov" | "webm" => Some("video"),
+        "mp3" => Some("audio"),
+        "glb" | "gltf" => Some("vr"),
+        "html" => Some("html"),
+        _ => None,
+    }
+}
+
+/// Checks that the category of the metadata matches the type of its animation file.
+pub fn check_animation_category(
+    animation_url: &str,
+    category: Option<&str>,
+) -> Result<(), ValidateError> {
+    let extension = Path::new(animation_url)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    if let Some(expected) = animation_category(&extension) {
+        if category != Some(expected) {
+            return Err(ValidateError::AnimationCategoryMismatch(
+                category.unwrap_or("none").to_string(),
+                animation_url.to_string(),
+                expected.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Animation files of the assets directory, by index.
+pub fn animation_files(file_names: &[String]) -> BTreeMap<usize, String> {
+    file_names
+        .iter()
+        .filter_map(|file_name| {
+            let path = Path::new(file_name);
+            let extension = path.extension()?.to_str()?.to_lowercase();
+
+            if !ANIMATION_EXTENSIONS.contains(&extension.as_str()) {
+                return None;
+            }
+
+            let index = path.file_stem()?.to_str()?.parse::<usize>().ok()?;
+            Some((index, file_name.clone()))
+        })
+        .collect()
+}
+
+/// Checks that the metadata files of the assets directory form a contiguous `0..n-1`
+/// range of indices, with one image per metadata file and no duplicated indices.
+/// When `expected` is set, the number of metadata files must also match it.
+pub fn check_asset_numbering(file_names: &[String], expected: Option<u64>) -> Vec<ValidateError> {
+    let mut errors = Vec::new();
+
+    let mut metadata: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    let mut images: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+
+    for file_name in file_names {
+        let path = Path::new(file_name);
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        let files = if extension == "json" {
+            &mut metadata
+        } else if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+            &mut images
+        } else {
+            continue;
+        };
+
+        // the collection files are not numbered items
+        if path.file_stem().and_then(|s| s.to_str()) == Some(COLLECTION_FILENAME) {
+            continue;
+        }
+
+        match path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<usize>().ok())
+        {
+            Some(index) => files.entry(index).or_default().push(file_name),
+            None => errors.push(ValidateError::InvalidAssetIndex(file_name.clone())),
+        }
+    }
+
+    for (index, files) in metadata.iter().chain(images.iter()) {
+        if files.len() > 1 {
+            errors.push(ValidateError::DuplicateIndex(*index, files.join(", ")));
+        }
+    }
+
+    let last = metadata
+        .keys()
+        .chain(images.keys())
+        .max()
+        .map(|last| last + 1)
+        .unwrap_or_default();
+    let missing: Vec<usize> = (0..last)
+        .filter(|index| !metadata.contains_key(index) && !images.contains_key(index))
+        .collect();
+
+    if !missing.is_empty() {
+        errors.push(ValidateError::MissingIndices(format_ranges(&missing)));
+    }
+
+    for (index, files) in &images {
+        if !metadata.contains_key(index) {
+            errors.push(ValidateError::MissingMetadataFile(files[0].to_string()));
+        }
+    }
+
+    for (index, files) in &metadata {
+        if !images.contains_key(index) {
+            errors.push(ValidateError::MissingImageFile(files[0].to_string()));
+        }
+    }
+
+    if let Some(number) = expected {
+        if metadata.len() as u64 != number {
+            errors.push(ValidateError::ItemCountMismatch(metadata.len(), number));
+        }
+    }
+
+    errors
+}
+
+/// Collapses a sorted list of indices into ranges (e.g. "3, 7-9, 1234").
+pub fn format_ranges(indices: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for &index in indices {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => ranges.push((index, index)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -2,19 +2,35 @@ use anyhow::Result;
 use console::style;
 use glob::glob;
 use rayon::prelude::*;
+use serde::Serialize;
 use std::{
+    collections::BTreeMap,
     fs::File,
-    path::Path,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
 };
 
 use crate::common::*;
+use crate::config::{
+    data::{ConfigData, Creator, HiddenSettings, AUTO_NUMBER},
+    parser::get_config_data,
+};
+use crate::upload::get_asset_pairs;
 use crate::utils::*;
 use crate::validate::*;
 
 pub struct ValidateArgs {
     pub assets_dir: String,
     pub strict: bool,
+    pub report: String,
+    pub config: Option<String>,
+    pub skip_image_checks: bool,
+    pub check_duplicates: bool,
+    pub allow_duplicate_names: bool,
+    pub seller_fee_threshold: u16,
+    pub hidden: bool,
+    pub fix: bool,
+    pub dry_run: bool,
+    pub no_backup: bool,
 }
 
 pub fn process_validate(args: ValidateArgs) -> Result<()> {
@@ -27,6 +43,37 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
 
     let assets_dir = Path::new(&args.assets_dir);
 
+    // the config file is used to cross-check the creators and the number of items
+    let config_data = match args.config {
+        Some(ref config) => Some(get_config_data(config)?),
+        None => None,
+    };
+
+    // for hidden drops, the placeholder is what gets minted and the assets
+    // directory is the (optional) reveal set
+    let hidden_settings = config_data.as_ref().and_then(|c| c.hidden_settings.clone());
+    let hidden = args.hidden || hidden_settings.is_some();
+
+    if hidden {
+        let hidden_settings = hidden_settings.ok_or(ValidateError::MissingHiddenSettings)?;
+        // an auto number is the size of the reveal set, when there is one
+        let number = match config_data.as_ref().map(|c| c.number) {
+            Some(Some(number)) => number,
+            _ => get_asset_pairs(&args.assets_dir)
+                .map(|pairs| pairs.len() as u64)
+                .unwrap_or_default(),
+        };
+        validate_hidden_settings(&hidden_settings, number)?;
+
+        if !assets_dir.exists() || assets_dir.read_dir()?.next().is_none() {
+            println!(
+                "\nNo reveal set found in '{}', skipping the assets validation.",
+                args.assets_dir
+            );
+            return Ok(());
+        }
+    }
+
     // missing or empty assets directory
     if !assets_dir.exists() || assets_dir.read_dir()?.next().is_none() {
         info!("Assets directory is missing or empty.");
@@ -38,82 +85,301 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
 
     let (paths, errors): (Vec<_>, Vec<_>) = glob(pattern)?.into_iter().partition(Result::is_ok);
 
-    let pb = spinner_with_style();
-    pb.enable_steady_tick(120);
+    // the collection metadata is validated on its own
+    let mut paths: Vec<_> = paths
+        .into_iter()
+        .map(Result::unwrap)
+        .filter(|path| !is_collection_file(path))
+        .collect();
+    // glob returns the paths in lexicographic order (10.json before 2.json)
+    sort_by_file_index(&mut paths);
+    let path_errors: Vec<_> = errors.into_iter().map(Result::unwrap_err).collect();
+
+    let creators = config_data.as_ref().map(|c| c.creators.as_slice());
+
+    let image_limits = config_data
+        .as_ref()
+        .map(|c| ImageLimits {
+            max_bytes: c.max_image_bytes,
+            max_dimension: c.max_image_dimension,
+        })
+        .filter(|limits| !args.skip_image_checks && !limits.is_empty());
+
+    let file_names: Vec<String> = assets_dir
+        .read_dir()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(String::from))
+        .collect();
+    if args.fix {
+        fix_files(&paths, &file_names, config_data.as_ref(), &args)?;
+    }
+
+    let animations = animation_files(&file_names);
+    let max_animation_bytes = config_data.as_ref().and_then(|c| c.max_animation_bytes);
+
+    let pb = progress_bar_with_style(paths.len() as u64);
     pb.set_message(format!("Validating {} metadata file(s)...", paths.len()));
 
-    let paths: Vec<_> = paths.into_iter().map(Result::unwrap).collect();
-    let path_errors: Vec<_> = errors.into_iter().map(Result::unwrap_err).collect();
+    let results = validate_files(
+        &paths,
+        args.strict,
+        creators,
+        image_limits.as_ref(),
+        max_animation_bytes,
+        &pb,
+    );
 
-    let file_open_errors = Arc::new(Mutex::new(Vec::new()));
-    let deserialize_errors = Arc::new(Mutex::new(Vec::new()));
-    let validate_errors = Arc::new(Mutex::new(Vec::new()));
+    pb.finish_and_clear();
 
-    paths.par_iter().for_each(|path| {
-        let file_open_errors = file_open_errors.clone();
-        let f = match File::open(path) {
-            Ok(f) => f,
-            Err(error) => {
-                error!("{}: {}", path.display(), error);
-                file_open_errors
-                    .lock()
-                    .unwrap()
-                    .push(FileOpenError { path, error });
-                return;
-            }
-        };
+    let mut files = Vec::new();
+    let mut warnings = Vec::new();
 
-        let metadata = match serde_json::from_reader::<File, Metadata>(f) {
-            Ok(metadata) => metadata,
-            Err(error) => {
-                error!("{}: {}", path.display(), error);
-                deserialize_errors
-                    .lock()
-                    .unwrap()
-                    .push(DeserializeError { path, error });
-                return;
+    // errors across the whole collection are reported for the assets directory
+    // an auto number is resolved from the assets, so there is nothing to cross-check
+    let number = config_data.as_ref().and_then(|c| c.number);
+    if config_data.is_some() && number.is_none() {
+        println!(
+            "Number of items is \"{}\", {} item(s) detected in '{}'.",
+            AUTO_NUMBER,
+            paths.len(),
+            args.assets_dir
+        );
+    }
+    let mut collection_wide_errors = check_asset_numbering(&file_names, number);
+
+    if !args.allow_duplicate_names {
+        let names: Vec<(String, String)> = paths
+            .iter()
+            .zip(&results)
+            .filter_map(|(path, result)| Some((file_index(path), result.name.clone()?)))
+            .collect();
+        collection_wide_errors.extend(find_duplicate_names(&names));
+    }
+
+    if args.check_duplicates {
+        let images: Vec<(String, PathBuf)> = paths
+            .iter()
+            .zip(&results)
+            .filter_map(|(path, result)| Some((file_index(path), result.image.clone()?)))
+            .collect();
+        collection_wide_errors.extend(find_duplicate_images(&images));
+    }
+
+    if !collection_wide_errors.is_empty() {
+        for error in &collection_wide_errors {
+            error!("{}: {}", assets_dir.display(), error);
+        }
+
+        files.push(FileReport {
+            file: assets_dir.display().to_string(),
+            errors: collection_wide_errors
+                .into_iter()
+                .map(ReportError::from)
+                .collect(),
+        });
+    }
+
+    let collection_errors = validate_collection(assets_dir, &file_names, args.strict);
+
+    if !collection_errors.is_empty() {
+        let collection = assets_dir.join(format!("{COLLECTION_FILENAME}.json"));
+
+        for error in &collection_errors {
+            error!("{}: {}", collection.display(), error.message);
+        }
+
+        files.push(FileReport {
+            file: collection.display().to_string(),
+            errors: collection_errors,
+        });
+    }
+
+    for error in path_errors {
+        error!("{}: {}", error.path().display(), error.error());
+        files.push(FileReport {
+            file: error.path().display().to_string(),
+            errors: vec![ReportError {
+                kind: "PathError".to_string(),
+                message: error.error().to_string(),
+            }],
+        });
+    }
+
+    let creators_matched = creators
+        .as_ref()
+        .map(|_| results.iter().filter(|r| r.creators_match).count());
+
+    // symbol and seller fee of every file are expected to match the config or,
+    // without a config, the value used by most files
+    let mut symbols = BTreeMap::new();
+    let mut seller_fees = BTreeMap::new();
+
+    for result in &results {
+        if let Some(symbol) = &result.symbol {
+            *symbols.entry(symbol.clone()).or_insert(0) += 1;
+        }
+        if let Some(seller_fee_basis_points) = result.seller_fee_basis_points {
+            *seller_fees.entry(seller_fee_basis_points).or_insert(0) += 1;
+        }
+    }
+
+    let expected_symbol = match &config_data {
+        Some(config_data) => Some(config_data.symbol.clone()),
+        None => majority(&symbols),
+    };
+    let expected_seller_fee = match &config_data {
+        Some(config_data) => Some(config_data.seller_fee_basis_points),
+        None => majority(&seller_fees),
+    };
+
+    for (path, result) in paths.iter().zip(results) {
+        let FileValidation {
+            mut errors,
+            symbol,
+            seller_fee_basis_points,
+            has_animation_url,
+            ..
+        } = result;
+
+        let index = file_index(path).parse::<usize>().ok();
+
+        if let Some(animation) = index.and_then(|index| animations.get(&index)) {
+            if !has_animation_url {
+                errors.push(ValidateError::UnreferencedAnimationFile(animation.clone()).into());
             }
-        };
+        }
 
-        if args.strict {
-            match metadata.validate_strict() {
-                Ok(()) => {}
-                Err(e) => {
-                    error!("{}: {}", path.display(), e);
-                    validate_errors.lock().unwrap().push(e);
-                }
+        if let (Some(symbol), Some(expected)) = (symbol, &expected_symbol) {
+            if symbol != *expected {
+                errors.push(ValidateError::SymbolMismatch(symbol, expected.clone()).into());
             }
-        } else {
-            match metadata.validate() {
-                Ok(()) => {}
-                Err(e) => {
-                    error!("{}: {}", path.display(), e);
-                    validate_errors.lock().unwrap().push(e);
-                }
+        }
+
+        if let Some(warning) = seller_fee_basis_points.and_then(|seller_fee_basis_points| {
+            check_seller_fee_sanity(seller_fee_basis_points, args.seller_fee_threshold).err()
+        }) {
+            warn!("{}: {}", path.display(), warning);
+            warnings.push(FileReport {
+                file: path.display().to_string(),
+                errors: vec![warning.into()],
+            });
+        }
+
+        if let (Some(seller_fee_basis_points), Some(expected)) =
+            (seller_fee_basis_points, expected_seller_fee)
+        {
+            if seller_fee_basis_points != expected {
+                errors.push(
+                    ValidateError::SellerFeeMismatch(seller_fee_basis_points, expected).into(),
+                );
             }
         }
-    });
 
-    pb.finish();
+        if errors.is_empty() {
+            continue;
+        }
+
+        for error in &errors {
+            error!("{}: {}", path.display(), error.message);
+        }
 
-    if !path_errors.is_empty() {
-        error!("Path errors: {:?}", path_errors);
-        return Err(ReadFilesError::PathErrors.into());
+        files.push(FileReport {
+            file: path.display().to_string(),
+            errors,
+        });
     }
 
-    if !file_open_errors.lock().unwrap().is_empty() {
-        error!("File open errors: {:?}", file_open_errors);
-        return Err(ReadFilesError::FileOpenErrors.into());
+    let mut errors_by_type = BTreeMap::new();
+    for error in files.iter().flat_map(|f| &f.errors) {
+        *errors_by_type.entry(error.kind.clone()).or_insert(0) += 1;
     }
 
-    if !deserialize_errors.lock().unwrap().is_empty() {
-        error!("Deserialize errors: {:?}", deserialize_errors);
-        return Err(ReadFilesError::DeserializeErrors.into());
+    let report = ValidationReport {
+        assets_dir: args.assets_dir.clone(),
+        files_checked: paths.len(),
+        files_with_errors: files.len(),
+        error_count: errors_by_type.values().sum(),
+        creators_matched,
+        symbols,
+        seller_fee_basis_points: seller_fees,
+        errors_by_type,
+        files,
+        warnings,
+    };
+
+    let report_file = File::create(&args.report)?;
+    serde_json::to_writer_pretty(report_file, &report)?;
+
+    if !report.warnings.is_empty() {
+        println!(
+            "\n{} {} file(s) with unusual seller fee basis points (0 or above {}), see '{}'.",
+            style("Warning:").yellow().bold(),
+            report.warnings.len(),
+            args.seller_fee_threshold,
+            args.report
+        );
     }
 
-    if !validate_errors.lock().unwrap().is_empty() {
-        error!("Validate errors: {:?}", validate_errors);
-        return Err(ReadFilesError::ValidateErrors.into());
+    if let Some(hidden_settings) = config_data
+        .as_ref()
+        .and_then(|c| c.hidden_settings.as_ref())
+    {
+        let hash = reveal_hash(&paths)?;
+
+        println!("\nReveal set hash: {}", style(&hash).bold());
+
+        if hash == hidden_settings.hash() {
+            println!("Matches the hidden settings hash.");
+        } else {
+            println!(
+                "{} does not match the hidden settings hash '{}'.",
+                style("Warning:").yellow().bold(),
+                hidden_settings.hash()
+            );
+        }
+    }
+
+    println!("\nSymbols found:");
+    for (symbol, count) in &report.symbols {
+        println!("  {:<32} {}", format!("'{}'", symbol), count);
+    }
+
+    println!("\nSeller fee basis points found:");
+    for (seller_fee_basis_points, count) in &report.seller_fee_basis_points {
+        println!("  {:<32} {}", seller_fee_basis_points, count);
+    }
+
+    if let Some(creators_matched) = report.creators_matched {
+        println!(
+            "\nCreators match the config in {} of {} file(s).",
+            creators_matched, report.files_checked
+        );
+    }
+
+    if report.error_count > 0 {
+        println!(
+            "\n{} {} error(s) found in {} of {} file(s):\n",
+            style("Validation failed:").red().bold(),
+            report.error_count,
+            report.files_with_errors,
+            report.files_checked
+        );
+
+        for (kind, count) in &report.errors_by_type {
+            println!("  {:<32} {}", kind, count);
+        }
+
+        println!("\nValidation report saved to '{}'.", args.report);
+
+        let error = anyhow!(
+            "Validation found {} error(s) in {} file(s), see '{}' for details",
+            report.error_count,
+            report.files_with_errors,
+            args.report
+        );
+        error!("{:?}", error);
+        return Err(error);
     }
 
     let message = "Validation complete, your metadata file(s) look good.";
@@ -122,3 +388,449 @@ pub fn process_validate(args: ValidateArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Report of a metadata validation, grouping the errors by file and by type.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub assets_dir: String,
+    pub files_checked: usize,
+    pub files_with_errors: usize,
+    pub error_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creators_matched: Option<usize>,
+    pub symbols: BTreeMap<String, usize>,
+    pub seller_fee_basis_points: BTreeMap<u16, usize>,
+    pub errors_by_type: BTreeMap<String, usize>,
+    pub files: Vec<FileReport>,
+    pub warnings: Vec<FileReport>,
+}
+
+/// Errors found in a single metadata file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub file: String,
+    pub errors: Vec<ReportError>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportError {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub message: String,
+}
+
+impl From<ValidateError> for ReportError {
+    fn from(error: ValidateError) -> Self {
+        ReportError {
+            kind: error.kind().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Outcome of validating a single metadata file.
+struct FileValidation {
+    errors: Vec<ReportError>,
+    /// Whether the creators of the file match the config creators.
+    creators_match: bool,
+    symbol: Option<String>,
+    seller_fee_basis_points: Option<u16>,
+    has_animation_url: bool,
+    name: Option<String>,
+    /// Local image file referenced by the metadata, if it exists.
+    image: Option<PathBuf>,
+}
+
+impl FileValidation {
+    fn failed(kind: &str, message: String) -> Self {
+        FileValidation {
+            errors: vec![ReportError {
+                kind: kind.to_string(),
+                message,
+            }],
+            creators_match: false,
+            symbol: None,
+            seller_fee_basis_points: None,
+            has_animation_url: false,
+            name: None,
+            image: None,
+        }
+    }
+}
+
+/// Most frequent value of the counts, preferring the smallest value on ties.
+fn majority<T: Ord + Clone>(counts: &BTreeMap<T, usize>) -> Option<T> {
+    counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        .map(|(value, _)| value.clone())
+}
+
+/// Applies the automatic fixes to the metadata files, printing the changes of
+/// each file. Files are rewritten with their keys in alphabetical order.
+fn fix_files(
+    paths: &[PathBuf],
+    file_names: &[String],
+    config_data: Option<&ConfigData>,
+    args: &ValidateArgs,
+) -> Result<()> {
+    let symbol = config_data.map(|c| c.symbol.as_str());
+    let mut fixed = 0;
+
+    println!(
+        "\n{}",
+        if args.dry_run {
+            "Fixes that would be applied:"
+        } else {
+            "Applying fixes:"
+        }
+    );
+
+    for path in paths {
+        let changes = match fix_file(path, file_names, symbol, args.dry_run, !args.no_backup) {
+            Ok(changes) => changes,
+            Err(error) => {
+                // unreadable files are reported by the validation
+                warn!("{}: could not fix file: {}", path.display(), error);
+                continue;
+            }
+        };
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        fixed += 1;
+        println!("  {}", style(path.display()).bold());
+        for change in changes {
+            info!("{}: {}", path.display(), change);
+            println!("    - {change}");
+        }
+    }
+
+    if fixed == 0 {
+        println!("  nothing to fix");
+    } else if args.dry_run {
+        println!("\n{fixed} file(s) would be fixed (dry run, no file changed).");
+    } else {
+        println!("\n{fixed} file(s) fixed.");
+    }
+
+    Ok(())
+}
+
+/// Validates the placeholder of the hidden settings, printing the result.
+fn validate_hidden_settings(hidden_settings: &HiddenSettings, number: u64) -> Result<()> {
+    let errors = check_hidden_settings(hidden_settings, number);
+
+    if errors.is_empty() {
+        println!("\nHidden settings placeholder looks good.");
+        return Ok(());
+    }
+
+    for error in &errors {
+        println!("  {} {}", style("-").red(), error);
+        error!("hidden settings: {}", error);
+    }
+
+    let error = anyhow!("Invalid hidden settings placeholder");
+    error!("{:?}", error);
+    Err(error)
+}
+
+/// Validates the metadata files in parallel. The results are collected in the
+/// same order as the paths so that errors are always reported by file index.
+fn validate_files(
+    paths: &[PathBuf],
+    strict: bool,
+    creators: Option<&[Creator]>,
+    image_limits: Option<&ImageLimits>,
+    max_animation_bytes: Option<u64>,
+    pb: &ProgressBar,
+) -> Vec<FileValidation> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let result = validate_file(path, strict, creators, image_limits, max_animation_bytes);
+            pb.inc(1);
+            result
+        })
+        .collect()
+}
+
+/// Sorts the paths by the numeric index of their file name; names that are not
+/// a number come last, in lexicographic order.
+fn sort_by_file_index(paths: &mut [PathBuf]) {
+    paths.sort_by_cached_key(|path| {
+        let index = file_index(path);
+        (index.parse::<u64>().map_err(|_| ()), index)
+    });
+}
+
+/// Index of an item, taken from its file name.
+fn file_index(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn is_collection_file(path: &Path) -> bool {
+    path.file_stem().and_then(|s| s.to_str()) == Some(COLLECTION_FILENAME)
+}
+
+/// Validates the collection metadata and image. Their absence is only an error
+/// in strict mode.
+fn validate_collection(assets_dir: &Path, file_names: &[String], strict: bool) -> Vec<ReportError> {
+    let path = assets_dir.join(format!("{COLLECTION_FILENAME}.json"));
+
+    if !path.is_file() {
+        let mut errors = Vec::new();
+
+        if strict {
+            errors.push(
+                ValidateError::MissingCollectionFile(format!("{COLLECTION_FILENAME}.json")).into(),
+            );
+
+            let has_image = file_names.iter().any(|file_name| {
+                let file = Path::new(file_name);
+                file.file_stem().and_then(|s| s.to_str()) == Some(COLLECTION_FILENAME)
+                    && file
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                        .unwrap_or(false)
+            });
+
+            if !has_image {
+                errors.push(
+                    ValidateError::MissingCollectionFile(format!("{COLLECTION_FILENAME}.png"))
+                        .into(),
+                );
+            }
+        }
+
+        return errors;
+    }
+
+    // the collection is validated like an item, without the strict item requirements
+    let mut errors = validate_file(&path, false, None, None, None).errors;
+
+    // deserialize errors are already reported by the item checks
+    let metadata = File::open(&path)
+        .ok()
+        .and_then(|f| serde_json::from_reader::<File, Metadata>(f).ok());
+
+    if let Some(creators) = metadata.and_then(|m| m.properties.creators) {
+        if let Err(error) = check_creator_shares(&creators) {
+            errors.push(error.into());
+        }
+    }
+
+    errors
+}
+
+fn validate_file(
+    path: &Path,
+    strict: bool,
+    creators: Option<&[Creator]>,
+    image_limits: Option<&ImageLimits>,
+    max_animation_bytes: Option<u64>,
+) -> FileValidation {
+    let f = match File::open(path) {
+        Ok(f) => f,
+        Err(error) => return FileValidation::failed("FileOpenError", error.to_string()),
+    };
+
+    // the raw value is kept to check for unknown fields in strict mode
+    let value = match serde_json::from_reader::<File, Value>(f) {
+        Ok(value) => value,
+        Err(error) => return FileValidation::failed("DeserializeError", error.to_string()),
+    };
+
+    let metadata = match serde_json::from_value::<Metadata>(value.clone()) {
+        Ok(metadata) => metadata,
+        Err(error) => return FileValidation::failed("DeserializeError", error.to_string()),
+    };
+
+    let mut errors = metadata.validate(strict);
+
+    if strict {
+        errors.extend(check_unknown_fields(&value));
+    }
+    errors.extend(metadata.validate_assets(path, strict));
+
+    // missing or remote images are reported by the asset checks
+    let image = Some(
+        path.parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&metadata.image),
+    )
+    .filter(|image| !metadata.image.contains("://") && image.is_file());
+
+    if let (Some(image), Some(image_limits)) = (&image, image_limits) {
+        errors.extend(check_image(image, image_limits));
+    }
+
+    if let (Some(animation_url), Some(max_bytes)) = (&metadata.animation_url, max_animation_bytes) {
+        let animation = path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(animation_url);
+
+        if let Ok(file) = animation.metadata() {
+            if file.len() > max_bytes {
+                errors.push(ValidateError::AnimationTooLarge(
+                    animation_url.clone(),
+                    file.len(),
+                    max_bytes,
+                ));
+            }
+        }
+    }
+
+    if let (Some(_), Some(creators)) = (creators, &metadata.properties.creators) {
+        if let Err(error) = check_creator_shares(creators) {
+            errors.push(error);
+        }
+    }
+
+    let creators_match = match creators {
+        Some(creators) => match metadata.validate_creators(creators, strict) {
+            Ok(matched) => matched,
+            Err(error) => {
+                errors.push(error);
+                false
+            }
+        },
+        None => false,
+    };
+
+    FileValidation {
+        errors: errors.into_iter().map(ReportError::from).collect(),
+        creators_match,
+        has_animation_url: metadata.animation_url.is_some(),
+        name: Some(metadata.name.clone()),
+        image,
+        symbol: Some(metadata.symbol),
+        seller_fee_basis_points: Some(metadata.seller_fee_basis_points),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, time::Instant};
+
+    /// Writes `count` metadata files (and their images) to a new directory.
+    fn write_assets(name: &str, count: usize) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("laddu-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        for index in 0..count {
+            let metadata = json!({
+                "name": format!("Laddu #{}", index),
+                "symbol": "LDU",
+                "description": "A laddu",
+                "seller_fee_basis_points": 500,
+                "image": format!("{}.png", index),
+                "attributes": [],
+                "properties": {
+                    "files": [{ "uri": format!("{}.png", index), "type": "image/png" }],
+                    "category": "image"
+                }
+            });
+            fs::write(dir.join(format!("{}.json", index)), metadata.to_string()).unwrap();
+            fs::write(dir.join(format!("{}.png", index)), b"png").unwrap();
+        }
+
+        dir
+    }
+
+    fn asset_paths(dir: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = glob(dir.join("*.json").to_str().unwrap())
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        sort_by_file_index(&mut paths);
+        paths
+    }
+
+    fn summary(results: &[FileValidation]) -> Vec<(Option<String>, Vec<String>)> {
+        results
+            .iter()
+            .map(|result| {
+                let errors = result.errors.iter().map(|e| e.message.clone()).collect();
+                (result.name.clone(), errors)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sort_paths_by_numeric_index() {
+        let mut paths: Vec<PathBuf> = ["10.json", "b.json", "2.json", "a.json", "0.json", "1.json"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        sort_by_file_index(&mut paths);
+
+        let names: Vec<String> = paths.iter().map(|path| file_index(path)).collect();
+        assert_eq!(names, ["0", "1", "2", "10", "a", "b"]);
+    }
+
+    #[test]
+    fn parallel_results_follow_the_path_order() {
+        let dir = write_assets("validate-order", 64);
+        let paths = asset_paths(&dir);
+
+        let serial: Vec<FileValidation> = paths
+            .iter()
+            .map(|path| validate_file(path, false, None, None, None))
+            .collect();
+        let parallel = validate_files(&paths, false, None, None, None, &ProgressBar::hidden());
+
+        assert_eq!(summary(&parallel), summary(&serial));
+        assert_eq!(parallel[10].name.as_deref(), Some("Laddu #10"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Compares the serial and parallel validation of a large collection, run with
+    /// `cargo test --release validate_benchmark -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn validate_benchmark() {
+        let dir = write_assets("validate-benchmark", 20_000);
+        let paths = asset_paths(&dir);
+
+        let start = Instant::now();
+        let serial: Vec<FileValidation> = paths
+            .iter()
+            .map(|path| validate_file(path, true, None, None, None))
+            .collect();
+        let serial_time = start.elapsed();
+
+        let start = Instant::now();
+        let parallel = validate_files(&paths, true, None, None, None, &ProgressBar::hidden());
+        let parallel_time = start.elapsed();
+
+        println!(
+            "validated {} files: serial {:?}, parallel {:?} ({:.1}x on {} threads)",
+            paths.len(),
+            serial_time,
+            parallel_time,
+            serial_time.as_secs_f64() / parallel_time.as_secs_f64(),
+            rayon::current_num_threads()
+        );
+
+        assert_eq!(summary(&parallel), summary(&serial));
+        if rayon::current_num_threads() > 1 {
+            assert!(parallel_time < serial_time);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
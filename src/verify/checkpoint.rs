@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    path::Path,
+};
+
+use crate::utils::write_atomic;
+use crate::verify::LinkCheck;
+
+/// Number of checked links between two checkpoint saves.
+pub const CHECKPOINT_INTERVAL: usize = 50;
+
+/// Links checked so far by a verify run.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyProgress {
+    pub magic_hat: String,
+    pub indices: String,
+    pub check_images: bool,
+    pub checked: Vec<String>,
+    pub links: Vec<LinkCheck>,
+}
+
+/// Checkpoints the link checks of a verify run in a state file, so that an
+/// interrupted run can be resumed.
+pub struct VerifyCheckpoint {
+    path: String,
+    progress: VerifyProgress,
+    /// Indices of `progress.checked`, for constant time lookups.
+    checked: HashSet<String>,
+    unsaved: usize,
+}
+
+impl VerifyCheckpoint {
+    /// Loads the progress of an interrupted run, if there is one.
+    pub fn load(path: &str) -> Result<Option<VerifyProgress>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+
+        let file = File::open(path)?;
+        let progress = serde_json::from_reader(file)
+            .map_err(|err| anyhow!("Failed to parse verify state file '{}': {}", path, err))?;
+
+        Ok(Some(progress))
+    }
+
+    /// Starts a new run, replacing any previous progress.
+    pub fn start(path: &str, magic_hat: &str, indices: &str, check_images: bool) -> Self {
+        VerifyCheckpoint {
+            path: path.to_string(),
+            progress: VerifyProgress {
+                magic_hat: magic_hat.to_string(),
+                indices: indices.to_string(),
+                check_images,
+                ..Default::default()
+            },
+            checked: HashSet::new(),
+            unsaved: 0,
+        }
+    }
+
+    /// Resumes an interrupted run. The parameters must match the ones of the
+    /// interrupted run.
+    pub fn resume(path: &str, magic_hat: &str, indices: &str, check_images: bool) -> Result<Self> {
+        let progress = match Self::load(path)? {
+            Some(progress) => progress,
+            None => return Err(anyhow!("No interrupted verify found in '{}'", path)),
+        };
+
+        if progress.magic_hat != magic_hat {
+            return Err(anyhow!(
+                "Cannot resume: the interrupted verify was for Magic Hat {}",
+                progress.magic_hat
+            ));
+        }
+
+        if progress.indices != indices || progress.check_images != check_images {
+            return Err(anyhow!(
+                "Cannot resume: the interrupted verify used different options"
+            ));
+        }
+
+        Ok(VerifyCheckpoint {
+            path: path.to_string(),
+            checked: progress.checked.iter().cloned().collect(),
+            progress,
+            unsaved: 0,
+        })
+    }
+
+    /// Whether the link of the item was checked by the interrupted run.
+    pub fn is_checked(&self, index: &str) -> bool {
+        self.checked.contains(index)
+    }
+
+    pub fn checked_len(&self) -> usize {
+        self.progress.checked.len()
+    }
+
+    /// Records a checked link, saving the state file every `CHECKPOINT_INTERVAL`
+    /// links.
+    pub fn record(&mut self, check: LinkCheck) -> Result<()> {
+        self.checked.insert(check.index.clone());
+        self.progress.checked.push(check.index.clone());
+        if !check.problems.is_empty() {
+            self.progress.links.push(check);
+        }
+
+        self.unsaved += 1;
+        if self.unsaved >= CHECKPOINT_INTERVAL {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the state file once all links were checked, returning the links
+    /// with problems.
+    pub fn finish(self) -> Result<Vec<LinkCheck>> {
+        if Path::new(&self.path).exists() {
+            fs::remove_file(&self.path)?;
+        }
+
+        Ok(self.progress.links)
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&self.progress)?.as_bytes(),
+        )?;
+        self.unsaved = 0;
+
+        Ok(())
+    }
+}
@@ -6,4 +6,6 @@ pub enum VerifyError {
     FailedToGetAccountData(String),
     #[error("{0} mismatch (expected='{1}', found='{2}')")]
     Mismatch(String, String, String),
+    #[error("Magic hat account data is too small to hold {0} config line(s) ({1} bytes).")]
+    AccountDataTooSmall(usize, usize),
 }
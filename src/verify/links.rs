@@ -0,0 +1,200 @@
+use futures::stream::{self, StreamExt};
+use indicatif::ProgressStyle;
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::common::*;
+use crate::utils::progress_bar_with_style;
+use crate::verify::VerifyCheckpoint;
+
+/// Number of attempts for a request before it is counted as a failure.
+const LINK_RETRIES: u32 = 3;
+
+/// Delay between attempts (multiplied by the attempt number).
+const LINK_RETRY_DELAY_MS: u64 = 500;
+
+/// Metadata link of a cache item to check.
+pub struct LinkItem {
+    pub index: String,
+    pub name: String,
+    pub uri: String,
+    /// Additional uploaded files of the item (checked with the images)
+    pub extra: Vec<String>,
+}
+
+/// Problems found with the links of an item.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheck {
+    pub index: String,
+    pub uri: String,
+    pub problems: Vec<String>,
+}
+
+/// Checks the metadata links with bounded concurrency: the link must be reachable,
+/// serve JSON and its `name` must match the cache item. When `check_images` is set,
+/// the image of the JSON and the additional files of the item must also be
+/// reachable (and the image serve an image). Only the items with problems are
+/// returned, sorted by index.
+///
+/// Items already checked by an interrupted run are skipped and every result is
+/// recorded in the checkpoint, which is removed once all links were checked.
+pub async fn check_links(
+    items: Vec<LinkItem>,
+    check_images: bool,
+    mut checkpoint: VerifyCheckpoint,
+) -> Result<Vec<LinkCheck>> {
+    let http_client = HttpClient::new();
+    let pb = progress_bar_with_style(items.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] {msg}{wide_bar} {pos}/{len} (ETA {eta})"),
+    );
+    pb.set_message("Checking links...");
+
+    let items: Vec<LinkItem> = items
+        .into_iter()
+        .filter(|item| !checkpoint.is_checked(&item.index))
+        .collect();
+    // the ETA only accounts for the links checked by this run
+    pb.set_position(checkpoint.checked_len() as u64);
+    pb.reset_eta();
+
+    let mut checks = stream::iter(items)
+        .map(|item| {
+            let http_client = &http_client;
+            let pb = &pb;
+            async move {
+                let problems = check_item(http_client, &item, check_images).await;
+                pb.inc(1);
+                LinkCheck {
+                    index: item.index,
+                    uri: item.uri,
+                    problems,
+                }
+            }
+        })
+        .buffer_unordered(PARALLEL_LIMIT);
+
+    while let Some(check) = checks.next().await {
+        checkpoint.record(check)?;
+    }
+
+    pb.finish_and_clear();
+
+    let mut links = checkpoint.finish()?;
+    links.sort_by_key(|check| check.index.parse::<usize>().unwrap_or(usize::MAX));
+    Ok(links)
+}
+
+async fn check_item(http_client: &HttpClient, item: &LinkItem, check_images: bool) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let response = match send_with_retry(http_client, &item.uri, false).await {
+        Ok(response) => response,
+        Err(error) => {
+            problems.push(format!("dead metadata link ({error})"));
+            return problems;
+        }
+    };
+
+    if !has_content_type(&response, &["application/json", "text/plain"]) {
+        problems.push(format!(
+            "metadata link has content type '{}'",
+            content_type(&response)
+        ));
+    }
+
+    let json = match response.json::<Value>().await {
+        Ok(json) => json,
+        Err(error) => {
+            problems.push(format!("invalid metadata JSON ({error})"));
+            return problems;
+        }
+    };
+
+    let name = json["name"].as_str().unwrap_or_default();
+    if name != item.name {
+        problems.push(format!(
+            "name mismatch (expected='{}', found='{}')",
+            item.name, name
+        ));
+    }
+
+    if check_images {
+        match json["image"].as_str() {
+            // only the headers of the image are needed
+            Some(image) => match send_with_retry(http_client, image, true).await {
+                Ok(response) => {
+                    if !has_content_type(&response, &["image/"]) {
+                        problems.push(format!(
+                            "image link has content type '{}'",
+                            content_type(&response)
+                        ));
+                    }
+                }
+                Err(error) => problems.push(format!("dead image link ({error})")),
+            },
+            None => problems.push("missing image in metadata JSON".to_string()),
+        }
+
+        for uri in &item.extra {
+            if let Err(error) = send_with_retry(http_client, uri, true).await {
+                problems.push(format!("dead file link '{uri}' ({error})"));
+            }
+        }
+    }
+
+    problems
+}
+
+/// Sends a GET (or HEAD) request, retrying network errors and server-side failures.
+async fn send_with_retry(http_client: &HttpClient, uri: &str, head: bool) -> Result<Response> {
+    let mut attempt = 1;
+
+    loop {
+        let request = if head {
+            http_client.head(uri)
+        } else {
+            http_client.get(uri)
+        };
+
+        let error = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                // client errors (e.g. 404) will not change on retry
+                if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                    return Err(anyhow!("HTTP {}", status));
+                }
+                anyhow!("HTTP {}", status)
+            }
+            Err(error) => anyhow!(error),
+        };
+
+        if attempt >= LINK_RETRIES {
+            return Err(error);
+        }
+
+        debug!("Request to {} failed (attempt {}): {}", uri, attempt, error);
+        tokio::time::sleep(Duration::from_millis(LINK_RETRY_DELAY_MS * attempt as u64)).await;
+        attempt += 1;
+    }
+}
+
+fn content_type(response: &Response) -> String {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn has_content_type(response: &Response, expected: &[&str]) -> bool {
+    let content_type = content_type(response).to_lowercase();
+    expected
+        .iter()
+        .any(|expected| content_type.starts_with(expected))
+}
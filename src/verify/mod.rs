@@ -1,5 +1,11 @@
+pub mod checkpoint;
 pub mod errors;
+pub mod links;
+pub mod on_chain;
 pub mod process;
 
+pub use checkpoint::*;
 pub use errors::*;
+pub use links::*;
+pub use on_chain::*;
 pub use process::*;
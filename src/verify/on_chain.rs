@@ -0,0 +1,249 @@
+use console::style;
+use std::fs::File;
+
+use crate::common::*;
+use crate::config::data::HiddenSettings;
+use crate::constants::{MAGICHAT_EMOJI, PAPER_EMOJI};
+use crate::magic_hat::MAGIC_HAT_ID;
+use crate::show::{get_bitmask, is_bit_set, LOADED_BITMASK};
+use crate::utils::*;
+use crate::validate::{check_hidden_settings, check_name, check_symbol, check_url, format_ranges};
+use crate::verify::{
+    check_collection, check_links, decode_config_lines, fetch_magic_hat, open_checkpoint,
+    select_indices, trim_padding, ItemMismatch, LinkItem, VerifyArgs, VerifyReport,
+};
+
+/// Length of the uuid of a magic hat.
+const UUID_LENGTH: usize = 6;
+
+/// Verifies a magic hat without a cache file: the written config lines are checked
+/// for gaps and structural problems (lengths, empty values and control characters)
+/// together with the uuid, symbol and collection of the magic hat. Links are only
+/// checked with `--check-links`.
+pub async fn process_verify_on_chain(args: VerifyArgs, magic_hat_id: &str) -> Result<()> {
+    let laddu_config = laddu_setup(args.keypair.clone(), args.rpc_url.clone())?;
+
+    println!(
+        "{} {}Loading Magic Hat",
+        style("[1/2]").bold().dim(),
+        MAGICHAT_EMOJI
+    );
+    println!("{} {}", style("Magic Hat ID:").bold(), magic_hat_id);
+
+    let magic_hat_pubkey = match Pubkey::from_str(magic_hat_id) {
+        Ok(pubkey) => pubkey,
+        Err(_) => {
+            let error = anyhow!("Failed to parse Magic Hat id: {}", magic_hat_id);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    let pb = spinner_with_style();
+    pb.set_message("Connecting...");
+
+    let client = setup_client(&laddu_config)?;
+    let program = client.program(MAGIC_HAT_ID);
+    let (data, magic_hat) = fetch_magic_hat(&program, &magic_hat_pubkey)?;
+
+    pb.finish_with_message("Completed");
+
+    println!(
+        "\n{} {}Verification",
+        style("[2/2]").bold().dim(),
+        PAPER_EMOJI
+    );
+    println!(
+        "{}",
+        style("No cache file for this Magic Hat: only on-chain checks are run.").dim()
+    );
+
+    let items_available = magic_hat.data.items_available;
+    let indices = match magic_hat.data.hidden_settings {
+        Some(_) => Vec::new(),
+        None => select_indices(&args, items_available as usize)?,
+    };
+    let partial = !indices.is_empty() && indices.len() < items_available as usize;
+
+    // link checks of an interrupted run are resumed from the checkpoint file
+    let checkpoint = if args.check_links && !indices.is_empty() {
+        Some(open_checkpoint(&args, magic_hat_id, &indices)?)
+    } else {
+        None
+    };
+
+    let mut report = VerifyReport {
+        magic_hat: magic_hat_id.to_string(),
+        missing: Vec::new(),
+        mismatched: Vec::new(),
+        links: None,
+        hidden_settings: None,
+        collection: None,
+        settings: Vec::new(),
+        partial: partial.then(|| format_ranges(&indices)),
+        receipts: Vec::new(),
+    };
+
+    let uuid = &magic_hat.data.uuid;
+    if uuid.len() != UUID_LENGTH || !uuid.chars().all(|c| c.is_ascii_alphanumeric()) {
+        report.settings.push(format!(
+            "uuid '{}' is not {} alphanumeric characters",
+            uuid.escape_debug(),
+            UUID_LENGTH
+        ));
+    }
+
+    let symbol = trim_padding(&magic_hat.data.symbol);
+    if let Err(err) = check_symbol(symbol) {
+        report.settings.push(err.to_string());
+    }
+    if symbol.trim() != symbol || symbol.chars().any(char::is_control) {
+        report.settings.push(format!(
+            "symbol '{}' has whitespace or control characters",
+            symbol.escape_debug()
+        ));
+    }
+
+    let mut written = Vec::new();
+    let mut gaps = Vec::new();
+
+    if let Some(hidden_settings) = &magic_hat.data.hidden_settings {
+        let hidden_settings = HiddenSettings::from_magichat_format(hidden_settings)?;
+        report.settings.extend(
+            check_hidden_settings(&hidden_settings, items_available)
+                .iter()
+                .map(|err| format!("hidden settings: {}", err)),
+        );
+    } else {
+        println!("\nVerifying {} config line(s):", indices.len());
+        let pb = progress_bar_with_style(indices.len() as u64);
+
+        let loaded = get_bitmask(&data, items_available, LOADED_BITMASK)?;
+        let loaded: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|index| {
+                let is_loaded = is_bit_set(loaded, *index as u64);
+                if !is_loaded {
+                    gaps.push(*index);
+                }
+                is_loaded
+            })
+            .collect();
+        report.missing = gaps.iter().map(|index| index.to_string()).collect();
+        pb.inc(gaps.len() as u64);
+
+        for (index, item) in loaded.iter().zip(decode_config_lines(&data, &loaded)?) {
+            let mut problems = Vec::new();
+
+            if item.name.is_empty() || item.uri.is_empty() {
+                problems.push("empty name or uri".to_string());
+            }
+            if let Err(err) = check_name(&item.name) {
+                problems.push(err.to_string());
+            }
+            if let Err(err) = check_url(&item.uri) {
+                problems.push(err.to_string());
+            }
+            if item
+                .name
+                .chars()
+                .chain(item.uri.chars())
+                .any(char::is_control)
+            {
+                problems.push(format!(
+                    "control characters in name '{}' or uri '{}'",
+                    item.name.escape_debug(),
+                    item.uri.escape_debug()
+                ));
+            }
+
+            if !problems.is_empty() {
+                report.mismatched.push(ItemMismatch {
+                    index: index.to_string(),
+                    error: problems.join("; "),
+                });
+            }
+
+            written.push(LinkItem {
+                index: index.to_string(),
+                name: item.name,
+                uri: item.uri,
+                extra: Vec::new(),
+            });
+            pb.inc(1);
+        }
+
+        pb.finish();
+    }
+
+    report.collection = Some(check_collection(
+        &program,
+        &magic_hat_pubkey,
+        &magic_hat,
+        &[],
+    )?);
+
+    if let Some(checkpoint) = checkpoint {
+        println!("\nChecking {} metadata link(s):", written.len());
+        report.links = Some(check_links(written, args.check_images, checkpoint).await?);
+    }
+
+    let report_file = File::create(&args.report)?;
+    serde_json::to_writer_pretty(report_file, &report)?;
+
+    if !report.settings.is_empty() {
+        println!("\nMagic Hat settings: ");
+        for problem in &report.settings {
+            println!("- {}", problem);
+        }
+    }
+
+    if !gaps.is_empty() {
+        println!("\nConfig lines not written: {}", format_ranges(&gaps));
+    }
+
+    if !report.mismatched.is_empty() {
+        println!("\nInvalid config lines: ");
+        for mismatch in &report.mismatched {
+            println!("- Item {}: {}", mismatch.index, mismatch.error);
+        }
+    }
+
+    if let Some(links) = &report.links {
+        if !links.is_empty() {
+            println!("\nItems with broken links: ");
+            for link in links {
+                println!("- Item {}: {}", link.index, link.problems.join("; "));
+            }
+        } else {
+            println!("\nAll metadata links are reachable.");
+        }
+    }
+
+    let total = report.settings.len()
+        + report.missing.len()
+        + report.mismatched.len()
+        + report.links.as_ref().map(Vec::len).unwrap_or_default()
+        + report
+            .collection
+            .as_ref()
+            .map(|check| check.errors.len())
+            .unwrap_or_default();
+
+    if total > 0 {
+        println!("\nVerify report saved to '{}'.", args.report);
+        return Err(anyhow!("{} problem(s) found.", total));
+    }
+
+    match &report.partial {
+        Some(description) => println!(
+            "\nItem(s) {} checked out ({}: the other items were not verified).",
+            description,
+            style("partial verification").yellow().bold()
+        ),
+        None => println!("\nNo problems found in the on-chain data."),
+    }
+
+    Ok(())
+}
@@ -1,21 +1,37 @@
 use anchor_lang::AccountDeserialize;
 use console::style;
-use std::{thread, time::Duration};
+use serde::Serialize;
+use std::fs::File;
 
 use magic_hat::MagicHat;
 
 use crate::cache::*;
 use crate::common::*;
-use crate::config::Cluster;
-use crate::constants::{MAGICHAT_EMOJI, PAPER_EMOJI};
+use crate::config::{data::HiddenSettings, parser::get_config_data, Cluster};
+use crate::constants::{DEFAULT_CONFIG, DEFAULT_VERIFY_STATE, MAGICHAT_EMOJI, PAPER_EMOJI};
 use crate::magic_hat::MAGIC_HAT_ID;
+use crate::pdas::{get_collection_pda, get_metadata_pda};
 use crate::utils::*;
-use crate::verify::VerifyError;
+use crate::validate::{check_hidden_settings, format_ranges, reveal_hash_from_hashes};
+use crate::verify::{
+    check_links, process_verify_on_chain, LinkCheck, LinkItem, VerifyCheckpoint, VerifyError,
+};
 
 pub struct VerifyArgs {
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub cache: String,
+    pub magic_hat: Option<String>,
+    pub config: Option<String>,
+    pub repair: bool,
+    pub check_links: bool,
+    pub check_images: bool,
+    pub report: String,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+    pub index: Option<String>,
+    pub resume: bool,
+    pub no_resume: bool,
 }
 
 #[derive(Debug)]
@@ -24,7 +40,18 @@ pub struct OnChainItem {
     pub uri: String,
 }
 
-pub fn process_verify(args: VerifyArgs) -> Result<()> {
+pub async fn process_verify(args: VerifyArgs) -> Result<()> {
+    // the magic hat id specified takes precedence over the one from the cache; a
+    // magic hat without a matching cache file only gets the on-chain checks
+    if let Some(magic_hat_id) = args.magic_hat.clone() {
+        let cached = Path::new(&args.cache).exists()
+            && load_cache(&args.cache, false)?.program.magic_hat == magic_hat_id;
+
+        if !cached {
+            return process_verify_on_chain(args, &magic_hat_id).await;
+        }
+    }
+
     let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
 
     // loads the cache file (this needs to have been created by
@@ -43,6 +70,17 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
         return Err(CacheError::CacheFileNotFound(args.cache).into());
     }
 
+    // indices to verify, validated against the cache before connecting
+    let indices = select_indices(&args, cache.items.0.len())?;
+    let partial = indices.len() < cache.items.0.len();
+
+    // link checks of an interrupted run are resumed from the checkpoint file
+    let checkpoint = if args.check_links {
+        Some(open_checkpoint(&args, &cache.program.magic_hat, &indices)?)
+    } else {
+        None
+    };
+
     println!(
         "{} {}Loading Magic Hat",
         style("[1/2]").bold().dim(),
@@ -63,13 +101,7 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
     let client = setup_client(&laddu_config)?;
     let program = client.program(MAGIC_HAT_ID);
 
-    let data = match program.rpc().get_account_data(&magic_hat_pubkey) {
-        Ok(account_data) => account_data,
-        Err(err) => {
-            return Err(VerifyError::FailedToGetAccountData(err.to_string()).into());
-        }
-    };
-    let magic_hat: MagicHat = MagicHat::try_deserialize(&mut data.as_slice())?;
+    let (data, magic_hat) = fetch_magic_hat(&program, &magic_hat_pubkey)?;
 
     pb.finish_with_message("Completed");
 
@@ -79,106 +111,581 @@ pub fn process_verify(args: VerifyArgs) -> Result<()> {
         PAPER_EMOJI
     );
 
-    if magic_hat.data.hidden_settings.is_none() {
-        let num_items = cache.items.0.len();
+    let hidden = magic_hat.data.hidden_settings.is_some();
+    let mut report = VerifyReport {
+        magic_hat: cache.program.magic_hat.clone(),
+        missing: Vec::new(),
+        mismatched: Vec::new(),
+        links: None,
+        hidden_settings: None,
+        collection: None,
+        settings: Vec::new(),
+        partial: partial.then(|| format_ranges(&indices)),
+        receipts: Vec::new(),
+    };
+
+    if let Some(description) = &report.partial {
+        println!(
+            "{} only verifying item(s) {} ({} of {})\n",
+            style("Partial verification:").yellow().bold(),
+            description,
+            indices.len(),
+            cache.items.0.len()
+        );
+    }
+
+    if !hidden {
         let cache_items = &mut cache.items.0;
-        let mut errors = Vec::new();
-
-        println!("Verifying {} config line(s): (Ctrl+C to abort)", num_items);
-        let pb = progress_bar_with_style(num_items as u64);
-        // sleeps for a about 1 second
-        let step: u64 = 1_000_000 / num_items as u64;
-
-        for i in 0..num_items {
-            let name_start = CONFIG_ARRAY_START
-                + STRING_LEN_SIZE
-                + CONFIG_LINE_SIZE * (i as usize)
-                + CONFIG_NAME_OFFSET;
-            let name_end = name_start + MAX_NAME_LENGTH;
-
-            let uri_start = CONFIG_ARRAY_START
-                + STRING_LEN_SIZE
-                + CONFIG_LINE_SIZE * (i as usize)
-                + CONFIG_URI_OFFSET;
-            let uri_end = uri_start + MAX_URI_LENGTH;
-
-            let name_error = format!("Failed to decode name for item {}", i);
-            let name = String::from_utf8(data[name_start..name_end].to_vec())
-                .expect(&name_error)
-                .trim_matches(char::from(0))
-                .to_string();
-
-            let uri_error = format!("Failed to decode uri for item {}", i);
-            let uri = String::from_utf8(data[uri_start..uri_end].to_vec())
-                .expect(&uri_error)
-                .trim_matches(char::from(0))
-                .to_string();
-
-            let on_chain_item = OnChainItem { name, uri };
-            let cache_item = cache_items
-                .get_mut(&i.to_string())
-                .expect("Failed to get item from config.");
-
-            if let Err(err) = items_match(cache_item, &on_chain_item) {
-                cache_item.on_chain = false;
-                errors.push((i.to_string(), err.to_string()));
+
+        println!("Verifying {} config line(s):", indices.len());
+        let pb = progress_bar_with_style(indices.len() as u64);
+
+        // all config lines are decoded from the account data fetched above, so the
+        // comparison does not need any further RPC call
+        let on_chain_items = decode_config_lines(&data, &indices)?;
+
+        for (i, on_chain_item) in indices.iter().zip(&on_chain_items) {
+            match cache_items.get_mut(&i.to_string()) {
+                Some(cache_item) => {
+                    report.receipts.extend(cache_item.receipts(&i.to_string()));
+
+                    let problem = if on_chain_item.name.is_empty() && on_chain_item.uri.is_empty() {
+                        report.missing.push(i.to_string());
+                        true
+                    } else if let Err(err) = items_match(cache_item, on_chain_item) {
+                        report.mismatched.push(ItemMismatch {
+                            index: i.to_string(),
+                            error: err.to_string(),
+                        });
+                        true
+                    } else {
+                        false
+                    };
+
+                    // only items with a problem are touched
+                    if problem && args.repair {
+                        cache_item.on_chain = false;
+                    }
+                }
+                None => report.mismatched.push(ItemMismatch {
+                    index: i.to_string(),
+                    error: "missing from the cache file".to_string(),
+                }),
             }
 
             pb.inc(1);
-            thread::sleep(Duration::from_micros(step));
         }
 
         pb.finish();
+    } else if let Some(hidden_settings) = &magic_hat.data.hidden_settings {
+        // there are no config lines in a magic hat with hidden settings, the
+        // placeholder and the hash of the reveal set are checked instead
+        println!("Verifying hidden settings:");
+
+        let hidden_settings = HiddenSettings::from_magichat_format(hidden_settings)?;
+        let placeholder_errors =
+            check_hidden_settings(&hidden_settings, magic_hat.data.items_available);
+
+        if placeholder_errors.is_empty() {
+            println!("{} placeholder name and uri", style("PASS").green().bold());
+        } else {
+            println!("{} placeholder", style("FAIL").red().bold());
+            for error in &placeholder_errors {
+                println!("  - {}", error);
+            }
+        }
 
-        if !errors.is_empty() {
-            cache.sync_file()?;
+        let mut hashes: Vec<(&String, &CacheItem)> = cache.items.0.iter().collect();
+        hashes.sort_by_key(|(index, _)| index.parse::<usize>().unwrap_or(usize::MAX));
+        let hashes: Vec<&str> = hashes
+            .iter()
+            .map(|(_, item)| item.metadata_hash.as_str())
+            .collect();
+        let hash = reveal_hash_from_hashes(&hashes);
+
+        if hash == hidden_settings.hash() {
+            println!("{} reveal set hash {}", style("PASS").green().bold(), hash);
+        } else {
+            println!(
+                "{} reveal set hash {} does not match the on-chain hash {}",
+                style("FAIL").red().bold(),
+                hash,
+                hidden_settings.hash()
+            );
+        }
+
+        report.hidden_settings = Some(HiddenSettingsCheck {
+            placeholder_errors: placeholder_errors.iter().map(|e| e.to_string()).collect(),
+            reveal_hash: hash.clone(),
+            on_chain_hash: hidden_settings.hash().to_string(),
+            hash_matches: hash == hidden_settings.hash(),
+        });
+    }
+
+    // the collection of the magic hat must match the one recorded in the cache
+    // (and the config, if it specifies one) and belong to the magic hat authority
+    let expected_collection = match &args.config {
+        Some(config) => get_config_data(config)?.collection_mint,
+        None if Path::new(DEFAULT_CONFIG).exists() => {
+            get_config_data(DEFAULT_CONFIG)?.collection_mint
+        }
+        None => None,
+    };
 
-            let total = errors.len();
-            println!("\nInvalid items found: ");
+    let expected = [
+        ("cache", cache.program.collection_mint.clone()),
+        ("config", expected_collection.map(|mint| mint.to_string())),
+    ];
+    report.collection = Some(check_collection(
+        &program,
+        &magic_hat_pubkey,
+        &magic_hat,
+        &expected,
+    )?);
+
+    if let Some(checkpoint) = checkpoint {
+        println!("\nChecking {} metadata link(s):", indices.len());
+
+        let items = cache
+            .items
+            .0
+            .iter()
+            .filter(|(index, _)| {
+                index
+                    .parse::<usize>()
+                    .map(|index| indices.contains(&index))
+                    .unwrap_or(false)
+            })
+            .map(|(index, item)| LinkItem {
+                index: index.clone(),
+                name: item.name.clone(),
+                uri: item.metadata_link.clone(),
+                extra: item
+                    .extra_files
+                    .as_ref()
+                    .map(|files| files.values().cloned().collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        report.links = Some(check_links(items, args.check_images, checkpoint).await?);
+    }
 
-            for e in errors {
-                println!("- Item {}: {}", e.0, e.1);
+    let report_file = File::create(&args.report)?;
+    serde_json::to_writer_pretty(report_file, &report)?;
+
+    let on_chain_total = report.missing.len() + report.mismatched.len();
+    let links_total = report.links.as_ref().map(Vec::len).unwrap_or_default();
+    let collection_total = report
+        .collection
+        .as_ref()
+        .map(|check| check.errors.len())
+        .unwrap_or_default();
+    let hidden_total = report
+        .hidden_settings
+        .as_ref()
+        .map(|check| check.placeholder_errors.len() + usize::from(!check.hash_matches))
+        .unwrap_or_default();
+
+    if on_chain_total > 0 {
+        if !report.missing.is_empty() {
+            println!("\nMissing items (config line not written): ");
+            for i in &report.missing {
+                println!("- Item {}", i);
             }
-            println!("\nCache updated - re-run `deploy`.");
+        }
 
-            return Err(anyhow!("{} invalid item(s) found.", total));
+        if !report.mismatched.is_empty() {
+            println!("\nMismatched items: ");
+            for mismatch in &report.mismatched {
+                println!("- Item {}: {}", mismatch.index, mismatch.error);
+            }
+        }
+
+        println!(
+            "\n{} missing, {} mismatched.",
+            report.missing.len(),
+            report.mismatched.len()
+        );
+
+        if args.repair {
+            cache.sync_file()?;
+            println!(
+                "\nCache updated - run `deploy` to fix {} item(s).",
+                on_chain_total
+            );
+        } else {
+            println!(
+                "\nRe-run with --repair to reset these items in the cache, then run `deploy`."
+            );
+        }
+    }
+
+    if let Some(links) = &report.links {
+        if !links.is_empty() {
+            println!("\nItems with broken links: ");
+            for link in links {
+                println!("- Item {}: {}", link.index, link.problems.join("; "));
+            }
+        } else {
+            println!("\nAll metadata links are reachable.");
         }
+    }
+
+    if hidden_total > 0 {
+        println!("\nVerify report saved to '{}'.", args.report);
+        return Err(anyhow!("Hidden settings verification failed."));
+    }
+
+    if collection_total > 0 {
+        println!("\nVerify report saved to '{}'.", args.report);
+        return Err(anyhow!("Collection verification failed."));
+    }
 
+    if on_chain_total + links_total > 0 {
+        println!("\nVerify report saved to '{}'.", args.report);
+        return Err(anyhow!(
+            "{} invalid item(s) found.",
+            on_chain_total + links_total
+        ));
+    }
+
+    if hidden {
+        println!("\nHidden settings enabled. You're good to go!");
+    } else {
         let cluster = match get_cluster(program.rpc())? {
             Cluster::Devnet => "devnet",
             Cluster::Mainnet => "mainnet",
         };
 
+        if let Some(description) = &report.partial {
+            println!(
+                "\nItem(s) {} checked out ({}: the other items were not verified).",
+                description,
+                style("partial verification").yellow().bold()
+            );
+        } else {
+            println!(
+                "\nAll items checked out. You're good to go!\nSee your magic hat at: https://www.solaneyes.com/address/{}?cluster={}",
+                cache.program.magic_hat,
+                cluster
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Report of a verify, with the items that failed each check.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub magic_hat: String,
+    pub missing: Vec<String>,
+    pub mismatched: Vec<ItemMismatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<LinkCheck>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_settings: Option<HiddenSettingsCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<CollectionCheck>,
+    /// Problems with the settings of the magic hat.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub settings: Vec<String>,
+    /// Indices verified, when only part of the items were verified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<String>,
+    /// Storage receipts of the verified items, from the cache file.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub receipts: Vec<ItemReceipts>,
+}
+
+/// Result of the collection checks of a magic hat.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionCheck {
+    /// Collection mint set on the magic hat, if any.
+    pub mint: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Result of the checks of a magic hat with hidden settings.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HiddenSettingsCheck {
+    pub placeholder_errors: Vec<String>,
+    pub reveal_hash: String,
+    pub on_chain_hash: String,
+    pub hash_matches: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemMismatch {
+    pub index: String,
+    pub error: String,
+}
+
+/// Starts or resumes (with `--resume`) the checkpoint of the link checks. An
+/// existing checkpoint is only discarded with `--no-resume`.
+pub fn open_checkpoint(
+    args: &VerifyArgs,
+    magic_hat: &str,
+    indices: &[usize],
+) -> Result<VerifyCheckpoint> {
+    let description = format_ranges(indices);
+
+    if args.resume {
+        let checkpoint = VerifyCheckpoint::resume(
+            DEFAULT_VERIFY_STATE,
+            magic_hat,
+            &description,
+            args.check_images,
+        )?;
         println!(
-            "\nAll items checked out. You're good to go!\nSee your magic hat at: https://www.solaneyes.com/address/{}?cluster={}",
-            cache.program.magic_hat,
-            cluster
+            "Resuming interrupted verify: {} link(s) already checked\n",
+            checkpoint.checked_len()
+        );
+        return Ok(checkpoint);
+    }
+
+    if !args.no_resume && VerifyCheckpoint::load(DEFAULT_VERIFY_STATE)?.is_some() {
+        let error = anyhow!(
+            "An interrupted verify was found in '{}': use --resume to continue it or --no-resume to start over",
+            DEFAULT_VERIFY_STATE
         );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    Ok(VerifyCheckpoint::start(
+        DEFAULT_VERIFY_STATE,
+        magic_hat,
+        &description,
+        args.check_images,
+    ))
+}
+
+/// Fetches the account data of the magic hat.
+pub fn fetch_magic_hat(program: &Program, pubkey: &Pubkey) -> Result<(Vec<u8>, MagicHat)> {
+    let data = match program.rpc().get_account_data(pubkey) {
+        Ok(account_data) => account_data,
+        Err(err) => {
+            return Err(VerifyError::FailedToGetAccountData(err.to_string()).into());
+        }
+    };
+    let magic_hat: MagicHat = MagicHat::try_deserialize(&mut data.as_slice())?;
+
+    Ok((data, magic_hat))
+}
+
+/// Checks the collection of the magic hat: its mint must match the expected ones
+/// (by source) and its update authority must be the magic hat authority.
+pub fn check_collection(
+    program: &Program,
+    pubkey: &Pubkey,
+    magic_hat: &MagicHat,
+    expected: &[(&str, Option<String>)],
+) -> Result<CollectionCheck> {
+    let collection_mint = get_collection_pda(pubkey, program)
+        .ok()
+        .map(|(_, collection_pda)| collection_pda.mint);
+    let found = collection_mint
+        .map(|mint| mint.to_string())
+        .unwrap_or_else(|| "not set".to_string());
+
+    let mut errors = Vec::new();
+
+    for (source, expected) in expected {
+        if let Some(expected) = expected {
+            if *expected != found {
+                let error = VerifyError::Mismatch(
+                    "collection".to_string(),
+                    expected.clone(),
+                    found.clone(),
+                );
+                errors.push(format!("{}: {}", source, error));
+            }
+        }
+    }
+
+    if let Some(mint) = collection_mint {
+        let (_, collection_metadata) = get_metadata_pda(&mint, program)?;
+
+        if collection_metadata.update_authority != magic_hat.authority {
+            let error = VerifyError::Mismatch(
+                "collection".to_string(),
+                magic_hat.authority.to_string(),
+                collection_metadata.update_authority.to_string(),
+            );
+            errors.push(format!("update authority: {}", error));
+        }
+    }
+
+    println!("\ncollection: {}", found);
+    for error in &errors {
+        println!("{} {}", style("FAIL").red().bold(), error);
+    }
+
+    Ok(CollectionCheck {
+        mint: collection_mint.map(|mint| mint.to_string()),
+        errors,
+    })
+}
+
+/// Indices of the items to verify: all items, a `--from`/`--to` range or an
+/// `--index` list, checked against the number of items.
+pub fn select_indices(args: &VerifyArgs, num_items: usize) -> Result<Vec<usize>> {
+    let indices: Vec<usize> = if let Some(ref list) = args.index {
+        let mut indices = list
+            .split(',')
+            .map(|index| {
+                index
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("Invalid index '{}' in --index", index.trim()))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    } else if args.from.is_none() && args.to.is_none() {
+        (0..num_items).collect()
     } else {
-        // nothing else todo, there are no config lines in a magic hat
-        // with hidden settings
-        println!("\nHidden settings enabled. You're good to go!");
+        let from = args.from.unwrap_or(0);
+        let to = args.to.unwrap_or_else(|| num_items.saturating_sub(1));
+
+        if from > to {
+            let error = anyhow!("Invalid range: --from {} is after --to {}", from, to);
+            error!("{:?}", error);
+            return Err(error);
+        }
+
+        (from..=to).collect()
+    };
+
+    if let Some(index) = indices.iter().find(|index| **index >= num_items) {
+        let error = anyhow!(
+            "Index {} is out of bounds, there are {} item(s)",
+            index,
+            num_items
+        );
+        error!("{:?}", error);
+        return Err(error);
     }
 
-    Ok(())
+    Ok(indices)
+}
+
+/// Decodes the name and uri of the config lines at the given indices of the magic
+/// hat account data.
+pub fn decode_config_lines(data: &[u8], indices: &[usize]) -> Result<Vec<OnChainItem>> {
+    let num_items = indices.iter().max().map(|i| i + 1).unwrap_or_default();
+    let required = CONFIG_ARRAY_START + STRING_LEN_SIZE + CONFIG_LINE_SIZE * num_items;
+
+    if data.len() < required {
+        return Err(VerifyError::AccountDataTooSmall(num_items, data.len()).into());
+    }
+
+    let decode = |start: usize, length: usize| decode_padded(&data[start..start + length]);
+
+    Ok(indices
+        .iter()
+        .map(|&i| {
+            let line_start = CONFIG_ARRAY_START + STRING_LEN_SIZE + CONFIG_LINE_SIZE * i;
+
+            OnChainItem {
+                name: decode(line_start + CONFIG_NAME_OFFSET, MAX_NAME_LENGTH),
+                uri: decode(line_start + CONFIG_URI_OFFSET, MAX_URI_LENGTH),
+            }
+        })
+        .collect())
+}
+
+/// Decodes a string stored in a fixed-size slot. Only the trailing null padding is
+/// removed, so any other control character is kept and reported as a mismatch. The
+/// bytes are trimmed before decoding to not split a multi-byte character that fills
+/// the slot exactly.
+fn decode_padded(bytes: &[u8]) -> String {
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map(|i| i + 1)
+        .unwrap_or_default();
+
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+/// Removes the trailing null padding of a string.
+pub fn trim_padding(value: &str) -> &str {
+    value.trim_end_matches(char::from(0))
 }
 
 fn items_match(cache_item: &CacheItem, on_chain_item: &OnChainItem) -> Result<()> {
-    if cache_item.name != on_chain_item.name {
+    // values are escaped so invisible characters show up in the output
+    if trim_padding(&cache_item.name) != trim_padding(&on_chain_item.name) {
         return Err(VerifyError::Mismatch(
             "name".to_string(),
-            cache_item.name.clone(),
-            on_chain_item.name.clone(),
+            cache_item.name.escape_debug().to_string(),
+            on_chain_item.name.escape_debug().to_string(),
         )
         .into());
-    } else if cache_item.metadata_link != on_chain_item.uri {
+    } else if trim_padding(&cache_item.metadata_link) != trim_padding(&on_chain_item.uri) {
         return Err(VerifyError::Mismatch(
             "uri".to_string(),
-            cache_item.metadata_link.clone(),
-            on_chain_item.uri.clone(),
+            cache_item.metadata_link.escape_debug().to_string(),
+            on_chain_item.uri.escape_debug().to_string(),
         )
         .into());
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::MAX_NAME_LENGTH;
+
+    /// Stores the value in a fixed-size slot padded with nulls, as on-chain.
+    fn padded(value: &str, size: usize) -> Vec<u8> {
+        let mut slot = value.as_bytes().to_vec();
+        slot.resize(size, 0);
+        slot
+    }
+
+    #[test]
+    fn decode_max_length_name() {
+        let name = "N".repeat(MAX_NAME_LENGTH);
+        let slot = padded(&name, MAX_NAME_LENGTH);
+
+        assert_eq!(decode_padded(&slot), name);
+        assert_eq!(trim_padding(&decode_padded(&slot)), name);
+    }
+
+    #[test]
+    fn decode_name_that_fits_exactly() {
+        let name = "Laddu #1";
+        let slot = padded(name, name.len());
+        assert_eq!(decode_padded(&slot), name);
+
+        // padding is removed but other control characters are kept
+        let slot = padded("Laddu #1\n", MAX_NAME_LENGTH);
+        assert_eq!(decode_padded(&slot), "Laddu #1\n");
+        assert_eq!(trim_padding("Laddu #1\0\0\0"), name);
+    }
+
+    #[test]
+    fn decode_multi_byte_character_at_boundary() {
+        // the two-byte 'é' takes the last two bytes of the slot
+        let name = format!("{}é", "N".repeat(MAX_NAME_LENGTH - 2));
+        assert_eq!(name.len(), MAX_NAME_LENGTH);
+        let slot = padded(&name, MAX_NAME_LENGTH);
+        assert_eq!(decode_padded(&slot), name);
+
+        // a multi-byte character followed by padding is not split
+        let slot = padded("Laddu 🍬", MAX_NAME_LENGTH);
+        assert_eq!(decode_padded(&slot), "Laddu 🍬");
+        assert_eq!(trim_padding(&decode_padded(&slot)), "Laddu 🍬");
+    }
+}
@@ -1,42 +1,212 @@
 pub use anchor_client::{
     solana_sdk::{
+        account::Account,
         commitment_config::{CommitmentConfig, CommitmentLevel},
+        instruction::InstructionError,
+        message::Message,
         native_token::LAMPORTS_PER_SOL,
         pubkey::Pubkey,
         signature::{Keypair, Signature, Signer},
         system_instruction, system_program, sysvar,
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
-    Client, Program,
+    Client, ClientError, Program,
 };
-use console::style;
-use solana_account_decoder::UiAccountEncoding;
+use anchor_lang::prelude::AccountMeta;
+use anchor_lang::Discriminator;
+use chrono::{NaiveDateTime, Utc};
+use console::{style, user_attended};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_client::{
+    client_error::Result as ClientResult,
     rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
 };
 use std::{
+    cmp, fmt, fs,
     io::{stdin, stdout, Write},
     rc::Rc,
     str::FromStr,
+    sync::Arc,
+    thread,
+    time::Duration,
 };
 
 use magic_hat::accounts as nft_accounts;
 use magic_hat::instruction as nft_instruction;
 
+use crate::cli::{ListOutputFormat, ListSort};
 use crate::common::*;
-use crate::magic_hat::MAGIC_HAT_ID;
+use crate::config::LadduConfig;
+use crate::magic_hat::{magic_hat_account_size, MAGIC_HAT_ID};
+use crate::pdas::find_collection_pda;
+use crate::program_errors::{decode_program_error, describe_transaction_error};
 use crate::setup::{laddu_setup, setup_client};
 use crate::utils::*;
 
+/// File with the outcome of a withdraw from a machines file.
+const WITHDRAW_REPORT_FILE: &str = "withdraw-report.json";
+
+/// Anchor error code of a `has_one` constraint violation (the authority of
+/// the magic hat does not match).
+const ANCHOR_CONSTRAINT_HAS_ONE: u32 = 2001;
+
+/// Maximum number of transfers to the destination wallet sent in a single
+/// transaction.
+const FORWARD_BATCH_SIZE: usize = 10;
+
+/// Data slice with the magic hat account header (everything but the config
+/// lines).
+const MAGIC_HAT_HEADER_SLICE: UiDataSliceConfig = UiDataSliceConfig {
+    offset: 0,
+    length: CONFIG_ARRAY_START,
+};
+
+/// Data slice for accounts where only the balance and owner are needed.
+const EMPTY_SLICE: UiDataSliceConfig = UiDataSliceConfig {
+    offset: 0,
+    length: 0,
+};
+
+/// Number of retries of a failed RPC request while scanning the magic hats.
+const SCAN_RETRIES: u32 = 3;
+
+/// Delay (multiplied by the attempt) between retries of a failed RPC request.
+const SCAN_RETRY_DELAY_MS: u64 = 500;
+
+/// Maximum number of accounts fetched by a single `getMultipleAccounts` request.
+const MULTIPLE_ACCOUNTS_PAGE_SIZE: usize = 100;
+
 pub struct WithdrawArgs {
     pub magic_hat: Option<String>,
     pub keypair: Option<String>,
     pub rpc_url: Option<String>,
     pub list: bool,
+    pub dry_run: bool,
+    pub yes: bool,
+    pub machines_file: Option<String>,
+    pub parallel: usize,
+    pub allow_unminted: bool,
+    pub output: ListOutputFormat,
+    pub out: Option<String>,
+    pub sort: Option<ListSort>,
+    pub destination: Option<String>,
+}
+
+/// Outcome of a successful withdraw.
+pub struct WithdrawOutcome {
+    pub signature: Signature,
+    /// Rent returned by closing the collection PDA (zero without a collection).
+    pub collection_lamports: u64,
+}
+
+/// Entry of the magic hats listing export; the items are only present when the
+/// account can be deserialized.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawListItem {
+    pub magic_hat: String,
+    pub lamports: u64,
+    pub sol: f64,
+    pub items_available: Option<u64>,
+    pub items_redeemed: Option<u64>,
+    pub data_length: Option<usize>,
+}
+
+/// Result of the withdraw from a magic hat listed in the machines file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawReportItem {
+    pub magic_hat: String,
+    pub status: WithdrawStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamports: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection_lamports: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forwarded_lamports: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_error: Option<String>,
+}
+
+/// Transfer of the funds reclaimed from a magic hat to the destination wallet.
+pub struct ForwardResult {
+    pub magic_hat: Pubkey,
+    pub lamports: u64,
+    pub result: std::result::Result<Signature, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WithdrawStatus {
+    Withdrawn,
+    Skipped,
+    Failed,
 }
 
-pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
+/// Report of a withdraw from the magic hats of a machines file.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawReport {
+    pub authority: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    pub withdrawn_lamports: u64,
+    pub machines: Vec<WithdrawReportItem>,
+}
+
+pub async fn process_withdraw(args: WithdrawArgs) -> Result<()> {
+    let yes = args.yes || is_non_interactive();
+
+    let destination = match args.destination {
+        Some(ref destination) => match Pubkey::from_str(destination) {
+            Ok(destination) => Some(destination),
+            Err(_) => {
+                let error = anyhow!("Failed to parse destination: {}", destination);
+                error!("{:?}", error);
+                return Err(error);
+            }
+        },
+        None => None,
+    };
+
+    // the machines file is validated before connecting
+    let machines = match args.machines_file {
+        Some(ref machines_file) => Some(read_machines_file(machines_file)?),
+        None => None,
+    };
+
+    // fails before connecting instead of blocking on a prompt that nobody
+    // can answer
+    if !yes && !args.list && !args.dry_run && args.magic_hat.is_none() && !user_attended() {
+        let error = anyhow!(
+            "Withdrawing from all Magic Hats requires confirmation; use --yes or set {} \
+             when running without a terminal",
+            NON_INTERACTIVE_ENV
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    // the listing export is the only thing printed to stdout when it is not
+    // written to a file
+    let export = args.list && (args.output != ListOutputFormat::Text || args.out.is_some());
+
+    if export {
+        let laddu_config = laddu_setup(args.keypair, args.rpc_url)?;
+        let (program, payer) = setup_withdraw(&laddu_config)?;
+
+        return export_machines(&program, payer, args.output, args.out, args.sort);
+    }
+
     // (1) Setting up connection
 
     println!(
@@ -48,10 +218,60 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     let pb = spinner_with_style();
     pb.set_message("Connecting...");
 
-    let (program, payer) = setup_withdraw(args.keypair, args.rpc_url)?;
+    let laddu_config = Arc::new(laddu_setup(args.keypair, args.rpc_url)?);
+    let (program, payer) = setup_withdraw(&laddu_config)?;
 
     pb.finish_with_message("Connected");
 
+    if destination == Some(payer) {
+        let error = anyhow!(
+            "The destination must be different from the authority {}",
+            payer
+        );
+        error!("{:?}", error);
+        return Err(error);
+    }
+
+    if args.dry_run {
+        println!(
+            "\n{} {}Estimating reclaimable funds (dry run)",
+            style("[2/2]").bold().dim(),
+            WITHDRAW_EMOJI
+        );
+
+        // as with --list, all magic hats are considered when listing
+        let targets = if args.list {
+            None
+        } else {
+            match (machines, args.magic_hat) {
+                (Some(machines), _) => Some(machines),
+                (None, Some(magic_hat)) => Some(vec![Pubkey::from_str(&magic_hat)?]),
+                (None, None) => None,
+            }
+        };
+        return dry_run_withdraw(&program, targets, payer, args.allow_unminted);
+    }
+
+    if let Some(machines) = machines {
+        println!(
+            "\n{} {}Retrieving funds",
+            style("[2/2]").bold().dim(),
+            WITHDRAW_EMOJI
+        );
+
+        return withdraw_machines(
+            laddu_config,
+            &program,
+            machines,
+            payer,
+            yes,
+            args.parallel,
+            args.allow_unminted,
+            destination,
+        )
+        .await;
+    }
+
     println!(
         "\n{} {}{} funds",
         style("[2/2]").bold().dim(),
@@ -72,34 +292,43 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
             let pb = spinner_with_style();
             pb.set_message("Draining Magic Hat...");
 
-            do_withdraw(Rc::new(program), magic_hat, payer)?;
+            let account = program.rpc().get_account(&magic_hat).ok();
+
+            let account = match check_withdraw_account(account, &payer, args.allow_unminted) {
+                Ok(account) => account,
+                Err(reason) => {
+                    pb.finish_and_clear();
+                    let error = anyhow!("Magic Hat {} not drained: {}", magic_hat, reason);
+                    error!("{:?}", error);
+                    return Err(error);
+                }
+            };
+
+            let program = Rc::new(program);
+            let outcome = do_withdraw(program.clone(), magic_hat, payer)?;
 
             pb.finish_with_message("Done");
+
+            if outcome.collection_lamports > 0 {
+                println!(
+                    "{} ◎ {}",
+                    style("Collection PDA rent reclaimed:").bold(),
+                    outcome.collection_lamports as f64 / LAMPORTS_PER_SOL as f64
+                );
+            }
+
+            if let Some(destination) = destination {
+                let amounts = vec![(magic_hat, account.lamports + outcome.collection_lamports)];
+                let forwards = forward_funds(&program, &laddu_config, destination, &amounts);
+                print_forward_summary(&forwards, &destination);
+            }
         }
         None => {
-            let config = RpcProgramAccountsConfig {
-                filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
-                    offset: 8, // key
-                    bytes: MemcmpEncodedBytes::Base58(payer.to_string()),
-                    encoding: None,
-                })]),
-                account_config: RpcAccountInfoConfig {
-                    encoding: Some(UiAccountEncoding::Base64),
-                    data_slice: None,
-                    commitment: Some(CommitmentConfig {
-                        commitment: CommitmentLevel::Confirmed,
-                    }),
-                },
-                with_context: None,
-            };
-
             let pb = spinner_with_style();
             pb.set_message("Looking up Magic Hats...");
 
             let program = Rc::new(program);
-            let accounts = program
-                .rpc()
-                .get_program_accounts_with_config(&program.id(), config)?;
+            let accounts = scan_magic_hats(&program, &payer)?;
 
             pb.finish_and_clear();
 
@@ -120,52 +349,55 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
                 // nothing else to do, we just say goodbye
                 println!("\n{}", style("[Completed]").bold().dim());
             } else if args.list {
-                println!("\n{:48} Balance", "Magic Hat ID");
-                println!("{:-<61}", "-");
-
-                for (pubkey, account) in accounts {
-                    println!(
-                        "{:48} {:>12.8}",
-                        pubkey.to_string(),
-                        account.lamports as f64 / LAMPORTS_PER_SOL as f64
-                    );
-                }
+                print_machines(&load_machines(&program, &accounts, args.sort)?);
 
                 println!("\n{}", style("[Completed]").bold().dim());
             } else {
+                print_machines(&load_machines(&program, &accounts, args.sort)?);
+
                 println!("\n+----------------------------------------------+");
                 println!("| WARNING: This will drain all Magic Hats. |");
                 println!("+----------------------------------------------+");
+                print_cluster(&laddu_config.rpc_url);
+
+                let confirmed = if yes {
+                    true
+                } else {
+                    print!("\nContinue? [Y/n] (default \'n\'): ");
+                    stdout().flush().ok();
+
+                    let mut s = String::new();
+                    stdin().read_line(&mut s).expect("Error reading input.");
+
+                    matches!(s.chars().next(), Some('Y'))
+                };
+
+                if confirmed {
+                    let mut balances = HashMap::new();
+                    let mut magic_hats = Vec::new();
+                    let mut skipped = Vec::new();
 
-                print!("\nContinue? [Y/n] (default \'n\'): ");
-                stdout().flush().ok();
-
-                let mut s = String::new();
-                stdin().read_line(&mut s).expect("Error reading input.");
-
-                if let Some('Y') = s.chars().next() {
-                    let pb = progress_bar_with_style(accounts.len() as u64);
-                    let mut not_drained = 0;
-
-                    accounts.iter().for_each(|account| {
-                        let (magic_hat, _account) = account;
-                        do_withdraw(program.clone(), *magic_hat, payer).unwrap_or_else(|e| {
-                            not_drained += 1;
-                            error!("Error: {}", e);
-                        });
-                        pb.inc(1);
-                    });
-
-                    pb.finish();
-
-                    if not_drained > 0 {
-                        println!(
-                            "{}",
-                            style(format!("Could not drain {} Magic Hat(s)", not_drained))
-                                .red()
-                                .bold()
-                                .dim()
-                        );
+                    for (pubkey, account) in accounts {
+                        match check_withdraw_account(Some(account), &payer, args.allow_unminted) {
+                            Ok(account) => {
+                                balances.insert(pubkey, account.lamports);
+                                magic_hats.push(pubkey);
+                            }
+                            Err(reason) => skipped.push((pubkey, reason)),
+                        }
+                    }
+
+                    let results =
+                        withdraw_parallel(Arc::clone(&laddu_config), magic_hats, args.parallel)
+                            .await?;
+                    print_withdraw_summary(&results, &balances);
+                    print_skipped(&skipped);
+
+                    if let Some(destination) = destination {
+                        let amounts = reclaimed_amounts(&results, &balances);
+                        let forwards =
+                            forward_funds(&program, &laddu_config, destination, &amounts);
+                        print_forward_summary(&forwards, &destination);
                     }
                 } else {
                     // there were magic hats to drain, but the user decided
@@ -179,24 +411,1000 @@ pub fn process_withdraw(args: WithdrawArgs) -> Result<()> {
     Ok(())
 }
 
-fn setup_withdraw(keypair: Option<String>, rpc_url: Option<String>) -> Result<(Program, Pubkey)> {
-    let laddu_config = laddu_setup(keypair, rpc_url)?;
-    let client = setup_client(&laddu_config)?;
+fn setup_withdraw(laddu_config: &LadduConfig) -> Result<(Program, Pubkey)> {
+    let client = setup_client(laddu_config)?;
     let program = client.program(MAGIC_HAT_ID);
     let payer = program.payer();
 
     Ok((program, payer))
 }
 
-fn do_withdraw(program: Rc<Program>, magic_hat: Pubkey, payer: Pubkey) -> Result<()> {
-    program
+/// Print the amount that would be reclaimed by withdrawing the funds of a
+/// single magic hat (or all magic hats of the payer) without sending any
+/// transaction.
+fn dry_run_withdraw(
+    program: &Program,
+    targets: Option<Vec<Pubkey>>,
+    payer: Pubkey,
+    allow_unminted: bool,
+) -> Result<()> {
+    let pb = spinner_with_style();
+    pb.set_message("Looking up Magic Hats...");
+
+    let accounts = match targets {
+        Some(targets) => fetch_accounts(program, &targets)?,
+        None => scan_magic_hats(program, &payer)?
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey, Some(account)))
+            .collect(),
+    };
+
+    let fee = estimate_withdraw_fee(program, payer)?;
+
+    let collection_pdas: Vec<Pubkey> = accounts
+        .iter()
+        .map(|(pubkey, _)| find_collection_pda(pubkey).0)
+        .collect();
+    let collection_accounts = fetch_accounts_with_slice(program, &collection_pdas, EMPTY_SLICE)?;
+
+    pb.finish_and_clear();
+
+    let mut total = 0u64;
+    let mut skipped = Vec::new();
+
+    println!(
+        "\n{:48} {:>14} {:>14} {:>14}",
+        "Magic Hat ID", "Balance", "Collection PDA", "Reclaimable"
+    );
+    println!("{:-<93}", "-");
+
+    for ((pubkey, account), (_, collection_account)) in
+        accounts.into_iter().zip(collection_accounts)
+    {
+        let account = match check_withdraw_account(account, &payer, allow_unminted) {
+            Ok(account) => account,
+            Err(reason) => {
+                skipped.push((pubkey, reason));
+                continue;
+            }
+        };
+
+        // magic hats without a collection do not have the account
+        let collection_lamports = match collection_account {
+            Some(collection_account) if collection_account.owner == program.id() => {
+                collection_account.lamports
+            }
+            _ => 0,
+        };
+
+        let reclaimable = (account.lamports + collection_lamports).saturating_sub(fee);
+        total += reclaimable;
+
+        println!(
+            "{:48} {:>14.8} {:>14.8} {:>14.8}",
+            pubkey.to_string(),
+            account.lamports as f64 / LAMPORTS_PER_SOL as f64,
+            collection_lamports as f64 / LAMPORTS_PER_SOL as f64,
+            reclaimable as f64 / LAMPORTS_PER_SOL as f64
+        );
+    }
+
+    println!(
+        "\n{} ◎ {} (after an estimated fee of ◎ {} per withdraw)",
+        style("Total reclaimable:").bold(),
+        total as f64 / LAMPORTS_PER_SOL as f64,
+        fee as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    if !skipped.is_empty() {
+        println!(
+            "\n{}",
+            style(format!("{} Magic Hat(s) would be skipped:", skipped.len()))
+                .yellow()
+                .bold()
+        );
+        for (pubkey, reason) in skipped {
+            println!("  {} ({})", pubkey, reason);
+        }
+    }
+
+    println!("\n{}", style("[Dry run: no funds withdrawn]").bold().dim());
+
+    Ok(())
+}
+
+/// Prints the cluster of the withdraw with the drain warning.
+fn print_cluster(rpc_url: &str) {
+    println!(
+        "{} {} {}",
+        style("Cluster:").bold(),
+        cluster_name(rpc_url),
+        style(format!("({})", rpc_url)).dim()
+    );
+}
+
+/// Withdraw the funds of the magic hats listed in the machines file, writing
+/// the outcome of each one to the withdraw report.
+async fn withdraw_machines(
+    laddu_config: Arc<LadduConfig>,
+    program: &Program,
+    machines: Vec<Pubkey>,
+    payer: Pubkey,
+    yes: bool,
+    parallel: usize,
+    allow_unminted: bool,
+    destination: Option<Pubkey>,
+) -> Result<()> {
+    let pb = spinner_with_style();
+    pb.set_message("Looking up Magic Hats...");
+
+    let accounts = fetch_accounts(program, &machines)?;
+
+    pb.finish_and_clear();
+
+    println!("Found {} Magic Hat(s) in the machines file", machines.len());
+
+    println!("\n+-------------------------------------------------+");
+    println!("| WARNING: This will drain the listed Magic Hats. |");
+    println!("+-------------------------------------------------+");
+    print_cluster(&laddu_config.rpc_url);
+
+    let confirmed = if yes {
+        true
+    } else {
+        print!("\nContinue? [Y/n] (default \'n\'): ");
+        stdout().flush().ok();
+
+        let mut s = String::new();
+        stdin().read_line(&mut s).expect("Error reading input.");
+
+        matches!(s.chars().next(), Some('Y'))
+    };
+
+    if !confirmed {
+        println!("\n{}", style("Withdraw aborted.").red().bold().dim());
+        return Ok(());
+    }
+
+    // the report follows the order of the file
+    let order: Vec<Pubkey> = accounts.iter().map(|(magic_hat, _)| *magic_hat).collect();
+
+    let mut machines = Vec::with_capacity(accounts.len());
+    let mut balances = HashMap::new();
+    let mut skipped = HashMap::new();
+
+    for (magic_hat, account) in accounts {
+        match check_withdraw_account(account, &payer, allow_unminted) {
+            Ok(account) => {
+                balances.insert(magic_hat, account.lamports);
+                machines.push(magic_hat);
+            }
+            Err(reason) => {
+                skipped.insert(magic_hat, reason);
+            }
+        }
+    }
+
+    let results = withdraw_parallel(Arc::clone(&laddu_config), machines, parallel).await?;
+    print_withdraw_summary(&results, &balances);
+
+    let mut forwards: HashMap<Pubkey, ForwardResult> = match destination {
+        Some(destination) => {
+            let amounts = reclaimed_amounts(&results, &balances);
+            let forwards = forward_funds(program, &laddu_config, destination, &amounts);
+            print_forward_summary(&forwards, &destination);
+
+            forwards
+                .into_iter()
+                .map(|forward| (forward.magic_hat, forward))
+                .collect()
+        }
+        None => HashMap::new(),
+    };
+
+    let mut results: HashMap<Pubkey, Result<WithdrawOutcome>> = results.into_iter().collect();
+    let mut report = WithdrawReport {
+        authority: payer.to_string(),
+        destination: destination.map(|destination| destination.to_string()),
+        withdrawn_lamports: 0,
+        machines: Vec::with_capacity(order.len()),
+    };
+
+    for magic_hat in order {
+        let mut item = WithdrawReportItem {
+            magic_hat: magic_hat.to_string(),
+            status: WithdrawStatus::Skipped,
+            lamports: None,
+            collection_lamports: None,
+            signature: None,
+            reason: skipped.remove(&magic_hat),
+            forwarded_lamports: None,
+            forward_signature: None,
+            forward_error: None,
+        };
+
+        if let Some(forward) = forwards.remove(&magic_hat) {
+            match forward.result {
+                Ok(signature) => {
+                    item.forwarded_lamports = Some(forward.lamports);
+                    item.forward_signature = Some(signature.to_string());
+                }
+                Err(err) => item.forward_error = Some(err),
+            }
+        }
+
+        match results.remove(&magic_hat) {
+            Some(Ok(outcome)) => {
+                let lamports = balances.get(&magic_hat).copied().unwrap_or_default();
+                report.withdrawn_lamports += lamports + outcome.collection_lamports;
+                item.status = WithdrawStatus::Withdrawn;
+                item.lamports = Some(lamports);
+                item.collection_lamports = Some(outcome.collection_lamports);
+                item.signature = Some(outcome.signature.to_string());
+            }
+            Some(Err(err)) => {
+                item.status = WithdrawStatus::Failed;
+                item.reason = Some(describe_withdraw_error(&err));
+            }
+            None => (),
+        }
+
+        report.machines.push(item);
+    }
+
+    fs::write(WITHDRAW_REPORT_FILE, serde_json::to_string_pretty(&report)?)?;
+
+    let skipped = report
+        .machines
+        .iter()
+        .filter(|item| matches!(item.status, WithdrawStatus::Skipped))
+        .count();
+
+    if skipped > 0 {
+        println!(
+            "{}",
+            style(format!("Skipped {} Magic Hat(s)", skipped))
+                .yellow()
+                .bold()
+        );
+    }
+
+    println!(
+        "\n{} {}",
+        style("Report written to").bold(),
+        style(WITHDRAW_REPORT_FILE).bold()
+    );
+
+    Ok(())
+}
+
+/// Withdraw the funds of the magic hats, running up to `parallel` withdraws
+/// concurrently. Returns the result of each withdraw in the original order.
+async fn withdraw_parallel(
+    laddu_config: Arc<LadduConfig>,
+    magic_hats: Vec<Pubkey>,
+    parallel: usize,
+) -> Result<Vec<(Pubkey, Result<WithdrawOutcome>)>> {
+    let parallel = cmp::max(1, cmp::min(parallel, PARALLEL_LIMIT));
+    let pb = progress_bar_with_style(magic_hats.len() as u64);
+
+    let mut results = stream::iter(magic_hats.into_iter().enumerate())
+        .map(|(index, magic_hat)| {
+            let config = Arc::clone(&laddu_config);
+
+            tokio::task::spawn_blocking(move || {
+                let result = setup_withdraw(&config)
+                    .and_then(|(program, payer)| do_withdraw(Rc::new(program), magic_hat, payer));
+                (index, magic_hat, result)
+            })
+        })
+        .buffer_unordered(parallel)
+        .inspect(|_| pb.inc(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    pb.finish();
+
+    results.sort_by_key(|(index, _, _)| *index);
+
+    Ok(results
+        .into_iter()
+        .map(|(_, magic_hat, result)| (magic_hat, result))
+        .collect())
+}
+
+/// Print the number of drained and failed magic hats, the total reclaimed
+/// (based on the balances before the withdraw, including the rent of closed
+/// collection PDAs) and a table of the failures.
+fn print_withdraw_summary(
+    results: &[(Pubkey, Result<WithdrawOutcome>)],
+    balances: &HashMap<Pubkey, u64>,
+) {
+    let mut reclaimed = 0;
+    let mut collections = Vec::new();
+    let mut failures = Vec::new();
+
+    for (magic_hat, result) in results {
+        match result {
+            Ok(outcome) => {
+                reclaimed += balances.get(magic_hat).copied().unwrap_or_default();
+
+                if outcome.collection_lamports > 0 {
+                    reclaimed += outcome.collection_lamports;
+                    collections.push((magic_hat, outcome.collection_lamports));
+                }
+            }
+            Err(err) => {
+                error!("Failed to withdraw from {}: {:?}", magic_hat, err);
+                failures.push((magic_hat, describe_withdraw_error(err)));
+            }
+        }
+    }
+
+    println!(
+        "\n{} Magic Hat(s) drained, {} failed, ◎ {} reclaimed",
+        results.len() - failures.len(),
+        failures.len(),
+        reclaimed as f64 / LAMPORTS_PER_SOL as f64
+    );
+
+    if !collections.is_empty() {
+        println!("\n{:48} {:>14}", "Magic Hat ID", "Collection PDA");
+        println!("{:-<63}", "-");
+
+        for (magic_hat, lamports) in collections {
+            println!(
+                "{:48} {:>14.8}",
+                magic_hat.to_string(),
+                lamports as f64 / LAMPORTS_PER_SOL as f64
+            );
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\n{:48} Error", "Magic Hat ID");
+        println!("{:-<78}", "-");
+
+        for (magic_hat, reason) in failures {
+            println!("{:48} {}", magic_hat.to_string(), style(reason).red());
+        }
+    }
+}
+
+/// Amount reclaimed from each drained magic hat: its balance before the
+/// withdraw plus the rent of the collection PDA.
+fn reclaimed_amounts(
+    results: &[(Pubkey, Result<WithdrawOutcome>)],
+    balances: &HashMap<Pubkey, u64>,
+) -> Vec<(Pubkey, u64)> {
+    results
+        .iter()
+        .filter_map(|(magic_hat, result)| {
+            result.as_ref().ok().map(|outcome| {
+                let lamports = balances.get(magic_hat).copied().unwrap_or_default();
+                (*magic_hat, lamports + outcome.collection_lamports)
+            })
+        })
+        .collect()
+}
+
+/// Transfer the amounts reclaimed from the magic hats to the destination,
+/// batching up to `FORWARD_BATCH_SIZE` transfers in each transaction.
+fn forward_funds(
+    program: &Program,
+    laddu_config: &LadduConfig,
+    destination: Pubkey,
+    amounts: &[(Pubkey, u64)],
+) -> Vec<ForwardResult> {
+    let payer = laddu_config.keypair.pubkey();
+    let rpc = program.rpc();
+    let mut forwards = Vec::with_capacity(amounts.len());
+
+    let pb = progress_bar_with_style(amounts.len() as u64);
+    pb.set_message("Forwarding funds ");
+
+    for batch in amounts.chunks(FORWARD_BATCH_SIZE) {
+        let instructions: Vec<_> = batch
+            .iter()
+            .map(|(_, lamports)| system_instruction::transfer(&payer, &destination, *lamports))
+            .collect();
+
+        let result = rpc
+            .get_latest_blockhash()
+            .and_then(|blockhash| {
+                let tx = Transaction::new_signed_with_payer(
+                    &instructions,
+                    Some(&payer),
+                    &[&laddu_config.keypair],
+                    blockhash,
+                );
+                rpc.send_and_confirm_transaction(&tx)
+            })
+            .map_err(|err| {
+                error!("Failed to forward funds to {}: {:?}", destination, err);
+                err.to_string()
+            });
+
+        forwards.extend(batch.iter().map(|(magic_hat, lamports)| ForwardResult {
+            magic_hat: *magic_hat,
+            lamports: *lamports,
+            result: result.clone(),
+        }));
+
+        pb.inc(batch.len() as u64);
+    }
+
+    pb.finish_and_clear();
+
+    forwards
+}
+
+/// Print the amount forwarded to the destination and the transfers that
+/// failed (to be retried).
+fn print_forward_summary(forwards: &[ForwardResult], destination: &Pubkey) {
+    let forwarded: u64 = forwards
+        .iter()
+        .filter(|forward| forward.result.is_ok())
+        .map(|forward| forward.lamports)
+        .sum();
+    let failed: Vec<&ForwardResult> = forwards
+        .iter()
+        .filter(|forward| forward.result.is_err())
+        .collect();
+
+    println!(
+        "\n◎ {} forwarded to {}",
+        forwarded as f64 / LAMPORTS_PER_SOL as f64,
+        destination
+    );
+
+    if !failed.is_empty() {
+        println!(
+            "\n{}",
+            style(format!("{} transfer(s) failed:", failed.len()))
+                .red()
+                .bold()
+        );
+        println!("{:48} {:>14} Error", "Magic Hat ID", "Amount");
+        println!("{:-<78}", "-");
+
+        for forward in failed {
+            println!(
+                "{:48} {:>14.8} {}",
+                forward.magic_hat.to_string(),
+                forward.lamports as f64 / LAMPORTS_PER_SOL as f64,
+                style(forward.result.as_ref().err().cloned().unwrap_or_default()).red()
+            );
+        }
+    }
+}
+
+/// Describe why a withdraw failed.
+fn describe_withdraw_error(err: &anyhow::Error) -> String {
+    let rpc_error = match err.downcast_ref::<ClientError>() {
+        Some(ClientError::SolanaClientError(rpc_error)) => rpc_error,
+        _ => return err.to_string(),
+    };
+
+    match rpc_error.get_transaction_error() {
+        Some(TransactionError::AccountInUse) => "account in use".to_string(),
+        Some(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(ANCHOR_CONSTRAINT_HAS_ONE),
+        )) => "authority mismatch".to_string(),
+        Some(transaction_error) => describe_transaction_error(&transaction_error, &[])
+            .unwrap_or_else(|| format!("transaction error: {}", transaction_error)),
+        None => format!("RPC error: {}", rpc_error),
+    }
+}
+
+/// Read the magic hat ids of a machines file, either a JSON array or one id
+/// per line.
+fn read_machines_file(path: &str) -> Result<Vec<Pubkey>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            let error = anyhow!("Failed to read machines file {}: {}", path, err);
+            error!("{:?}", error);
+            return Err(error);
+        }
+    };
+
+    let ids: Vec<String> = if contents.trim_start().starts_with('[') {
+        serde_json::from_str(&contents)
+            .map_err(|err| anyhow!("Failed to parse machines file {}: {}", path, err))?
+    } else {
+        contents
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    };
+
+    let mut machines: Vec<Pubkey> = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        let magic_hat = match Pubkey::from_str(&id) {
+            Ok(magic_hat) => magic_hat,
+            Err(_) => {
+                let error = anyhow!("Failed to parse Magic Hat id in {}: {}", path, id);
+                error!("{:?}", error);
+                return Err(error);
+            }
+        };
+
+        if machines.contains(&magic_hat) {
+            warn!("Duplicated Magic Hat id in {}: {}", path, magic_hat);
+        } else {
+            machines.push(magic_hat);
+        }
+    }
+
+    if machines.is_empty() {
+        return Err(anyhow!(
+            "Machines file {} does not contain any Magic Hat",
+            path
+        ));
+    }
+
+    Ok(machines)
+}
+
+/// Fetch the accounts (in pages), keeping their order. Only the header of the
+/// magic hat account is requested, which is all the withdraw needs.
+fn fetch_accounts(
+    program: &Program,
+    magic_hats: &[Pubkey],
+) -> Result<Vec<(Pubkey, Option<Account>)>> {
+    fetch_accounts_with_slice(program, magic_hats, MAGIC_HAT_HEADER_SLICE)
+}
+
+fn fetch_accounts_with_slice(
+    program: &Program,
+    pubkeys: &[Pubkey],
+    data_slice: UiDataSliceConfig,
+) -> Result<Vec<(Pubkey, Option<Account>)>> {
+    let mut accounts = Vec::with_capacity(pubkeys.len());
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        data_slice: Some(data_slice),
+        commitment: Some(CommitmentConfig {
+            commitment: CommitmentLevel::Confirmed,
+        }),
+    };
+
+    for chunk in pubkeys.chunks(MULTIPLE_ACCOUNTS_PAGE_SIZE) {
+        let page = with_retry(|| {
+            program
+                .rpc()
+                .get_multiple_accounts_with_config(chunk, config.clone())
+        })?;
+        accounts.extend(chunk.iter().cloned().zip(page.value));
+    }
+
+    Ok(accounts)
+}
+
+/// Find the magic hats of the authority.
+///
+/// The scan only returns the addresses (and balances), so the response stays
+/// small for authorities with thousands of magic hats; the account headers are
+/// then fetched in pages, retrying failed requests.
+fn scan_magic_hats(program: &Program, authority: &Pubkey) -> Result<Vec<(Pubkey, Account)>> {
+    let pubkeys: Vec<Pubkey> = with_retry(|| {
+        program
+            .rpc()
+            .get_program_accounts_with_config(&program.id(), authority_filter(authority))
+    })?
+    .into_iter()
+    .map(|(pubkey, _)| pubkey)
+    .collect();
+
+    Ok(fetch_accounts(program, &pubkeys)?
+        .into_iter()
+        // accounts closed between the scan and the fetch are ignored
+        .filter_map(|(pubkey, account)| account.map(|account| (pubkey, account)))
+        .collect())
+}
+
+/// Run an RPC request, retrying it with a linear backoff when it fails.
+fn with_retry<T, F>(request: F) -> Result<T>
+where
+    F: Fn() -> ClientResult<T>,
+{
+    let mut attempt = 0;
+
+    loop {
+        match request() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < SCAN_RETRIES => {
+                attempt += 1;
+                warn!("RPC request failed (attempt {}): {}", attempt, err);
+                thread::sleep(Duration::from_millis(SCAN_RETRY_DELAY_MS * attempt as u64));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Check that the funds of the magic hat account can be withdrawn by the payer,
+/// returning the reason why it would be skipped otherwise.
+///
+/// Withdrawing closes the magic hat, so unless `allow_unminted` is set only
+/// sold out magic hats are drained.
+fn check_withdraw_account(
+    account: Option<Account>,
+    payer: &Pubkey,
+    allow_unminted: bool,
+) -> std::result::Result<Account, String> {
+    let account = account.ok_or_else(|| "account not found".to_string())?;
+
+    // the same checks that would make the withdraw fail
+    let state = match MagicHat::try_deserialize(&mut account.data.as_slice()) {
+        Ok(state) if state.authority != *payer => {
+            return Err(format!("authority is {}", state.authority))
+        }
+        Ok(state) => state,
+        Err(_) => return Err("failed to deserialize the account".to_string()),
+    };
+
+    let status = MachineStatus::of(&state, Utc::now().timestamp());
+
+    if !allow_unminted && status != MachineStatus::SoldOut {
+        return Err(format!(
+            "{} with {} unminted item(s), use --allow-unminted to drain it",
+            status,
+            state
+                .data
+                .items_available
+                .saturating_sub(state.items_redeemed)
+        ));
+    }
+
+    Ok(account)
+}
+
+/// Mint status of a magic hat.
+#[derive(Debug, PartialEq)]
+enum MachineStatus {
+    SoldOut,
+    Live,
+    NotStarted,
+}
+
+impl MachineStatus {
+    fn of(state: &MagicHat, now: i64) -> Self {
+        if state.items_redeemed >= state.data.items_available {
+            MachineStatus::SoldOut
+        } else if matches!(state.data.go_live_date, Some(date) if date <= now) {
+            MachineStatus::Live
+        } else {
+            MachineStatus::NotStarted
+        }
+    }
+}
+
+impl fmt::Display for MachineStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self {
+            MachineStatus::SoldOut => "sold out",
+            MachineStatus::Live => "live",
+            MachineStatus::NotStarted => "not started",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// Write the listing of the magic hats of the payer as text, JSON or CSV, to
+/// stdout or to a file.
+fn export_machines(
+    program: &Program,
+    payer: Pubkey,
+    format: ListOutputFormat,
+    out: Option<String>,
+    sort: Option<ListSort>,
+) -> Result<()> {
+    let pb = spinner_with_style();
+    pb.set_message("Looking up Magic Hats...");
+
+    let accounts = scan_magic_hats(program, &payer)?;
+
+    let machines = load_machines(program, &accounts, sort)?;
+
+    pb.finish_and_clear();
+
+    let items: Vec<WithdrawListItem> = machines
+        .iter()
+        .map(|machine| WithdrawListItem {
+            magic_hat: machine.pubkey.to_string(),
+            lamports: machine.lamports,
+            sol: machine.lamports as f64 / LAMPORTS_PER_SOL as f64,
+            items_available: machine
+                .state
+                .as_ref()
+                .map(|state| state.data.items_available),
+            items_redeemed: machine.state.as_ref().map(|state| state.items_redeemed),
+            data_length: machine.data_length,
+        })
+        .collect();
+
+    let contents = match format {
+        ListOutputFormat::Json => serde_json::to_string_pretty(&items)?,
+        ListOutputFormat::Csv => {
+            let mut lines = vec![
+                "magic_hat,lamports,sol,items_available,items_redeemed,data_length".to_string(),
+            ];
+            lines.extend(items.iter().map(|item| {
+                format!(
+                    "{},{},{},{},{},{}",
+                    item.magic_hat,
+                    item.lamports,
+                    item.sol,
+                    format_optional(item.items_available),
+                    format_optional(item.items_redeemed),
+                    format_optional(item.data_length)
+                )
+            }));
+            lines.join("\n")
+        }
+        ListOutputFormat::Text => {
+            let mut lines = vec![
+                format!(
+                    "{:48} {:>14} {:>10} {:>10} {:>8}",
+                    "Magic Hat ID", "Balance", "Available", "Redeemed", "Length"
+                ),
+                format!("{:-<94}", "-"),
+            ];
+            lines.extend(items.iter().map(|item| {
+                format!(
+                    "{:48} {:>14.8} {:>10} {:>10} {:>8}",
+                    item.magic_hat,
+                    item.sol,
+                    format_optional(item.items_available),
+                    format_optional(item.items_redeemed),
+                    format_optional(item.data_length)
+                )
+            }));
+            lines.join("\n")
+        }
+    };
+
+    match out {
+        Some(out) => {
+            fs::write(&out, contents + "\n")?;
+            println!(
+                "{} Magic Hat(s) found, listing written to {}",
+                style(items.len()).bold(),
+                style(&out).bold()
+            );
+        }
+        None => println!("{}", contents),
+    }
+
+    Ok(())
+}
+
+fn format_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Balance and state of a magic hat in the listing.
+struct MachineInfo {
+    pubkey: Pubkey,
+    lamports: u64,
+    data_length: Option<usize>,
+    /// `None` when the account cannot be deserialized.
+    state: Option<MagicHat>,
+    collection: bool,
+}
+
+/// Deserialize the magic hat accounts and look up their collection PDAs,
+/// sorting the result by the requested column.
+fn load_machines(
+    program: &Program,
+    accounts: &[(Pubkey, Account)],
+    sort: Option<ListSort>,
+) -> Result<Vec<MachineInfo>> {
+    let collection_pdas: Vec<Pubkey> = accounts
+        .iter()
+        .map(|(pubkey, _)| find_collection_pda(pubkey).0)
+        .collect();
+    let collection_accounts = fetch_accounts_with_slice(program, &collection_pdas, EMPTY_SLICE)?;
+
+    let mut machines: Vec<MachineInfo> = accounts
+        .iter()
+        .zip(collection_accounts)
+        .map(|((pubkey, account), (_, collection_account))| {
+            let state = MagicHat::try_deserialize(&mut account.data.as_slice()).ok();
+
+            MachineInfo {
+                pubkey: *pubkey,
+                lamports: account.lamports,
+                // only the header is fetched, so the size is derived from the data
+                data_length: state
+                    .as_ref()
+                    .map(|state| magic_hat_account_size(&state.data)),
+                state,
+                collection: matches!(collection_account, Some(account) if account.owner == program.id()),
+            }
+        })
+        .collect();
+
+    // balance and redeemed are sorted from the largest; the go live date from
+    // the earliest, with the magic hats without a date last
+    match sort {
+        Some(ListSort::Balance) => machines.sort_by(|a, b| b.lamports.cmp(&a.lamports)),
+        Some(ListSort::Redeemed) => machines.sort_by_key(|machine| {
+            cmp::Reverse(machine.state.as_ref().map(|state| state.items_redeemed))
+        }),
+        Some(ListSort::Date) => machines.sort_by_key(|machine| {
+            match machine
+                .state
+                .as_ref()
+                .and_then(|state| state.data.go_live_date)
+            {
+                Some(date) => (0, date),
+                None => (1, 0),
+            }
+        }),
+        None => (),
+    }
+
+    Ok(machines)
+}
+
+/// Print the balance, state and mint status of the magic hats.
+fn print_machines(machines: &[MachineInfo]) {
+    let now = Utc::now().timestamp();
+
+    println!(
+        "\n{:48} {:>12} {:>13} {:20} {:10} Status",
+        "Magic Hat ID", "Balance", "Redeemed", "Go live date", "Collection"
+    );
+    println!("{:-<118}", "-");
+
+    for machine in machines {
+        let (items, go_live_date, status) = match machine.state {
+            Some(ref state) => {
+                let status = MachineStatus::of(state, now);
+                (
+                    format!("{}/{}", state.items_redeemed, state.data.items_available),
+                    format_go_live_date(state.data.go_live_date),
+                    match status {
+                        MachineStatus::SoldOut => style(status.to_string()).green(),
+                        _ => style(status.to_string()).yellow(),
+                    },
+                )
+            }
+            None => (
+                "n/a".to_string(),
+                "n/a".to_string(),
+                style("n/a".to_string()).red(),
+            ),
+        };
+
+        println!(
+            "{:48} {:>12.8} {:>13} {:20} {:10} {}",
+            machine.pubkey.to_string(),
+            machine.lamports as f64 / LAMPORTS_PER_SOL as f64,
+            items,
+            go_live_date,
+            if machine.collection { "yes" } else { "no" },
+            status
+        );
+    }
+}
+
+fn format_go_live_date(date: Option<i64>) -> String {
+    match date {
+        Some(date) => NaiveDateTime::from_timestamp(date, 0)
+            .format("%Y-%m-%d %H:%M UTC")
+            .to_string(),
+        None => "none".to_string(),
+    }
+}
+
+/// Print the magic hats that were not drained and why.
+fn print_skipped(skipped: &[(Pubkey, String)]) {
+    if skipped.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{}",
+        style(format!("Skipped {} Magic Hat(s):", skipped.len()))
+            .yellow()
+            .bold()
+    );
+    for (pubkey, reason) in skipped {
+        println!("  {} ({})", pubkey, reason);
+    }
+}
+
+/// Estimate the fee of a withdraw transaction.
+fn estimate_withdraw_fee(program: &Program, payer: Pubkey) -> Result<u64> {
+    // any address works to build the message, the fee only depends on the
+    // signatures
+    let instructions = program
         .request()
         .accounts(nft_accounts::WithdrawFunds {
-            magic_hat,
+            magic_hat: Pubkey::default(),
             authority: payer,
         })
         .args(nft_instruction::WithdrawFunds {})
-        .send()?;
+        .instructions()?;
 
-    Ok(())
+    let rpc = program.rpc();
+    let mut message = Message::new(&instructions, Some(&payer));
+    message.recent_blockhash = rpc.get_latest_blockhash()?;
+
+    Ok(rpc.get_fee_for_message(&message)?)
+}
+
+/// Filter for the magic hat accounts of an authority, requesting no data.
+fn authority_filter(authority: &Pubkey) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(vec![
+            // only magic hat accounts, not other accounts of the program
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 0,
+                bytes: MemcmpEncodedBytes::Base58(
+                    bs58::encode(MagicHat::discriminator()).into_string(),
+                ),
+                encoding: None,
+            }),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 8, // key
+                bytes: MemcmpEncodedBytes::Base58(authority.to_string()),
+                encoding: None,
+            }),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: 0,
+            }),
+            commitment: Some(CommitmentConfig {
+                commitment: CommitmentLevel::Confirmed,
+            }),
+        },
+        with_context: None,
+    }
+}
+
+/// Withdraw the funds of the magic hat, also closing its collection PDA (if
+/// any) so that its rent is returned to the authority.
+fn do_withdraw(program: Rc<Program>, magic_hat: Pubkey, payer: Pubkey) -> Result<WithdrawOutcome> {
+    let collection_pda = find_collection_pda(&magic_hat).0;
+    // magic hats without a collection do not have the account
+    let collection_lamports = match program.rpc().get_account(&collection_pda) {
+        Ok(account) if account.owner == program.id() => account.lamports,
+        _ => 0,
+    };
+
+    let mut builder = program
+        .request()
+        .accounts(nft_accounts::WithdrawFunds {
+            magic_hat,
+            authority: payer,
+        })
+        .args(nft_instruction::WithdrawFunds {});
+
+    if collection_lamports > 0 {
+        builder = builder.accounts(AccountMeta {
+            pubkey: collection_pda,
+            is_signer: false,
+            is_writable: true,
+        });
+    }
+
+    let signature = builder.send().map_err(decode_program_error)?;
+
+    Ok(WithdrawOutcome {
+        signature,
+        collection_lamports,
+    })
 }